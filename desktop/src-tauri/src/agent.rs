@@ -3,18 +3,88 @@
 //! Handles communication between the Tauri desktop app and the Python agent runtime
 //! via localhost HTTP.
 
-use reqwest::Client;
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Certificate, Client, RequestBuilder, Response, StatusCode};
+use semver::Version;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
-use tracing::{error, info, warn};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, RwLock, Semaphore};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
+use crate::agent_process::AgentProcessState;
+use crate::attachment::{self, Attachment};
+use crate::audio::{self, AudioFormat};
+use crate::audit_log::AuditLogger;
+use crate::commands;
+use crate::config::{AgentConfig, SlovoConfig};
 use crate::error::SlovoError;
+use crate::events::{emit_agent_status, AgentStatus, MonitorHeartbeat};
+use crate::health_history::HealthSnapshot;
+use crate::metrics::Metrics;
+use crate::middleware::{self, RequestMiddleware};
+use crate::notify::{notify, ConnectivityNotifier};
+use crate::speech::SpeechPlayback;
+use crate::state::AppState;
+use crate::wake_word::WakeWordDetector;
+use crate::ws::{self, AgentStream};
 
 /// Agent runtime configuration
 const AGENT_HOST: &str = "127.0.0.1";
 const AGENT_PORT: u16 = 8741;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Bounds for a request timeout chosen by the user, in seconds
+pub const MIN_REQUEST_TIMEOUT_SECS: u64 = 1;
+pub const MAX_REQUEST_TIMEOUT_SECS: u64 = 600;
 const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Bounds for a health check interval chosen by the user, in seconds
+pub const MIN_HEALTH_CHECK_INTERVAL_SECS: u64 = 1;
+pub const MAX_HEALTH_CHECK_INTERVAL_SECS: u64 = 300;
+/// Faster polling while waiting for the agent to come up, so it is seen promptly
+const STARTUP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+const MAX_MESSAGE_CHARS: usize = 32_000;
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+/// Model loading can take far longer than an ordinary request
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(180);
+/// Attempts made for a chat request that carries an idempotency key
+const MAX_SEND_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const REQUEST_ID_HEADER: &str = "X-Request-ID";
+/// Longest `Retry-After` we wait out before handing the error to the caller
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+/// How long the agent gets to send its last transcripts after the audio ends
+const TRANSCRIBE_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Oldest agent runtime version this desktop build can talk to
+pub const MIN_AGENT_VERSION: &str = "0.1.0";
+
+/// Capability the agent reports when it offers speech-to-text
+pub const CAPABILITY_TRANSCRIBE: &str = "transcribe";
+/// Capability an agent advertises when it can answer questions about images
+pub const CAPABILITY_VISION: &str = "vision";
+/// Capability the agent reports when it offers text-to-speech
+pub const CAPABILITY_TTS: &str = "tts";
+/// Capability the agent reports when it can spot a wake word in short clips
+pub const CAPABILITY_WAKE_WORD: &str = "wake_word";
+/// Capability the agent reports when it accepts attachments as multipart form parts
+pub const CAPABILITY_MULTIPART_ATTACHMENTS: &str = "attachments_multipart";
+
+/// Fallback client for code running without Tauri's managed state
+///
+/// Set by [`init_default_client`] during startup; see [`agent_client`].
+static DEFAULT_CLIENT: OnceLock<AgentClient> = OnceLock::new();
 
 /// Agent health status
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,55 +92,679 @@ pub struct AgentHealth {
     pub status: String,
     pub version: String,
     pub uptime: f64,
+    /// Features this agent offers, e.g. `"chat"`, `"transcribe"`, `"vision"`
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl AgentHealth {
+    /// Whether the agent advertises a capability
+    ///
+    /// Agents that predate capability reporting send an empty list and are
+    /// assumed to support everything.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.is_empty() || self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+/// Features reported by the agent's capabilities endpoint
+///
+/// The default is the conservative set assumed for agents without the endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capabilities {
+    pub streaming: bool,
+    pub tts: bool,
+    pub transcribe: bool,
+    pub attachments: bool,
+    /// Largest context the agent accepts, in tokens, when it says
+    pub max_context: Option<u32>,
+}
+
+/// The model answering on the agent's side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub provider: String,
+    /// Context window in tokens, when the agent reports it
+    #[serde(default)]
+    pub context_window: Option<u32>,
+    /// Generation parameters such as temperature
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Agent metadata shown in the frontend status bar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgentInfoResponse {
+    pub version: String,
+    pub uptime_secs: f64,
+    pub capabilities: Vec<String>,
+    pub status: String,
+    /// Share of recent health checks that reached the agent, from 0 to 100
+    pub uptime_percentage: Option<f64>,
+}
+
+impl AgentInfoResponse {
+    /// Whether `other` matches in everything but `uptime_secs`, which moves with every check
+    pub fn same_besides_uptime(&self, other: &Self) -> bool {
+        *self == Self { uptime_secs: self.uptime_secs, ..other.clone() }
+    }
+}
+
+impl From<AgentHealth> for AgentInfoResponse {
+    fn from(health: AgentHealth) -> Self {
+        Self {
+            version: health.version,
+            uptime_secs: health.uptime,
+            capabilities: health.capabilities,
+            status: health.status,
+            uptime_percentage: None,
+        }
+    }
 }
 
 /// Chat request to the agent
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ChatRequest {
     pub message: String,
     pub conversation_id: Option<String>,
+    pub include_reasoning: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+    /// Overrides the agent's built-in system prompt when set; an empty prompt restores it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Sent as the `Idempotency-Key` header rather than in the body
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+    /// Sent as the `X-Request-ID` header rather than in the body
+    #[serde(skip)]
+    pub correlation_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// The message was answered before; the agent should vary its reply
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub regenerate: bool,
+    /// Answer with the conversation as context, but keep the exchange out of its history
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ephemeral: bool,
+}
+
+impl ChatRequest {
+    /// Build a request carrying the given generation options
+    pub fn new(message: impl Into<String>, conversation_id: Option<String>, options: &ChatOptions) -> Self {
+        Self {
+            message: message.into(),
+            conversation_id,
+            include_reasoning: options.include_reasoning,
+            reasoning_effort: options.reasoning_effort.clone(),
+            system_prompt: options.system_prompt.clone(),
+            idempotency_key: options.idempotency_key.clone(),
+            correlation_id: options.correlation_id.clone(),
+            attachments: Vec::new(),
+            regenerate: options.regenerate,
+            ephemeral: options.ephemeral,
+        }
+    }
+}
+
+/// Per-request options controlling how the agent answers
+#[derive(Debug, Clone, Default)]
+pub struct ChatOptions {
+    /// Ask the agent to return its reasoning alongside the response
+    pub include_reasoning: bool,
+    /// Reasoning effort hint, e.g. `"low"` or `"high"`
+    pub reasoning_effort: Option<String>,
+    /// Conversation-specific system prompt
+    pub system_prompt: Option<String>,
+    /// Lets the agent dedupe retries of one user-initiated send
+    pub idempotency_key: Option<String>,
+    /// Ties our log lines for one user send to the agent's logs for it
+    pub correlation_id: Option<String>,
+    /// Aborts the request, including pending retries, once cancelled
+    pub cancel: Option<CancellationToken>,
+    /// Ask for a different answer to a message the agent already replied to
+    pub regenerate: bool,
+    /// Ask about the conversation without adding to it, e.g. for its title
+    pub ephemeral: bool,
+}
+
+impl ChatOptions {
+    /// Options from the user's saved preferences
+    pub fn from_config(config: &SlovoConfig) -> Self {
+        Self {
+            include_reasoning: config.include_reasoning,
+            reasoning_effort: config.reasoning_effort.clone(),
+            system_prompt: None,
+            idempotency_key: None,
+            correlation_id: None,
+            cancel: None,
+            regenerate: false,
+            ephemeral: false,
+        }
+    }
 }
 
 /// Chat response from the agent
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub id: String,
     pub response: String,
     pub conversation_id: String,
     pub reasoning: Option<String>,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
 }
 
-/// Agent client for IPC communication
+/// Token counts reported by OpenAI-compatible backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens = self.prompt_tokens.saturating_add(other.prompt_tokens);
+        self.completion_tokens = self.completion_tokens.saturating_add(other.completion_tokens);
+        self.total_tokens = self.total_tokens.saturating_add(other.total_tokens);
+    }
+}
+
+impl ChatResponse {
+    /// Reasoning payload for the `agent-reasoning-chunk` event, if the agent returned any
+    pub fn reasoning_chunk(&self) -> Option<ReasoningChunk> {
+        self.reasoning.as_ref().map(|reasoning| ReasoningChunk {
+            id: self.id.clone(),
+            conversation_id: self.conversation_id.clone(),
+            reasoning: reasoning.clone(),
+        })
+    }
+}
+
+/// Agent reasoning emitted separately from the response text
+#[derive(Debug, Clone, Serialize)]
+pub struct ReasoningChunk {
+    /// Id of the response this reasoning belongs to
+    pub id: String,
+    pub conversation_id: String,
+    pub reasoning: String,
+}
+
+/// Structured error body returned by the agent
+///
+/// Accepts both `{"code", "message"}` and the OpenAI-style
+/// `{"error": {"code", "message"}}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum AgentErrorResponse {
+    Nested { error: AgentErrorBody },
+    Flat(AgentErrorBody),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AgentErrorBody {
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Transcription result from the agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+    /// Overall confidence from 0.0 to 1.0, if the agent reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Word or phrase breakdown, if the agent provides one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+}
+
+/// A timed piece of a transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    pub text: String,
+    /// Offsets into the audio, in seconds
+    pub start: f32,
+    pub end: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// Message from the agent on a live transcription stream
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TranscriptFrame {
+    Partial { text: String },
+    Final { text: String },
+    Error { message: String },
+}
+
+/// Progress of a live transcription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptUpdate {
+    /// Best guess at the words since the last final segment; may still change
+    Partial(String),
+    /// A finished segment that will not be revised
+    Final(String),
+}
+
+impl TranscriptionResponse {
+    /// Whether the agent was less sure than `threshold`; unreported confidence never is
+    pub fn is_below(&self, threshold: f32) -> bool {
+        self.confidence.is_some_and(|confidence| confidence < threshold)
+    }
+}
+
+/// Chat message carrying an image for the agent to look at
+#[derive(Debug, Clone, Serialize)]
+pub struct VisionChatRequest {
+    pub image_base64: String,
+    pub image_mime: String,
+    /// Question about the image; the agent describes it when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+/// Chat message about a local file, sent whole to the agent
+#[derive(Debug, Clone, Serialize)]
+pub struct FileAttachmentRequest {
+    pub file_content_base64: String,
+    pub file_name: String,
+    pub file_mime: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+/// Wake-word check result from the agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeWordResponse {
+    pub detected: bool,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+/// Text-to-speech request to the agent
+#[derive(Debug, Serialize)]
+struct SpeechRequest<'a> {
+    text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    voice: Option<&'a str>,
+    /// Playback rate, where 1.0 is normal speed
+    speed: f32,
+}
+
+/// Synthesized speech returned by the agent
+#[derive(Debug, Clone)]
+pub struct SpeechAudio {
+    pub audio: Vec<u8>,
+    pub format: AudioFormat,
+}
+
+/// TLS settings for the agent connection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TlsConfig {
+    /// Plain HTTP, used for the bundled localhost runtime
+    #[default]
+    Disabled,
+    /// HTTPS validated against the system certificate store
+    SystemRoots,
+    /// HTTPS validated against a PEM-encoded CA bundle
+    CustomCa(PathBuf),
+}
+
+/// Fluent builder for [`AgentClient`]
 #[derive(Clone)]
-pub struct AgentClient {
+pub struct AgentClientBuilder {
+    host: String,
+    port: u16,
+    timeout: Duration,
+    auth_token: Option<String>,
+    tls: TlsConfig,
+    max_response_bytes: usize,
+    max_message_chars: usize,
+    max_concurrent_requests: usize,
+    proxy: Option<String>,
+    audit: Option<Arc<AuditLogger>>,
+    middleware: Vec<Arc<dyn RequestMiddleware>>,
+}
+
+impl AgentClientBuilder {
+    /// Start from the default localhost runtime settings
+    pub fn new() -> Self {
+        Self {
+            host: AGENT_HOST.to_string(),
+            port: AGENT_PORT,
+            timeout: REQUEST_TIMEOUT,
+            auth_token: None,
+            tls: TlsConfig::Disabled,
+            max_response_bytes: MAX_RESPONSE_BYTES,
+            max_message_chars: MAX_MESSAGE_CHARS,
+            max_concurrent_requests: MAX_CONCURRENT_REQUESTS,
+            proxy: None,
+            audit: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Start from the connection settings in the app config
+    pub fn from_config(config: &AgentConfig) -> Self {
+        Self::new()
+            .host(config.host.clone())
+            .port(config.port)
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .optional_auth_token(config.auth_token.clone())
+            .max_response_bytes(config.max_response_bytes)
+            .max_message_chars(config.max_message_chars)
+            .max_concurrent_requests(config.max_concurrent_requests)
+            .proxy(config.proxy.clone())
+    }
+
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    pub fn optional_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn max_response_bytes(mut self, limit: usize) -> Self {
+        self.max_response_bytes = limit;
+        self
+    }
+
+    pub fn max_message_chars(mut self, limit: usize) -> Self {
+        self.max_message_chars = limit;
+        self
+    }
+
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = limit;
+        self
+    }
+
+    /// Route requests through a proxy; `None` falls back to the proxy environment variables
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy.filter(|proxy| !proxy.trim().is_empty());
+        self
+    }
+
+    /// Record every chat request and response in an audit log
+    pub fn audit_logger(mut self, audit: Arc<AuditLogger>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Run every request through `middleware`, after any added before it
+    pub fn middleware(mut self, middleware: impl RequestMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Validate the settings and build the client
+    pub fn build(self) -> Result<AgentClient, SlovoError> {
+        if self.host.trim().is_empty() {
+            return Err(SlovoError::ConfigError("Agent host must not be empty".to_string()));
+        }
+        if self.port == 0 {
+            return Err(SlovoError::ConfigError("Agent port must be nonzero".to_string()));
+        }
+        if self.max_concurrent_requests == 0 {
+            return Err(SlovoError::ConfigError(
+                "Max concurrent requests must be nonzero".to_string(),
+            ));
+        }
+
+        // The timeout is set per request so it can change without dropping pooled connections
+        let mut builder = Client::builder();
+
+        if let TlsConfig::CustomCa(path) = &self.tls {
+            let pem = std::fs::read(path)?;
+            let certificate = Certificate::from_pem(&pem)
+                .map_err(|e| SlovoError::ConfigError(format!("Invalid CA certificate: {}", e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(parse_proxy(proxy)?);
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| SlovoError::ConfigError(format!("Failed to create HTTP client: {}", e)))?;
+
+        let scheme = match self.tls {
+            TlsConfig::Disabled => "http",
+            TlsConfig::SystemRoots | TlsConfig::CustomCa(_) => "https",
+        };
+
+        let endpoint = Endpoint {
+            client,
+            base_url: format!("{}://{}:{}", scheme, self.host, self.port),
+            max_response_bytes: self.max_response_bytes,
+            max_message_chars: self.max_message_chars,
+        };
+
+        Ok(AgentClient {
+            endpoint: Arc::new(std::sync::RwLock::new(Arc::new(endpoint))),
+            auth_token: Arc::new(std::sync::RwLock::new(self.auth_token)),
+            health: Arc::new(RwLock::new(None)),
+            capabilities: Arc::new(RwLock::new(None)),
+            model_info: Arc::new(RwLock::new(None)),
+            permits: Arc::new(Semaphore::new(self.max_concurrent_requests)),
+            timeout_ms: Arc::new(AtomicU64::new(self.timeout.as_millis() as u64)),
+            audit: self.audit,
+            middleware: self.middleware,
+        })
+    }
+}
+
+impl Default for AgentClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where and how the client reaches the agent; swapped as a whole by `reconfigure`
+struct Endpoint {
     client: Client,
     base_url: String,
+    max_response_bytes: usize,
+    max_message_chars: usize,
+}
+
+/// Agent client for IPC communication
+#[derive(Clone)]
+pub struct AgentClient {
+    /// Shared between clones so a profile switch reaches every holder
+    endpoint: Arc<std::sync::RwLock<Arc<Endpoint>>>,
+    /// Shared between clones so a token change reaches every holder
+    auth_token: Arc<std::sync::RwLock<Option<String>>>,
+    /// Last successful health check and when it was made, shared between clones
+    health: Arc<RwLock<Option<(AgentHealth, Instant)>>>,
+    /// Capabilities fetched after the agent last connected, shared between clones
+    capabilities: Arc<RwLock<Option<Capabilities>>>,
+    /// Last fetched model info and when it was fetched, shared between clones
+    model_info: Arc<RwLock<Option<(ModelInfo, Instant)>>>,
+    /// Caps chat requests in flight, shared between clones
+    permits: Arc<Semaphore>,
+    /// Timeout applied to each request, shared between clones
+    timeout_ms: Arc<AtomicU64>,
+    audit: Option<Arc<AuditLogger>>,
+    /// Applied in order to every HTTP request; kept across `reconfigure`
+    middleware: Vec<Arc<dyn RequestMiddleware>>,
 }
 
 impl AgentClient {
-    /// Create a new agent client
+    /// Create a new agent client with the default settings
     pub fn new() -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+        AgentClientBuilder::new()
             .build()
-            .expect("Failed to create HTTP client");
+            .expect("Failed to create HTTP client")
+    }
 
-        Self {
-            client,
-            base_url: format!("http://{}:{}", AGENT_HOST, AGENT_PORT),
+    /// Start building a client with custom settings
+    pub fn builder() -> AgentClientBuilder {
+        AgentClientBuilder::new()
+    }
+
+    /// Point the client at a different agent, keeping the audit log and request cap
+    ///
+    /// Cached health and capabilities belong to the old agent and are dropped.
+    pub async fn reconfigure(&self, config: &AgentConfig) -> Result<(), SlovoError> {
+        let rebuilt = AgentClientBuilder::from_config(config).build()?;
+        *self.endpoint.write().unwrap() = rebuilt.endpoint();
+        self.timeout_ms
+            .store(rebuilt.timeout_ms.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.set_auth_token(config.auth_token.clone());
+        *self.health.write().await = None;
+        *self.capabilities.write().await = None;
+        *self.model_info.write().await = None;
+        Ok(())
+    }
+
+    /// Current connection settings
+    fn endpoint(&self) -> Arc<Endpoint> {
+        self.endpoint.read().unwrap().clone()
+    }
+
+    /// How long a request may take before it is abandoned
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// Change the request timeout, in seconds (1 to 600), for every clone of this client
+    ///
+    /// Requests already in flight keep the timeout they were sent with.
+    pub fn set_request_timeout(&self, secs: u64) -> Result<(), SlovoError> {
+        if !(MIN_REQUEST_TIMEOUT_SECS..=MAX_REQUEST_TIMEOUT_SECS).contains(&secs) {
+            return Err(SlovoError::ConfigError(format!(
+                "Request timeout must be between {} and {} seconds, got {}",
+                MIN_REQUEST_TIMEOUT_SECS, MAX_REQUEST_TIMEOUT_SECS, secs
+            )));
+        }
+        self.timeout_ms.store(secs * 1000, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Replace (or clear) the bearer token used for subsequent requests
+    pub fn set_auth_token(&self, token: Option<String>) {
+        *self.auth_token.write().unwrap() = token;
+    }
+
+    /// Attach the bearer token (when configured) to an outgoing request
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.auth_token.read().unwrap().as_ref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
         }
     }
 
+    /// Authorize `request`, pass it through the middleware and send it
+    ///
+    /// Repeats a failed connection when middleware asks for retries and the
+    /// request is safe to send twice.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, SlovoError> {
+        self.send_with_timeout(request, self.request_timeout()).await
+    }
+
+    /// [`send`](Self::send) with a timeout other than the configured one
+    async fn send_with_timeout(
+        &self,
+        request: RequestBuilder,
+        timeout: Duration,
+    ) -> Result<Response, SlovoError> {
+        let request = self
+            .middleware
+            .iter()
+            .fold(self.authorize(request.timeout(timeout)), |request, middleware| {
+                middleware.before_send(request)
+            });
+        let retries = self.middleware.iter().map(|m| m.max_retries()).max().unwrap_or(0);
+        let repeatable = retries > 0
+            && request
+                .try_clone()
+                .and_then(|request| request.build().ok())
+                .is_some_and(|request| middleware::is_repeatable(&request, IDEMPOTENCY_KEY_HEADER));
+
+        let response = if repeatable {
+            with_retry(retries + 1, || {
+                let attempt = request.try_clone();
+                async move {
+                    let attempt =
+                        attempt.ok_or_else(|| SlovoError::agent("request cannot be repeated"))?;
+                    attempt.send().await.map_err(SlovoError::from)
+                }
+            })
+            .await?
+        } else {
+            request
+                .send()
+                .await
+                .map_err(SlovoError::from)?
+        };
+
+        for middleware in &self.middleware {
+            middleware.after_response(&response);
+        }
+        Ok(response)
+    }
+
+    /// Snapshot from the last successful health check, without contacting the agent
+    pub async fn cached_health(&self) -> Option<AgentHealth> {
+        self.health.read().await.as_ref().map(|(health, _)| health.clone())
+    }
+
+    /// Health from a check made within `max_age`, or from a fresh check otherwise
+    pub async fn recent_health(&self, max_age: Duration) -> Result<AgentHealth, SlovoError> {
+        if let Some((health, checked)) = self.health.read().await.as_ref() {
+            if checked.elapsed() < max_age {
+                return Ok(health.clone());
+            }
+        }
+        self.health_check().await
+    }
+
+    /// Forget the last health check so the next `recent_health` contacts the agent
+    pub async fn invalidate_health_cache(&self) {
+        *self.health.write().await = None;
+    }
+
     /// Check agent health
     pub async fn health_check(&self) -> Result<AgentHealth, SlovoError> {
-        let url = format!("{}/health", self.base_url);
-        
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| SlovoError::AgentConnection(e.to_string()))?;
+        let endpoint = self.endpoint();
+        let url = format!("{}/health", endpoint.base_url);
+
+        let response = self.send(endpoint.client.get(&url)).await?;
+        let response = reject_unauthorized(response)?;
 
         if !response.status().is_success() {
             return Err(SlovoError::AgentConnection(format!(
@@ -79,42 +773,698 @@ impl AgentClient {
             )));
         }
 
-        response
-            .json::<AgentHealth>()
+        let health: AgentHealth = read_json_capped(response, endpoint.max_response_bytes).await?;
+
+        *self.health.write().await = Some((health.clone(), Instant::now()));
+        Ok(health)
+    }
+
+    /// Ask the agent to load its model, returning once it reports ready
+    ///
+    /// Agents without a warm-up endpoint load lazily and are treated as ready.
+    pub async fn warm_up(&self) -> Result<(), SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/warmup", endpoint.base_url);
+
+        let request = endpoint.client.post(&url);
+        let response = self.send_with_timeout(request, WARMUP_TIMEOUT).await?;
+        let response = reject_unauthorized(response)?;
+
+        if response.status() == StatusCode::NOT_FOUND || response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(agent_error(status, &error_text))
+    }
+
+    /// Ask the agent to drop its state for a conversation
+    ///
+    /// A conversation the agent does not know counts as deleted.
+    pub async fn delete_conversation(&self, conversation_id: &str) -> Result<(), SlovoError> {
+        let endpoint = self.endpoint();
+        let mut url = url::Url::parse(&endpoint.base_url)
+            .map_err(|e| SlovoError::ConfigError(format!("Invalid agent URL: {}", e)))?;
+        // Pushed as a segment so the id is escaped rather than able to change the path
+        url.path_segments_mut()
+            .map_err(|_| SlovoError::ConfigError("Invalid agent URL".to_string()))?
+            .pop_if_empty()
+            .extend(["api", "v1", "conversations", conversation_id]);
+
+        let response = self.send(endpoint.client.delete(url)).await?;
+        let response = reject_unauthorized(response)?;
+
+        if response.status() == StatusCode::NOT_FOUND || response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(agent_error(status, &error_text))
+    }
+
+    /// Fetch the agent's feature set, remembering it for `cached_capabilities`
+    ///
+    /// Older agents without the endpoint get the conservative default.
+    pub async fn capabilities(&self) -> Result<Capabilities, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/capabilities", endpoint.base_url);
+
+        let response = self.send(endpoint.client.get(&url)).await?;
+        let response = reject_unauthorized(response)?;
+
+        let capabilities = if response.status() == StatusCode::NOT_FOUND {
+            Capabilities::default()
+        } else if response.status().is_success() {
+            read_json_capped(response, endpoint.max_response_bytes).await?
+        } else {
+            return Err(SlovoError::AgentConnection(format!(
+                "Capabilities request failed with status: {}",
+                response.status()
+            )));
+        };
+
+        *self.capabilities.write().await = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Fetch which model the agent is running, remembering it for `cached_model_info`
+    pub async fn model_info(&self) -> Result<ModelInfo, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/model", endpoint.base_url);
+
+        let response = self.send(endpoint.client.get(&url)).await?;
+        let response = reject_unauthorized(response)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(agent_error(status, &error_text));
+        }
+
+        let info: ModelInfo = read_json_capped(response, endpoint.max_response_bytes).await?;
+        *self.model_info.write().await = Some((info.clone(), Instant::now()));
+        Ok(info)
+    }
+
+    /// Model info from the last fetch and its age, without contacting the agent
+    pub async fn cached_model_info(&self) -> Option<(ModelInfo, Duration)> {
+        self.model_info
+            .read()
             .await
-            .map_err(|e| SlovoError::AgentConnection(e.to_string()))
+            .as_ref()
+            .map(|(info, fetched)| (info.clone(), fetched.elapsed()))
+    }
+
+    /// Capabilities from the last fetch, without contacting the agent
+    pub async fn cached_capabilities(&self) -> Option<Capabilities> {
+        self.capabilities.read().await.clone()
+    }
+
+    /// Whether the last-known health snapshot advertises a capability
+    ///
+    /// Returns false until a health check has succeeded.
+    pub async fn supports(&self, capability: &str) -> bool {
+        self.health
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|(health, _)| health.supports(capability))
+    }
+
+    /// Fail fast when the agent lacks a capability, checking health first if none is cached
+    pub async fn require(&self, capability: &str) -> Result<(), SlovoError> {
+        if self.health.read().await.is_none() {
+            self.health_check().await?;
+        }
+        if !self.supports(capability).await {
+            return Err(SlovoError::agent(format!(
+                "capability not available: {}",
+                capability
+            )));
+        }
+        Ok(())
+    }
+
+    /// Pre-send checks a chat message must pass before it reaches the agent
+    pub fn check_message(&self, message: &str) -> Result<(), SlovoError> {
+        if message.trim().is_empty() {
+            return Err(SlovoError::agent("message is empty"));
+        }
+
+        let length = message.chars().count();
+        let limit = self.endpoint().max_message_chars;
+        if length > limit {
+            return Err(SlovoError::agent(format!(
+                "message too long: {} characters (limit {})",
+                length, limit
+            )));
+        }
+        Ok(())
     }
 
     /// Send a chat message to the agent
-    pub async fn send_message(&self, message: &str, conversation_id: Option<&str>) -> Result<ChatResponse, SlovoError> {
-        let url = format!("{}/api/v1/chat", self.base_url);
-        
-        let request = ChatRequest {
-            message: message.to_string(),
-            conversation_id: conversation_id.map(|s| s.to_string()),
+    pub async fn send_message(
+        &self,
+        message: &str,
+        conversation_id: Option<&str>,
+        options: &ChatOptions,
+    ) -> Result<ChatResponse, SlovoError> {
+        let request = ChatRequest::new(message, conversation_id.map(|s| s.to_string()), options);
+        self.send_request(request, options).await
+    }
+
+    /// Send a chat message with files attached
+    ///
+    /// Agents advertising multipart support receive the files as form parts;
+    /// others get them base64-encoded in the JSON body.
+    pub async fn send_message_with_attachments(
+        &self,
+        message: &str,
+        attachments: Vec<Attachment>,
+        conversation_id: Option<&str>,
+        options: &ChatOptions,
+    ) -> Result<ChatResponse, SlovoError> {
+        attachment::validate(&attachments)?;
+        let mut request = ChatRequest::new(message, conversation_id.map(|s| s.to_string()), options);
+        request.attachments = attachments;
+        self.send_request(request, options).await
+    }
+
+    #[tracing::instrument(
+        name = "agent.send_message",
+        skip_all,
+        fields(
+            conversation_id = request.conversation_id.as_deref().unwrap_or_default(),
+            message_length = request.message.chars().count()
+        )
+    )]
+    async fn send_request(&self, request: ChatRequest, options: &ChatOptions) -> Result<ChatResponse, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/chat", endpoint.base_url);
+        self.check_message(&request.message)?;
+        let started = Instant::now();
+
+        // Only a keyed request can be retried without risking a duplicate reply
+        let attempts = if request.idempotency_key.is_some() { MAX_SEND_ATTEMPTS } else { 1 };
+        let send = with_retry(attempts, || self.post_chat(&url, &request));
+        let result = match &options.cancel {
+            Some(token) => tokio::select! {
+                result = send => result,
+                _ = token.cancelled() => Err(SlovoError::agent("request cancelled")),
+            },
+            None => send.await,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
+        // Recorded as span events for trace exporters
+        let response_time_ms = started.elapsed().as_millis() as u64;
+        if let Ok(response) = &result {
+            let tokens_used = response.usage.map(|usage| usage.total_tokens);
+            info!(
+                agent.response_id = %response.id,
+                agent.response_time_ms = response_time_ms,
+                agent.tokens_used = tokens_used,
+                "agent responded"
+            );
+        }
+        result
+    }
+
+    /// Send independent chat requests concurrently, returning results in input order
+    ///
+    /// At most `max_concurrent_requests` are in flight at once.
+    pub async fn send_batch(&self, requests: Vec<ChatRequest>) -> Vec<Result<ChatResponse, SlovoError>> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/chat", endpoint.base_url);
+        let url = &url;
+        join_all(requests.iter().map(|request| async move {
+            self.check_message(&request.message)?;
+            let attempts = if request.idempotency_key.is_some() { MAX_SEND_ATTEMPTS } else { 1 };
+            with_retry(attempts, || self.post_chat(url, request)).await
+        }))
+        .await
+    }
+
+    /// POST one chat attempt, recording it in the audit log when one is configured
+    async fn post_chat(&self, url: &str, request: &ChatRequest) -> Result<ChatResponse, SlovoError> {
+        let _permit = self
+            .permits
+            .acquire()
             .await
-            .map_err(|e| SlovoError::AgentConnection(e.to_string()))?;
+            .map_err(|e| SlovoError::agent(e.to_string()))?;
+        let Some(audit) = &self.audit else {
+            return self.exchange_chat(url, request, &mut None).await;
+        };
+
+        let conversation_id = request.conversation_id.as_deref();
+        audit.record_request(conversation_id, &request.message);
+        let started = Instant::now();
+        let mut status = None;
+        let result = self.exchange_chat(url, request, &mut status).await;
+
+        match &result {
+            Ok(response) => audit.record_response(
+                Some(&response.conversation_id),
+                &response.response,
+                status,
+                started.elapsed(),
+            ),
+            Err(_) => audit.record_response(conversation_id, "", status, started.elapsed()),
+        }
+        result
+    }
+
+    async fn exchange_chat(
+        &self,
+        url: &str,
+        request: &ChatRequest,
+        status: &mut Option<u16>,
+    ) -> Result<ChatResponse, SlovoError> {
+        let endpoint = self.endpoint();
+        let mut builder = endpoint.client.post(url);
+        builder = if !request.attachments.is_empty() && self.supports(CAPABILITY_MULTIPART_ATTACHMENTS).await {
+            builder.multipart(multipart_form(request)?)
+        } else {
+            builder.json(request)
+        };
+        if let Some(key) = &request.idempotency_key {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        if let Some(id) = &request.correlation_id {
+            builder = builder.header(REQUEST_ID_HEADER, id);
+        }
+
+        let response = self.send(builder).await?;
+        *status = Some(response.status().as_u16());
+        let response = reject_unauthorized(response)?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(SlovoError::RateLimitError {
+                retry_after_ms: retry_after_ms(&response),
+            });
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(agent_error(status, &error_text));
+        }
+
+        read_json_capped(response, endpoint.max_response_bytes).await
+    }
+
+    /// Send an image to the agent's vision endpoint
+    pub async fn send_vision(&self, request: &VisionChatRequest) -> Result<ChatResponse, SlovoError> {
+        self.post_json_chat("/api/v1/chat/vision", request).await
+    }
+
+    /// Send a file to the agent's file chat endpoint
+    pub async fn send_file(&self, request: &FileAttachmentRequest) -> Result<ChatResponse, SlovoError> {
+        self.post_json_chat("/api/v1/chat/file", request).await
+    }
+
+    /// POST a JSON chat variant to `path`, sharing the chat concurrency limit
+    async fn post_json_chat<T: Serialize>(&self, path: &str, body: &T) -> Result<ChatResponse, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}{}", endpoint.base_url, path);
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| SlovoError::agent(e.to_string()))?;
+
+        let request = endpoint.client.post(&url).json(body);
+        let response = self.send(request).await?;
+        let response = reject_unauthorized(response)?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(SlovoError::AgentError(format!(
-                "Chat request failed with status {}: {}",
+            return Err(agent_error(status, &error_text));
+        }
+
+        read_json_capped(response, endpoint.max_response_bytes).await
+    }
+
+    /// Transcribe an audio buffer via the agent's speech-to-text endpoint
+    pub async fn transcribe(&self, audio: Vec<u8>, format: AudioFormat) -> Result<TranscriptionResponse, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/transcribe", endpoint.base_url);
+
+        let request = endpoint.client.post(&url)
+            .query(&[("format", format.as_str())])
+            .header(reqwest::header::CONTENT_TYPE, format.mime_type())
+            .body(audio);
+        let response = self.send(request).await?;
+        let response = reject_unauthorized(response)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SlovoError::VoiceError(format!(
+                "Transcription failed with status {}: {}",
                 status, error_text
             )));
         }
 
-        response
-            .json::<ChatResponse>()
+        read_json_capped(response, endpoint.max_response_bytes).await
+    }
+
+    /// Ask the agent whether a short WAV clip contains `keyword`
+    pub async fn detect_wake_word(&self, audio: Vec<u8>, keyword: &str) -> Result<WakeWordResponse, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/wake-word", endpoint.base_url);
+
+        let request = endpoint.client.post(&url)
+            .query(&[("keyword", keyword)])
+            .header(reqwest::header::CONTENT_TYPE, AudioFormat::Wav.mime_type())
+            .body(audio);
+        let response = self.send(request).await?;
+        let response = reject_unauthorized(response)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SlovoError::VoiceError(format!(
+                "Wake-word detection failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        read_json_capped(response, endpoint.max_response_bytes).await
+    }
+
+    /// Synthesize speech for `text` via the agent's text-to-speech endpoint
+    pub async fn synthesize(&self, text: &str, voice: Option<&str>, speed: f32) -> Result<SpeechAudio, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!("{}/api/v1/tts", endpoint.base_url);
+
+        let speech = SpeechRequest { text, voice, speed };
+
+        let request = endpoint.client.post(&url).json(&speech);
+        let response = self.send(request).await?;
+        let response = reject_unauthorized(response)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SlovoError::VoiceError(format!(
+                "Speech synthesis failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let audio = read_body_capped(response, endpoint.max_response_bytes).await?;
+        let format = audio::detect_format(&audio).ok_or_else(|| {
+            SlovoError::VoiceError("Agent returned audio in an unrecognized format".to_string())
+        })?;
+        Ok(SpeechAudio { audio, format })
+    }
+
+    /// Transcribe audio while it is still being captured
+    ///
+    /// Sends 16-bit PCM frames from `frames` as binary messages to
+    /// `/api/v1/transcribe/stream` and reports transcripts through `on_update`
+    /// as they arrive. Once `frames` closes, an `end` message asks the agent to
+    /// flush, and the final segments received before it closes the socket are
+    /// joined into the returned transcript.
+    pub async fn transcribe_stream(
+        &self,
+        sample_rate: u32,
+        channels: u16,
+        mut frames: mpsc::Receiver<Vec<i16>>,
+        mut on_update: impl FnMut(TranscriptUpdate),
+    ) -> Result<String, SlovoError> {
+        let endpoint = self.endpoint();
+        let url = format!(
+            "{}/api/v1/transcribe/stream?encoding=pcm_s16le&sample_rate={}&channels={}",
+            endpoint.base_url.replacen("http", "ws", 1),
+            sample_rate,
+            channels
+        );
+        let auth_token = self.auth_token.read().unwrap().clone();
+        let socket = ws::connect(&url, auth_token.as_deref())
             .await
-            .map_err(|e| SlovoError::AgentConnection(e.to_string()))
+            .map_err(|e| SlovoError::AgentConnection(e.to_string()))?;
+        let (mut sink, mut stream) = socket.split();
+
+        let mut segments: Vec<String> = Vec::new();
+        let mut ending = false;
+        let flush_deadline = tokio::time::sleep(Duration::MAX);
+        tokio::pin!(flush_deadline);
+        loop {
+            tokio::select! {
+                frame = frames.recv(), if !ending => {
+                    let message = match frame {
+                        Some(samples) => Message::binary(
+                            samples.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<u8>>(),
+                        ),
+                        None => {
+                            ending = true;
+                            flush_deadline
+                                .as_mut()
+                                .reset(tokio::time::Instant::now() + TRANSCRIBE_FLUSH_TIMEOUT);
+                            Message::text(r#"{"type":"end"}"#)
+                        }
+                    };
+                    sink.send(message)
+                        .await
+                        .map_err(|e| SlovoError::AgentConnection(e.to_string()))?;
+                }
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<TranscriptFrame>(&text) {
+                            Ok(TranscriptFrame::Partial { text }) => {
+                                on_update(TranscriptUpdate::Partial(text));
+                            }
+                            Ok(TranscriptFrame::Final { text }) => {
+                                segments.push(text.clone());
+                                on_update(TranscriptUpdate::Final(text));
+                            }
+                            Ok(TranscriptFrame::Error { message }) => {
+                                return Err(SlovoError::VoiceError(message));
+                            }
+                            Err(e) => warn!("Ignoring unreadable transcript frame: {}", e),
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None if ending => break,
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Err(SlovoError::AgentConnection(
+                            "Transcription stream closed before the audio ended".to_string(),
+                        ));
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(SlovoError::AgentConnection(e.to_string())),
+                },
+                _ = &mut flush_deadline, if ending => {
+                    warn!("Agent did not close the transcription stream; using the transcript so far");
+                    break;
+                }
+            }
+        }
+        let _ = sink.close().await;
+
+        Ok(segments
+            .iter()
+            .map(|segment| segment.trim())
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+
+    /// Open a persistent WebSocket stream to the agent
+    ///
+    /// The connection runs in the background, reconnecting with backoff when it
+    /// drops, and forwards streamed chunks to the frontend as events.
+    pub fn connect_ws(&self, app: AppHandle) -> AgentStream {
+        let endpoint = self.endpoint();
+        let ws_url = format!("{}/api/v1/ws", endpoint.base_url.replacen("http", "ws", 1));
+        let auth_token = self.auth_token.read().unwrap().clone();
+        AgentStream::spawn(ws_url, auth_token, app)
+    }
+}
+
+/// Validate a configured proxy URL up front so a typo is not reported as a connection failure
+fn parse_proxy(proxy: &str) -> Result<reqwest::Proxy, SlovoError> {
+    let invalid =
+        |reason: String| SlovoError::ConfigError(format!("Invalid proxy URL {}: {}", proxy, reason));
+    let url = url::Url::parse(proxy.trim()).map_err(|e| invalid(e.to_string()))?;
+    match url.scheme() {
+        "http" | "https" => {}
+        "socks4" | "socks4a" | "socks5" | "socks5h" => {
+            return Err(invalid("SOCKS proxies are not supported by this build".to_string()));
+        }
+        scheme => return Err(invalid(format!("unsupported scheme '{}'", scheme))),
+    }
+    if url.host_str().is_none() {
+        return Err(invalid("missing host".to_string()));
+    }
+    reqwest::Proxy::all(url.as_str()).map_err(|e| invalid(e.to_string()))
+}
+
+/// Build the client that [`agent_client`] returns
+///
+/// Call once at startup, before anything that may run ahead of managed state.
+/// Only the first call takes effect; later ones return false and change nothing.
+pub fn init_default_client(config: AgentConfig) -> Result<bool, SlovoError> {
+    let client = AgentClientBuilder::from_config(&config).build()?;
+    Ok(DEFAULT_CLIENT.set(client).is_ok())
+}
+
+/// The process-wide fallback client, for code that has no `AppHandle` or runs
+/// before setup has managed the app's client
+///
+/// Prefer `AppState::of(&app).client` wherever it is available. Release builds
+/// panic if [`init_default_client`] has not run yet; debug builds and tests
+/// instead get a client built from the default agent config.
+pub fn agent_client() -> &'static AgentClient {
+    DEFAULT_CLIENT.get_or_init(|| {
+        if !cfg!(debug_assertions) {
+            panic!("agent_client() called before init_default_client()");
+        }
+        AgentClientBuilder::from_config(&AgentConfig::default())
+            .build()
+            .expect("Default agent config is invalid")
+    })
+}
+
+/// Run `op` up to `attempts` times, backing off exponentially between
+/// transient connection failures
+///
+/// A rate limited request is retried after the longer of the backoff and the
+/// agent's `Retry-After`; waits longer than `MAX_RETRY_AFTER` are left to the
+/// caller.
+async fn with_retry<T, F, Fut>(attempts: u32, mut op: F) -> Result<T, SlovoError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, SlovoError>>,
+{
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(SlovoError::AgentConnection(e) | SlovoError::Timeout(e)) if attempt < attempts => {
+                warn!(
+                    "Agent request failed (attempt {}/{}), retrying in {:?}: {}",
+                    attempt, attempts, delay, e
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(SlovoError::RateLimitError { retry_after_ms })
+                if attempt < attempts
+                    && retry_after_ms.map_or(true, |ms| Duration::from_millis(ms) <= MAX_RETRY_AFTER) =>
+            {
+                // Never sooner than the agent asked, even early in the backoff
+                let wait = retry_after_ms.map_or(delay, |ms| delay.max(Duration::from_millis(ms)));
+                warn!(
+                    "Agent rate limited request (attempt {}/{}), retrying in {:?}",
+                    attempt, attempts, wait
+                );
+                tokio::time::sleep(wait).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Parse `Retry-After` as delay seconds or an HTTP date, in milliseconds from now
+fn retry_after_ms(response: &Response) -> Option<u64> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, chrono::Utc::now())
+}
+
+/// `Retry-After` in milliseconds after `now`; dates in the past mean no wait
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs.saturating_mul(1000));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let ms = (at.with_timezone(&chrono::Utc) - now).num_milliseconds();
+    Some(ms.max(0) as u64)
+}
+
+/// Build an `AgentError` from a failed response, using the agent's code when the body is JSON
+fn agent_error(status: StatusCode, body: &str) -> SlovoError {
+    match serde_json::from_str::<AgentErrorResponse>(body) {
+        Ok(AgentErrorResponse::Nested { error: body }) | Ok(AgentErrorResponse::Flat(body)) => {
+            SlovoError::AgentError {
+                code: body.code,
+                message: body.message,
+            }
+        }
+        Err(_) => SlovoError::agent(format!("Chat request failed with status {}: {}", status, body)),
+    }
+}
+
+/// Map HTTP 401 to an authentication error instead of a generic agent error
+fn reject_unauthorized(response: Response) -> Result<Response, SlovoError> {
+    if response.status() == StatusCode::UNAUTHORIZED {
+        return Err(SlovoError::AuthError(
+            "Agent rejected the configured auth token".to_string(),
+        ));
+    }
+    Ok(response)
+}
+
+/// Read and decode a JSON body, refusing to buffer more than `limit` bytes
+async fn read_json_capped<T: DeserializeOwned>(response: Response, limit: usize) -> Result<T, SlovoError> {
+    let body = read_body_capped(response, limit).await?;
+    serde_json::from_slice(&body).map_err(|e| SlovoError::parse(e, &body))
+}
+
+/// Read a response body, refusing to buffer more than `limit` bytes
+async fn read_body_capped(mut response: Response, limit: usize) -> Result<Vec<u8>, SlovoError> {
+    let too_large = || SlovoError::agent("response too large");
+
+    if response.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(SlovoError::from)?
+    {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// The agent operations any connection to it supports, however it is carried
+#[async_trait]
+pub trait AgentTransport: Send + Sync {
+    async fn health_check(&self) -> Result<AgentHealth, SlovoError>;
+
+    async fn send_message(
+        &self,
+        message: &str,
+        conversation_id: Option<&str>,
+        options: &ChatOptions,
+    ) -> Result<ChatResponse, SlovoError>;
+}
+
+#[async_trait]
+impl AgentTransport for AgentClient {
+    async fn health_check(&self) -> Result<AgentHealth, SlovoError> {
+        AgentClient::health_check(self).await
+    }
+
+    async fn send_message(
+        &self,
+        message: &str,
+        conversation_id: Option<&str>,
+        options: &ChatOptions,
+    ) -> Result<ChatResponse, SlovoError> {
+        AgentClient::send_message(self, message, conversation_id, options).await
     }
 }
 
@@ -124,35 +1474,446 @@ impl Default for AgentClient {
     }
 }
 
-/// Monitor agent health and emit status updates
-pub async fn monitor_agent_health(app: AppHandle) {
-    let client = AgentClient::new();
-    let mut last_status = "disconnected".to_string();
+/// Restart wake-word detection for the saved keyword once the agent is reachable
+fn resume_wake_word(app: &AppHandle) {
+    let Some(keyword) = AppState::of(app).config.read().unwrap().wake_word.clone() else {
+        return;
+    };
+    if app.state::<WakeWordDetector>().is_running() {
+        return;
+    }
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let detector = handle.state::<WakeWordDetector>();
+        if let Err(e) = detector.start(handle.clone(), keyword).await {
+            warn!("Failed to resume wake-word detection: {}", e);
+        }
+    });
+}
+
+/// Multipart body carrying the request as JSON plus one part per attachment
+fn multipart_form(request: &ChatRequest) -> Result<Form, SlovoError> {
+    let body = ChatRequest {
+        attachments: Vec::new(),
+        ..request.clone()
+    };
+    let json = serde_json::to_string(&body).map_err(|e| SlovoError::agent(e.to_string()))?;
+    let mut form = Form::new().text("request", json);
+
+    for attachment in &request.attachments {
+        let part = Part::bytes(attachment.bytes()?)
+            .file_name(attachment.name.clone().unwrap_or_else(|| "attachment".to_string()))
+            .mime_str(&attachment.mime_type)
+            .map_err(|e| SlovoError::agent(e.to_string()))?;
+        form = form.part("attachments", part);
+    }
+    Ok(form)
+}
+
+/// Check whether an agent version satisfies the minimum supported version
+///
+/// Versions are compared with semver precedence, so a pre-release such as
+/// `0.2.0-beta` is older than `0.2.0`. Unparseable versions are incompatible.
+pub fn version_compatible(agent: &str, minimum: &str) -> bool {
+    let parse = |v: &str| Version::parse(v.trim().trim_start_matches('v'));
+    match (parse(agent), parse(minimum)) {
+        (Ok(agent), Ok(minimum)) => agent >= minimum,
+        _ => false,
+    }
+}
+
+/// Tracks whether the agent's model is loaded so warm-ups are not repeated
+#[derive(Default)]
+pub struct AgentWarmup {
+    /// Held for the duration of a warm-up so concurrent callers share it
+    in_flight: tokio::sync::Mutex<()>,
+    ready: AtomicBool,
+}
+
+impl AgentWarmup {
+    /// Warm the agent up unless it already is, emitting `agent-warming` and `agent-ready`
+    ///
+    /// Callers arriving mid-warm-up wait for it rather than starting another.
+    pub async fn run(&self, app: &AppHandle) -> Result<(), SlovoError> {
+        let _guard = self.in_flight.lock().await;
+        if self.is_ready() {
+            return Ok(());
+        }
+
+        let _ = app.emit("agent-warming", ());
+        let started = Instant::now();
+        AppState::of(app).client.warm_up().await?;
+        self.ready.store(true, Ordering::SeqCst);
+        info!("Agent warmed up in {} ms", started.elapsed().as_millis());
+        let _ = app.emit("agent-ready", ());
+        Ok(())
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    /// Forget the warm state, e.g. once the agent goes away
+    pub fn reset(&self) {
+        self.ready.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Managed handle to the running health monitor task
+pub struct HealthMonitor {
+    cancel: std::sync::Mutex<Option<CancellationToken>>,
+    /// Seconds between checks, read by the running loop before every wait
+    interval_secs: Arc<AtomicU64>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self {
+            cancel: std::sync::Mutex::new(None),
+            interval_secs: Arc::new(AtomicU64::new(HEALTH_CHECK_INTERVAL.as_secs())),
+        }
+    }
+}
+
+impl HealthMonitor {
+    /// Change how often the agent is checked; takes effect after the current wait
+    pub fn set_interval(&self, secs: u64) -> Result<(), SlovoError> {
+        if !(MIN_HEALTH_CHECK_INTERVAL_SECS..=MAX_HEALTH_CHECK_INTERVAL_SECS).contains(&secs) {
+            return Err(SlovoError::ConfigError(format!(
+                "Health check interval must be between {} and {} seconds, got {}",
+                MIN_HEALTH_CHECK_INTERVAL_SECS, MAX_HEALTH_CHECK_INTERVAL_SECS, secs
+            )));
+        }
+        self.interval_secs.store(secs, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Start monitoring, replacing any monitor that is already running
+    pub fn start(&self, app: &AppHandle) {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.cancel.lock().unwrap().replace(token.clone()) {
+            previous.cancel();
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            monitor_agent_health(app, token).await;
+        });
+    }
+
+    /// Stop the running monitor, if any
+    pub fn stop(&self) {
+        if let Some(token) = self.cancel.lock().unwrap().take() {
+            token.cancel();
+        }
+    }
+}
+
+/// Monitor agent health and emit status updates until `cancel` fires
+///
+/// Until the agent first answers, failures within the startup grace period
+/// are ignored, since a cold-starting runtime is not listening yet.
+///
+/// Checks go over the app's [`AgentTransport`]. Without a managed
+/// [`AppState`], as in tests, they go through the default client with default
+/// settings and no health history is kept.
+pub async fn monitor_agent_health(app: AppHandle, cancel: CancellationToken) {
+    let state = app.try_state::<AppState>();
+    let state = state.as_ref().map(|state| state.inner());
+    let client = state.map_or_else(|| agent_client().clone(), |state| state.client.clone());
+    let transport = state.map_or_else(
+        || Arc::new(client.clone()) as Arc<dyn AgentTransport>,
+        |state| state.transport.clone(),
+    );
+    let grace_period = Duration::from_secs(state.map_or(
+        AgentConfig::default().startup_grace_period_secs,
+        |state| state.config.read().unwrap().agent.startup_grace_period_secs,
+    ));
+    let interval_secs = app.state::<HealthMonitor>().interval_secs.clone();
+    let started = Instant::now();
+    let mut starting_up = !grace_period.is_zero();
+    let mut last_status = AgentStatus::Disconnected;
+    let mut last_info: Option<AgentInfoResponse> = None;
+    let mut notifier = ConnectivityNotifier::default();
 
     loop {
-        let status = match client.health_check().await {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        let _ = app.emit(MonitorHeartbeat::EVENT, MonitorHeartbeat { timestamp });
+
+        // Respawn a managed runtime that died, rather than waiting on HTTP failures
+        if app.state::<AgentProcessState>().supervise().await {
+            info!("Agent runtime restarted after unexpected exit");
+        }
+
+        let checked_at = Instant::now();
+        let health = tokio::select! {
+            _ = cancel.cancelled() => break,
+            health = transport.health_check() => health,
+        };
+        let latency_ms = checked_at.elapsed().as_millis() as u64;
+        if starting_up {
+            starting_up = health.is_err() && started.elapsed() < grace_period;
+            if starting_up {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(STARTUP_CHECK_INTERVAL) => continue,
+                }
+            }
+            if health.is_err() {
+                info!("Agent still unreachable after the {}s startup grace period", grace_period.as_secs());
+            }
+        }
+        let status = match health {
             Ok(health) => {
-                if health.status == "healthy" {
-                    "connected"
+                let info = AgentInfoResponse::from(health.clone());
+                if last_info.as_ref().map_or(true, |last| !info.same_besides_uptime(last)) {
+                    let updated = AgentInfoResponse {
+                        uptime_percentage: state
+                            .and_then(|state| state.health_history.uptime_percentage()),
+                        ..info.clone()
+                    };
+                    let _ = app.emit("agent-info-updated", &updated);
+                    last_info = Some(info);
+                }
+
+                if !version_compatible(&health.version, MIN_AGENT_VERSION) {
+                    if last_status != AgentStatus::Incompatible {
+                        warn!(
+                            "Agent version {} is older than the minimum supported {}",
+                            health.version, MIN_AGENT_VERSION
+                        );
+                        notify(
+                            &app,
+                            "Slovo agent needs an update",
+                            &format!(
+                                "The agent runtime is version {}, but this app requires {} or newer.",
+                                health.version, MIN_AGENT_VERSION
+                            ),
+                        );
+                    }
+                    AgentStatus::Incompatible
+                } else if health.status == "healthy" {
+                    AgentStatus::Connected
                 } else {
-                    "degraded"
+                    AgentStatus::Degraded
                 }
             }
             Err(e) => {
-                if last_status != "disconnected" {
+                if last_status != AgentStatus::Disconnected {
                     warn!("Agent health check failed: {}", e);
                 }
-                "disconnected"
+                last_info = None;
+                AgentStatus::Disconnected
             }
         };
+        if let Some(state) = state {
+            state.health_history.record(HealthSnapshot {
+                timestamp: SystemTime::now(),
+                status: status.as_str().to_string(),
+                latency_ms,
+            });
+        }
+        notifier.update(&app, status);
+        app.state::<Metrics>().set_agent_connected(status == AgentStatus::Connected);
 
         // Only emit if status changed
         if status != last_status {
             info!("Agent status changed: {} -> {}", last_status, status);
-            let _ = app.emit("agent-status-changed", status);
-            last_status = status.to_string();
+            emit_agent_status(&app, status);
+            if status == AgentStatus::Disconnected {
+                if app.state::<SpeechPlayback>().stop() {
+                    info!("Stopped speech playback after agent disconnected");
+                }
+                // Every check would fail until the agent is back
+                app.state::<WakeWordDetector>().stop();
+                // A restarted agent will have to load its model again
+                app.state::<AgentWarmup>().reset();
+            }
+            if status == AgentStatus::Connected {
+                // The agent may have been upgraded while it was away
+                match client.capabilities().await {
+                    Ok(capabilities) => {
+                        let _ = app.emit("agent-capabilities-updated", &capabilities);
+                    }
+                    Err(e) => warn!("Failed to fetch agent capabilities: {}", e),
+                }
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    commands::flush_queued_messages(&handle).await;
+                });
+                resume_wake_word(&app);
+                if state.is_some_and(|state| state.config.read().unwrap().auto_warmup) {
+                    let handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = handle.state::<AgentWarmup>().run(&handle).await {
+                            warn!("Agent warm-up failed: {}", e);
+                        }
+                    });
+                }
+            }
+            last_status = status;
+        }
+
+        if !wait_for_next_check(&interval_secs, &cancel).await {
+            break;
         }
+    }
+    info!("Agent health monitor stopped");
+}
+
+/// Sleep for the current interval; returns false as soon as `cancel` fires
+async fn wait_for_next_check(interval_secs: &AtomicU64, cancel: &CancellationToken) -> bool {
+    let interval = Duration::from_secs(interval_secs.load(Ordering::Relaxed));
+    tokio::select! {
+        _ = cancel.cancelled() => false,
+        _ = tokio::time::sleep(interval) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn version_compatible_compares_patch_levels() {
+        assert!(version_compatible("0.2.0", "0.2.0"));
+        assert!(version_compatible("0.2.1", "0.2.0"));
+        assert!(version_compatible("v0.3.0", "0.2.9"));
+        assert!(!version_compatible("0.1.9", "0.2.0"));
+        assert!(!version_compatible("0.2.0", "0.2.1"));
+    }
+
+    #[test]
+    fn version_compatible_ranks_pre_releases_below_their_release() {
+        assert!(!version_compatible("0.2.0-beta", "0.2.0"));
+        assert!(!version_compatible("0.2.0-alpha.1", "0.2.0-beta"));
+        assert!(version_compatible("0.2.0-rc.2", "0.2.0-rc.1"));
+        assert!(version_compatible("0.2.1-beta", "0.2.0"));
+    }
+
+    #[test]
+    fn version_compatible_rejects_unparseable_versions() {
+        assert!(!version_compatible("", "0.2.0"));
+        assert!(!version_compatible("0.2", "0.2.0"));
+        assert!(!version_compatible("latest", "0.2.0"));
+    }
+
+    #[test]
+    fn agent_info_comparison_ignores_uptime() {
+        let info = AgentInfoResponse {
+            version: "0.2.0".to_string(),
+            uptime_secs: 12.5,
+            capabilities: vec!["chat".to_string()],
+            status: "healthy".to_string(),
+            uptime_percentage: None,
+        };
+        let later = AgentInfoResponse { uptime_secs: 42.5, ..info.clone() };
+        let degraded = AgentInfoResponse { status: "degraded".to_string(), ..later.clone() };
+
+        assert!(later.same_besides_uptime(&info));
+        assert!(!degraded.same_besides_uptime(&info));
+    }
+
+    fn at(secs: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, secs).unwrap()
+    }
+
+    #[test]
+    fn parse_retry_after_reads_delay_seconds() {
+        assert_eq!(parse_retry_after("120", at(0)), Some(120_000));
+        assert_eq!(parse_retry_after(" 0 ", at(0)), Some(0));
+        assert_eq!(parse_retry_after("-5", at(0)), None);
+        assert_eq!(parse_retry_after("soon", at(0)), None);
+    }
+
+    #[test]
+    fn parse_retry_after_reads_http_dates() {
+        let date = "Wed, 21 Oct 2015 07:28:30 GMT";
+        assert_eq!(parse_retry_after(date, at(0)), Some(30_000));
+        assert_eq!(parse_retry_after(date, at(30)), Some(0));
+        // Already passed, so retry straight away
+        assert_eq!(parse_retry_after(date, at(45)), Some(0));
+    }
+
+    /// Fails with `error` until the last attempt, recording when each attempt ran
+    async fn retry_times(error: impl Fn() -> SlovoError) -> Vec<Duration> {
+        let started = tokio::time::Instant::now();
+        let calls = AtomicU32::new(0);
+        let times = std::sync::Mutex::new(Vec::new());
+        let _ = with_retry(3, || {
+            times.lock().unwrap().push(started.elapsed());
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            let result = if call < 2 { Err(error()) } else { Ok(()) };
+            async move { result }
+        })
+        .await;
+        times.into_inner().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_retry_waits_for_the_longer_of_backoff_and_retry_after() {
+        let rate_limited = |ms| move || SlovoError::RateLimitError { retry_after_ms: Some(ms) };
+        let times = retry_times(rate_limited(2_000)).await;
+        assert_eq!(times, [Duration::ZERO, Duration::from_secs(2), Duration::from_secs(4)]);
+
+        // A hint shorter than the backoff does not shorten it
+        let times = retry_times(rate_limited(10)).await;
+        assert_eq!(times, [Duration::ZERO, RETRY_BASE_DELAY, RETRY_BASE_DELAY * 3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelling_ends_the_monitor_wait_within_one_interval() {
+        let interval_secs = AtomicU64::new(30);
+        let cancel = CancellationToken::new();
+        let started = tokio::time::Instant::now();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            canceller.cancel();
+        });
+
+        assert!(!wait_for_next_check(&interval_secs, &cancel).await);
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+        // Already cancelled, so the next wait does not sleep at all
+        assert!(!wait_for_next_check(&interval_secs, &cancel).await);
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn new_interval_applies_from_the_next_tick() {
+        let monitor = HealthMonitor::default();
+        let cancel = CancellationToken::new();
+        let started = tokio::time::Instant::now();
+
+        // Changed partway through the first wait, which keeps its old length
+        let first = wait_for_next_check(&monitor.interval_secs, &cancel);
+        let change = async {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            monitor.set_interval(2).unwrap();
+        };
+        let (ticked, ()) = tokio::join!(first, change);
+        assert!(ticked);
+        assert_eq!(started.elapsed(), HEALTH_CHECK_INTERVAL);
+
+        assert!(wait_for_next_check(&monitor.interval_secs, &cancel).await);
+        assert_eq!(started.elapsed(), HEALTH_CHECK_INTERVAL + Duration::from_secs(2));
+    }
+
+    #[test]
+    fn out_of_range_interval_is_rejected_and_keeps_the_old_one() {
+        let monitor = HealthMonitor::default();
 
-        tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+        assert!(monitor.set_interval(0).is_err());
+        assert!(monitor.set_interval(MAX_HEALTH_CHECK_INTERVAL_SECS + 1).is_err());
+        assert_eq!(
+            monitor.interval_secs.load(Ordering::Relaxed),
+            HEALTH_CHECK_INTERVAL.as_secs()
+        );
     }
 }
@@ -0,0 +1,240 @@
+//! Python agent process lifecycle module
+//!
+//! Spawns the agent runtime as a child process, supervises it from the health
+//! monitor, and restarts it when it dies or becomes unresponsive.
+
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::agent::{AgentClientBuilder, AgentHealth, HealthMonitor};
+use crate::config::AgentConfig;
+use crate::error::SlovoError;
+use crate::ws::AgentStreamState;
+
+/// Delay between health polls while waiting for the runtime to come up
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Grace period between SIGTERM and SIGKILL
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+/// Consecutive crash restarts before supervision gives up
+const MAX_CRASH_RESTARTS: u32 = 3;
+
+/// A running agent runtime child process
+pub struct AgentProcess {
+    child: Child,
+    config: AgentConfig,
+}
+
+impl AgentProcess {
+    /// Launch the configured Python entry point
+    pub fn start(config: &AgentConfig) -> Result<Self, SlovoError> {
+        let mut command = Command::new(&config.python_path);
+        command
+            .args(&config.agent_args)
+            .env("AGENT_HOST", &config.host)
+            .env("AGENT_PORT", config.port.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = &config.agent_dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command.spawn().map_err(|e| {
+            SlovoError::AgentConnection(format!(
+                "Failed to spawn agent runtime '{}': {}",
+                config.python_path, e
+            ))
+        })?;
+        info!("Spawned agent runtime (pid {:?})", child.id());
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_output(stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_output(stderr));
+        }
+
+        Ok(Self {
+            child,
+            config: config.clone(),
+        })
+    }
+
+    /// OS process id, if the process has not been reaped yet
+    pub fn pid(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Whether the process is still alive
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Ask the process to exit, killing it if it does not within the grace period
+    pub async fn stop(&mut self) -> Result<(), SlovoError> {
+        if !self.is_running() {
+            return Ok(());
+        }
+
+        let pid = self.pid();
+        terminate(&mut self.child)?;
+
+        match tokio::time::timeout(STOP_TIMEOUT, self.child.wait()).await {
+            Ok(status) => {
+                info!("Agent runtime (pid {:?}) exited: {:?}", pid, status?);
+            }
+            Err(_) => {
+                warn!("Agent runtime (pid {:?}) ignored SIGTERM, killing", pid);
+                self.child.kill().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the process and launch a fresh one with the same config
+    pub async fn restart(&mut self) -> Result<(), SlovoError> {
+        self.stop().await?;
+        *self = Self::start(&self.config)?;
+        Ok(())
+    }
+}
+
+/// Managed slot holding the agent process started by this app
+#[derive(Default)]
+pub struct AgentProcessState {
+    process: Mutex<Option<AgentProcess>>,
+    crash_restarts: AtomicU32,
+}
+
+impl AgentProcessState {
+    /// Start the agent (unless one is already running) and wait for it to become healthy
+    pub async fn start(&self, config: &AgentConfig) -> Result<AgentHealth, SlovoError> {
+        {
+            let mut slot = self.process.lock().await;
+            let running = slot.as_mut().is_some_and(AgentProcess::is_running);
+            if !running {
+                *slot = Some(AgentProcess::start(config)?);
+            }
+        }
+        self.crash_restarts.store(0, Ordering::Relaxed);
+        wait_until_healthy(config).await
+    }
+
+    /// Stop the tracked agent process; returns false when none was running
+    pub async fn stop(&self) -> Result<bool, SlovoError> {
+        match self.process.lock().await.take() {
+            Some(mut process) => {
+                process.stop().await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Replace any tracked process with a fresh one and wait for it to become healthy
+    pub async fn restart(&self, config: &AgentConfig) -> Result<AgentHealth, SlovoError> {
+        self.stop().await?;
+        self.start(config).await
+    }
+
+    /// Restart the tracked process if it has died; returns true when a restart happened
+    ///
+    /// Only processes started by this app are supervised, and supervision stops
+    /// after repeated crashes so a broken runtime is not respawned forever.
+    pub async fn supervise(&self) -> bool {
+        let mut slot = self.process.lock().await;
+        let Some(process) = slot.as_mut() else {
+            return false;
+        };
+        if process.is_running() {
+            return false;
+        }
+
+        let attempt = self.crash_restarts.fetch_add(1, Ordering::Relaxed) + 1;
+        if attempt > MAX_CRASH_RESTARTS {
+            error!("Agent runtime crashed {} times, giving up", MAX_CRASH_RESTARTS);
+            *slot = None;
+            return false;
+        }
+
+        warn!("Agent runtime exited unexpectedly, restarting (attempt {})", attempt);
+        match process.restart().await {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Failed to restart agent runtime: {}", e);
+                *slot = None;
+                false
+            }
+        }
+    }
+}
+
+/// Stop the agent runtime and close the stream ahead of app exit
+pub async fn shutdown(app: &AppHandle) {
+    info!("Shutting down agent connections");
+    // Stop supervising first so the runtime is not respawned as it exits
+    app.state::<HealthMonitor>().stop();
+    app.state::<AgentStreamState>().close();
+    if let Err(e) = app.state::<AgentProcessState>().stop().await {
+        warn!("Failed to stop agent runtime during shutdown: {}", e);
+    }
+}
+
+/// Poll the agent until it reports healthy or the startup timeout elapses
+async fn wait_until_healthy(config: &AgentConfig) -> Result<AgentHealth, SlovoError> {
+    let client = AgentClientBuilder::from_config(config).build()?;
+    let timeout = Duration::from_secs(config.startup_timeout_secs);
+
+    let poll = async {
+        loop {
+            if let Ok(health) = client.health_check().await {
+                if health.status == "healthy" {
+                    return health;
+                }
+            }
+            tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, poll).await.map_err(|_| {
+        SlovoError::AgentConnection(format!(
+            "Agent did not become healthy within {}s",
+            config.startup_timeout_secs
+        ))
+    })
+}
+
+/// Send SIGTERM so the runtime can shut down cleanly
+#[cfg(unix)]
+fn terminate(child: &mut Child) -> Result<(), SlovoError> {
+    if let Some(pid) = child.id() {
+        // SAFETY: `kill` has no memory-safety preconditions; the pid belongs to our child
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+    Ok(())
+}
+
+/// Windows has no SIGTERM equivalent for console-less children, so kill directly
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) -> Result<(), SlovoError> {
+    child.start_kill()?;
+    Ok(())
+}
+
+/// Relay the runtime's output into our log
+async fn forward_output<R: AsyncRead + Unpin>(stream: R) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!("agent: {}", line);
+    }
+}
@@ -0,0 +1,264 @@
+//! Chat message attachments
+//!
+//! Files travel to the agent base64-encoded; type and total size are checked
+//! before any request is made.
+
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::error::SlovoError;
+
+/// Largest combined size of a message's attachments, in decoded bytes
+pub const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+
+/// MIME types the agent accepts as attachments
+const SUPPORTED_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "text/plain",
+    "text/markdown",
+];
+
+/// Directories holding OS files that are never sent to the agent
+#[cfg(unix)]
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr", "/var",
+    "/System", "/Library", "/private/etc", "/private/var",
+];
+
+/// Directories holding OS files that are never sent to the agent
+#[cfg(windows)]
+const SYSTEM_DIRS: &[&str] = &[
+    r"C:\Windows",
+    r"C:\Program Files",
+    r"C:\Program Files (x86)",
+    r"C:\ProgramData",
+];
+
+/// A file sent alongside a chat message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub mime_type: String,
+    /// Base64-encoded contents
+    pub data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// An attachment as supplied by the frontend: inline data or a file to read
+#[derive(Debug, Deserialize)]
+pub struct AttachmentInput {
+    pub mime_type: String,
+    pub name: Option<String>,
+    /// Base64-encoded contents
+    pub data: Option<String>,
+    pub path: Option<PathBuf>,
+}
+
+impl AttachmentInput {
+    /// Load the contents, reading from disk when given a path
+    ///
+    /// Paths go through [`user_file`], so OS files are refused whatever type they claim.
+    pub fn resolve(self) -> Result<Attachment, SlovoError> {
+        let data = match (self.data, self.path) {
+            (Some(data), _) => data,
+            (None, Some(path)) => {
+                let path = user_file(&path)?;
+                let size = std::fs::metadata(&path)?.len();
+                if size > MAX_ATTACHMENT_BYTES as u64 {
+                    return Err(too_large(size as usize));
+                }
+                STANDARD.encode(std::fs::read(&path)?)
+            }
+            (None, None) => {
+                return Err(SlovoError::agent("attachment has neither data nor a path"));
+            }
+        };
+        Ok(Attachment {
+            mime_type: self.mime_type,
+            data,
+            name: self.name,
+        })
+    }
+}
+
+impl Attachment {
+    /// Decoded contents of the attachment
+    pub fn bytes(&self) -> Result<Vec<u8>, SlovoError> {
+        STANDARD
+            .decode(&self.data)
+            .map_err(|e| SlovoError::agent(format!("attachment is not valid base64: {}", e)))
+    }
+}
+
+/// MIME type for a file, judged by its extension
+pub fn mime_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "md" | "markdown" => "text/markdown",
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "txt" | "log" | "rs" | "py" | "js" | "ts" | "tsx" | "jsx" | "c" | "h" | "cpp" | "go"
+        | "java" | "toml" | "yaml" | "yml" | "sh" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Resolve `path` and refuse it unless it is a file outside the operating system directories
+///
+/// Links and `..` are resolved first, so they cannot lead around the check.
+pub fn user_file(path: &Path) -> Result<PathBuf, SlovoError> {
+    let resolved = path.canonicalize()?;
+    if in_system_dir(&resolved) || !resolved.is_file() {
+        return Err(SlovoError::VoiceError(format!(
+            "cannot attach {}: not a user file",
+            path.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+fn in_system_dir(path: &Path) -> bool {
+    // Windows paths compare without case, and canonical ones carry a `\\?\` prefix
+    let normalize = |path: &str| {
+        if cfg!(windows) {
+            path.to_lowercase()
+        } else {
+            path.to_string()
+        }
+    };
+    let path = PathBuf::from(normalize(path.to_string_lossy().trim_start_matches(r"\\?\")));
+    SYSTEM_DIRS.iter().any(|dir| path.starts_with(normalize(dir)))
+}
+
+/// Reject unsupported types and attachments that together exceed the size cap
+pub fn validate(attachments: &[Attachment]) -> Result<(), SlovoError> {
+    let mut total = 0;
+    for attachment in attachments {
+        if !SUPPORTED_MIME_TYPES.contains(&attachment.mime_type.as_str()) {
+            return Err(SlovoError::agent(format!(
+                "unsupported attachment type: {}",
+                attachment.mime_type
+            )));
+        }
+        total += attachment.bytes()?.len();
+        if total > MAX_ATTACHMENT_BYTES {
+            return Err(too_large(total));
+        }
+    }
+    Ok(())
+}
+
+fn too_large(size: usize) -> SlovoError {
+    SlovoError::agent(format!(
+        "attachments too large: {} bytes (limit {})",
+        size, MAX_ATTACHMENT_BYTES
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(mime_type: &str, bytes: &[u8]) -> Attachment {
+        Attachment {
+            mime_type: mime_type.to_string(),
+            data: STANDARD.encode(bytes),
+            name: None,
+        }
+    }
+
+    /// A scratch directory outside the system ones; the OS temp dir is under /var on macOS
+    fn user_dir() -> tempfile::TempDir {
+        tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap()
+    }
+
+    #[test]
+    fn validate_rejects_unsupported_types() {
+        let error = validate(&[attachment("text/plain", b"hi"), attachment("text/html", b"<p>")])
+            .unwrap_err();
+
+        assert!(error.to_string().contains("unsupported attachment type: text/html"), "{}", error);
+    }
+
+    #[test]
+    fn validate_caps_the_combined_size() {
+        let half = vec![0; MAX_ATTACHMENT_BYTES / 2];
+        let at_cap = [attachment("image/png", &half), attachment("image/png", &half)];
+        let over_cap = [
+            attachment("image/png", &half),
+            attachment("image/png", &half),
+            attachment("image/png", b"x"),
+        ];
+
+        assert!(validate(&at_cap).is_ok());
+        assert!(validate(&over_cap).unwrap_err().to_string().contains("too large"));
+    }
+
+    #[test]
+    fn user_file_accepts_a_file_in_a_user_directory() {
+        let dir = user_dir();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+
+        assert_eq!(user_file(&path).unwrap(), path.canonicalize().unwrap());
+        assert!(user_file(dir.path()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn user_file_refuses_system_files_however_they_are_reached() {
+        let dir = user_dir();
+        let up = "../".repeat(dir.path().components().count());
+        let link = dir.path().join("passwd");
+        std::os::unix::fs::symlink("/etc/passwd", &link).unwrap();
+
+        assert!(user_file(Path::new("/etc/passwd")).is_err());
+        assert!(user_file(&dir.path().join(up).join("etc/passwd")).is_err());
+        assert!(user_file(&link).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_reads_user_files_but_not_system_ones() {
+        let dir = user_dir();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let input = |path: &str| AttachmentInput {
+            mime_type: "text/plain".to_string(),
+            name: None,
+            data: None,
+            path: Some(PathBuf::from(path)),
+        };
+
+        let resolved = input(path.to_str().unwrap()).resolve().unwrap();
+
+        assert_eq!(resolved.bytes().unwrap(), b"hello");
+        assert!(input("/etc/passwd").resolve().is_err());
+    }
+
+    #[test]
+    fn mime_type_follows_the_extension_ignoring_case() {
+        assert_eq!(mime_type_for(Path::new("photo.JPG")), "image/jpeg");
+        assert_eq!(mime_type_for(Path::new("notes.md")), "text/markdown");
+        assert_eq!(mime_type_for(Path::new("main.rs")), "text/plain");
+        assert_eq!(mime_type_for(Path::new("archive.tar.gz")), "application/octet-stream");
+        assert_eq!(mime_type_for(Path::new("Makefile")), "application/octet-stream");
+    }
+}
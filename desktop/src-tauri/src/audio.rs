@@ -0,0 +1,210 @@
+//! Audio format detection module
+//!
+//! Sniffs container magic bytes so unsupported or truncated buffers are rejected
+//! before they are sent to the agent's speech-to-text endpoint.
+
+use serde::Serialize;
+
+use crate::error::SlovoError;
+
+/// Smallest buffer worth sending for transcription
+const MIN_AUDIO_BYTES: usize = 1024;
+
+/// WAV `fmt ` codec tags accepted by the STT endpoint
+const WAV_FORMAT_PCM: u16 = 1;
+const WAV_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Supported audio container formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Ogg,
+    Flac,
+    Mp3,
+}
+
+impl AudioFormat {
+    /// Short name sent to the agent alongside the audio
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "mp3",
+        }
+    }
+
+    /// MIME type used as the request content type
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Mp3 => "audio/mpeg",
+        }
+    }
+}
+
+/// Detect the audio container format from its magic bytes
+pub fn detect_format(data: &[u8]) -> Option<AudioFormat> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        return Some(AudioFormat::Wav);
+    }
+    if data.starts_with(b"OggS") {
+        return Some(AudioFormat::Ogg);
+    }
+    if data.starts_with(b"fLaC") {
+        return Some(AudioFormat::Flac);
+    }
+    if data.starts_with(b"ID3") || is_mp3_frame_sync(data) {
+        return Some(AudioFormat::Mp3);
+    }
+    None
+}
+
+/// Validate an audio buffer and return its detected format
+pub fn validate_audio(data: &[u8]) -> Result<AudioFormat, SlovoError> {
+    if data.len() < MIN_AUDIO_BYTES {
+        return Err(SlovoError::VoiceError(format!(
+            "Audio buffer too short: {} bytes (minimum {})",
+            data.len(),
+            MIN_AUDIO_BYTES
+        )));
+    }
+
+    let format = detect_format(data)
+        .ok_or_else(|| SlovoError::VoiceError("Unrecognized audio format".to_string()))?;
+
+    match format {
+        AudioFormat::Wav => validate_wav_codec(data)?,
+        AudioFormat::Ogg => validate_ogg_codec(data)?,
+        AudioFormat::Flac | AudioFormat::Mp3 => {}
+    }
+
+    Ok(format)
+}
+
+/// MPEG audio frames start with 11 set sync bits
+fn is_mp3_frame_sync(data: &[u8]) -> bool {
+    data.len() >= 2 && data[0] == 0xFF && (data[1] & 0xE0) == 0xE0
+}
+
+fn validate_wav_codec(data: &[u8]) -> Result<(), SlovoError> {
+    if data.len() < 22 || &data[12..16] != b"fmt " {
+        return Err(SlovoError::VoiceError("WAV file is missing its fmt chunk".to_string()));
+    }
+
+    let codec = u16::from_le_bytes([data[20], data[21]]);
+    match codec {
+        WAV_FORMAT_PCM | WAV_FORMAT_IEEE_FLOAT => Ok(()),
+        other => Err(SlovoError::VoiceError(format!(
+            "Unsupported WAV codec: 0x{:04x}",
+            other
+        ))),
+    }
+}
+
+fn validate_ogg_codec(data: &[u8]) -> Result<(), SlovoError> {
+    // The first page's payload starts after the 27-byte header and segment table
+    let payload = data
+        .get(26)
+        .map(|&segments| 27 + segments as usize)
+        .and_then(|start| data.get(start..))
+        .unwrap_or_default();
+
+    if payload.starts_with(b"OpusHead") || payload.starts_with(b"\x01vorbis") {
+        Ok(())
+    } else {
+        Err(SlovoError::VoiceError(
+            "Unsupported Ogg codec (expected Opus or Vorbis)".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A canonical 44-byte WAV header with the given codec tag, padded to `MIN_AUDIO_BYTES`
+    fn wav(codec: u16) -> Vec<u8> {
+        let mut data = Vec::with_capacity(MIN_AUDIO_BYTES);
+        data.extend_from_slice(b"RIFF");
+        data.extend_from_slice(&(MIN_AUDIO_BYTES as u32 - 8).to_le_bytes());
+        data.extend_from_slice(b"WAVEfmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&codec.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes()); // mono
+        data.extend_from_slice(&16_000u32.to_le_bytes());
+        data.extend_from_slice(&32_000u32.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&16u16.to_le_bytes());
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&(MIN_AUDIO_BYTES as u32 - 44).to_le_bytes());
+        data.resize(MIN_AUDIO_BYTES, 0);
+        data
+    }
+
+    /// A first Ogg page with one segment carrying `payload`
+    fn ogg(payload: &[u8]) -> Vec<u8> {
+        let mut data = b"OggS".to_vec();
+        data.push(0); // version
+        data.push(0x02); // beginning of stream
+        data.extend_from_slice(&[0; 20]); // granule, serial, sequence, checksum
+        data.push(1);
+        data.push(payload.len() as u8);
+        data.extend_from_slice(payload);
+        data.resize(MIN_AUDIO_BYTES, 0);
+        data
+    }
+
+    fn padded(header: &[u8]) -> Vec<u8> {
+        let mut data = header.to_vec();
+        data.resize(MIN_AUDIO_BYTES, 0);
+        data
+    }
+
+    #[test]
+    fn accepts_pcm_and_float_wav() {
+        assert_eq!(validate_audio(&wav(WAV_FORMAT_PCM)).unwrap(), AudioFormat::Wav);
+        assert_eq!(validate_audio(&wav(WAV_FORMAT_IEEE_FLOAT)).unwrap(), AudioFormat::Wav);
+    }
+
+    #[test]
+    fn rejects_compressed_wav() {
+        // 0x0055 is MP3-in-WAV
+        assert!(validate_audio(&wav(0x0055)).is_err());
+    }
+
+    #[test]
+    fn accepts_opus_and_vorbis_ogg() {
+        let opus = b"OpusHead\x01\x01\x38\x01\x80\x3e\x00\x00\x00\x00\x00";
+        assert_eq!(validate_audio(&ogg(opus)).unwrap(), AudioFormat::Ogg);
+        assert_eq!(validate_audio(&ogg(b"\x01vorbis\x00\x00\x00\x00")).unwrap(), AudioFormat::Ogg);
+    }
+
+    #[test]
+    fn rejects_other_ogg_codecs() {
+        assert!(validate_audio(&ogg(b"\x80theora")).is_err());
+    }
+
+    #[test]
+    fn detects_flac() {
+        let streaminfo = b"fLaC\x00\x00\x00\x22";
+        assert_eq!(validate_audio(&padded(streaminfo)).unwrap(), AudioFormat::Flac);
+    }
+
+    #[test]
+    fn detects_mp3_with_and_without_id3() {
+        assert_eq!(validate_audio(&padded(b"ID3\x04\x00\x00")).unwrap(), AudioFormat::Mp3);
+        // MPEG-1 Layer III frame header, 128 kbit/s at 44.1 kHz
+        assert_eq!(validate_audio(&padded(&[0xFF, 0xFB, 0x90, 0x64])).unwrap(), AudioFormat::Mp3);
+    }
+
+    #[test]
+    fn rejects_unknown_and_short_buffers() {
+        assert_eq!(detect_format(&padded(b"\x1aE\xdf\xa3")), None); // Matroska
+        assert!(validate_audio(&padded(b"\x1aE\xdf\xa3")).is_err());
+        assert!(validate_audio(&wav(WAV_FORMAT_PCM)[..MIN_AUDIO_BYTES - 1]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Audit log of agent interactions
+//!
+//! Appends one JSON line per chat request and response to `audit.log` in the
+//! app data directory. Message content is stored as a SHA-256 hash unless
+//! full-content logging is enabled for development. The file is rotated to
+//! `audit.log.1` once it grows past the configured size.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::config::SlovoConfig;
+
+/// Audit log file name inside the app data directory
+pub const AUDIT_LOG_FILE: &str = "audit.log";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Request,
+    Response,
+}
+
+/// One line of the audit log
+#[derive(Debug, Serialize)]
+struct AuditEntry<'a> {
+    timestamp: String,
+    direction: Direction,
+    conversation_id: Option<&'a str>,
+    message_hash: String,
+    /// Plaintext content, only when full-content logging is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    status_code: Option<u16>,
+    latency_ms: Option<u64>,
+}
+
+/// Append-only JSON-lines log of what was sent to and received from the agent
+pub struct AuditLogger {
+    path: PathBuf,
+    max_bytes: u64,
+    full_content: bool,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLogger {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, full_content: bool) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            full_content,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Logger writing to `dir/audit.log` with the user's audit settings
+    pub fn from_config(dir: &Path, config: &SlovoConfig) -> Self {
+        Self::new(
+            dir.join(AUDIT_LOG_FILE),
+            config.audit_log_max_bytes,
+            config.audit_log_full_content,
+        )
+    }
+
+    /// Record a message about to be sent to the agent
+    pub fn record_request(&self, conversation_id: Option<&str>, message: &str) {
+        self.write(Direction::Request, conversation_id, message, None, None);
+    }
+
+    /// Record the agent's reply; `status_code` is `None` if no response arrived
+    pub fn record_response(
+        &self,
+        conversation_id: Option<&str>,
+        message: &str,
+        status_code: Option<u16>,
+        latency: Duration,
+    ) {
+        let latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        self.write(Direction::Response, conversation_id, message, status_code, Some(latency_ms));
+    }
+
+    /// Push everything written so far to disk
+    pub fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            if let Err(e) = file.flush().and_then(|()| file.sync_data()) {
+                warn!("Failed to flush audit log: {}", e);
+            }
+        }
+    }
+
+    fn write(
+        &self,
+        direction: Direction,
+        conversation_id: Option<&str>,
+        message: &str,
+        status_code: Option<u16>,
+        latency_ms: Option<u64>,
+    ) {
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            direction,
+            conversation_id,
+            message_hash: sha256_hex(message),
+            message: self.full_content.then_some(message),
+            status_code,
+            latency_ms,
+        };
+
+        // Auditing must never break a chat request, so failures are only logged
+        if let Err(e) = self.append(&entry) {
+            warn!("Failed to write audit log: {}", e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap();
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            *file = None;
+            std::fs::rename(&self.path, rotated_path(&self.path))?;
+        }
+
+        if file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            *file = Some(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+        file.as_mut().expect("audit log file was just opened").write_all(&line)
+    }
+}
+
+/// `audit.log` -> `audit.log.1`
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
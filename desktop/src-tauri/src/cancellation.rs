@@ -0,0 +1,60 @@
+//! Cancellation registry
+//!
+//! Long-running commands register a token under a caller-chosen id so a
+//! separate command can cancel them. Entries older than the TTL are evicted
+//! in case a command never cleaned up after itself.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+/// How long a registered operation is remembered by default
+const DEFAULT_TTL: Duration = Duration::from_secs(600);
+
+/// Managed map of cancellable operations keyed by id
+pub struct CancellationRegistry {
+    ttl: Duration,
+    tokens: Mutex<HashMap<String, (CancellationToken, Instant)>>,
+}
+
+impl Default for CancellationRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl CancellationRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register an operation and return its token
+    pub fn register(&self, id: impl Into<String>) -> CancellationToken {
+        let token = CancellationToken::new();
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|_, (_, registered_at)| registered_at.elapsed() < self.ttl);
+        tokens.insert(id.into(), (token.clone(), Instant::now()));
+        token
+    }
+
+    /// Cancel an operation; returns false if no operation has that id
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.tokens.lock().unwrap().get(id) {
+            Some((token, _)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a finished operation
+    pub fn remove(&self, id: &str) {
+        self.tokens.lock().unwrap().remove(id);
+    }
+}
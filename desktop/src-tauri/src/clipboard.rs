@@ -0,0 +1,53 @@
+//! System clipboard text access
+//!
+//! Goes through the clipboard-manager plugin. An empty clipboard, or one
+//! holding something other than text, reads as no text rather than an error.
+//! Calls block until the platform clipboard answers.
+
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tracing::debug;
+
+use crate::error::SlovoError;
+
+/// Text on the clipboard; `None` if it is empty or holds something other than text
+pub fn read_text<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    text_or_none(app.clipboard().read_text())
+}
+
+/// Replace the clipboard contents with `text`
+pub fn write_text<R: Runtime>(app: &AppHandle<R>, text: &str) -> Result<(), SlovoError> {
+    app.clipboard().write_text(text).map_err(|e| {
+        SlovoError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    })
+}
+
+fn text_or_none(result: Result<String, tauri_plugin_clipboard_manager::Error>) -> Option<String> {
+    match result {
+        Ok(text) => Some(text).filter(|text| !text.is_empty()),
+        // The platform clipboard reports "nothing to give" as an error
+        Err(e) => {
+            debug!("No text on the clipboard: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri_plugin_clipboard_manager::Error;
+
+    use super::*;
+
+    #[test]
+    fn empty_clipboard_has_no_text() {
+        assert_eq!(text_or_none(Ok(String::new())), None);
+        let not_text = Error::Clipboard("The clipboard contents were not available".to_string());
+        assert_eq!(text_or_none(Err(not_text)), None);
+    }
+
+    #[test]
+    fn clipboard_text_is_returned_as_is() {
+        assert_eq!(text_or_none(Ok(" hello\n".to_string())).as_deref(), Some(" hello\n"));
+    }
+}
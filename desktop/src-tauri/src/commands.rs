@@ -1,18 +1,77 @@
 //! Tauri commands for frontend-backend communication
 
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
-use tracing::{error, info};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, OnceCell};
+use tracing::{error, info, info_span, warn, Instrument};
+use uuid::Uuid;
 
-use crate::agent::AgentClient;
+use crate::agent::{
+    AgentClient, AgentClientBuilder, AgentInfoResponse, AgentWarmup, Capabilities, ChatOptions,
+    ChatRequest, ChatResponse, FileAttachmentRequest, HealthMonitor, ModelInfo, TokenUsage,
+    TranscriptUpdate, TranscriptionResponse, VisionChatRequest, CAPABILITY_TRANSCRIBE,
+    CAPABILITY_TTS, CAPABILITY_VISION,
+};
+use crate::agent_process::AgentProcessState;
+use crate::attachment::{self, Attachment, AttachmentInput};
+use crate::audio::{self, AudioFormat};
+use crate::cancellation::CancellationRegistry;
+use crate::clipboard;
+use crate::config::{ConfigSummary, ProfiledConfig, SharedConfig, SlovoConfig};
+use crate::conversation::{
+    self, ConversationListResponse, ConversationManager, MAX_SEARCH_RESULTS,
+};
+use crate::credentials;
+use crate::devices::{self, AudioDevices, DeviceKind};
 use crate::error::SlovoError;
+use crate::events::{
+    emit_agent_status, AgentBusy, AgentStatus, ConversationTitled, LatestAgentStatus, RequestGuard,
+};
+use crate::export::{self, ExportFormat};
+use crate::health_history::{self, HealthSnapshot};
+use crate::logging::{self, MessageContent};
+use crate::metrics::Metrics;
+use crate::permissions::{self, MicrophonePermission, PermissionState, PermissionStatus};
+use crate::queue::{MessageQueue, QueuedMessage};
+use crate::rate_limit::RateLimiter;
+use crate::recording::{CaptureFormat, RecordingState};
+use crate::speech::SpeechPlayback;
+use crate::screenshot;
+use crate::search::SearchResult;
+use crate::shutdown::InFlightRequests;
+use crate::state::AppState;
+use crate::theme;
+use crate::tray::{self, TrayManager, TrayState};
+use crate::vad::{self, EndpointDetector, VadEvent};
+use crate::wake_word::WakeWordDetector;
+use crate::window_state;
+use crate::ws::AgentStreamState;
+
+/// How often auto-stopping recordings are checked for end of speech
+const VAD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How old a health check `check_agent_status` may answer from
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(5);
 
 /// Response type for command results
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CommandResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// The agent's machine-readable error code, when it sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
 }
 
 impl<T> CommandResponse<T> {
@@ -21,6 +80,7 @@ impl<T> CommandResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_code: None,
         }
     }
 
@@ -29,6 +89,19 @@ impl<T> CommandResponse<T> {
             success: false,
             data: None,
             error: Some(error.to_string()),
+            error_code: None,
+        }
+    }
+
+    /// Failure carrying the agent's error code so the frontend can branch on it
+    pub fn from_error(error: SlovoError) -> Self {
+        let error_code = match &error {
+            SlovoError::AgentError { code, .. } => code.clone(),
+            _ => None,
+        };
+        Self {
+            error_code,
+            ..Self::err(error)
         }
     }
 }
@@ -40,35 +113,705 @@ pub struct AgentStatusResponse {
     pub version: Option<String>,
 }
 
-/// Chat message response
+/// Result of a one-off connection test
 #[derive(Debug, Serialize)]
+pub struct PingResponse {
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub version: Option<String>,
+    /// Underlying failure, e.g. connection refused, timeout, or HTTP status
+    pub error: Option<String>,
+}
+
+/// Chat message response
+#[derive(Debug, Clone, Serialize)]
 pub struct ChatMessageResponse {
     pub id: String,
     pub response: String,
     pub conversation_id: String,
     pub reasoning: Option<String>,
+    pub usage: Option<TokenUsage>,
+}
+
+impl From<ChatResponse> for ChatMessageResponse {
+    fn from(response: ChatResponse) -> Self {
+        Self {
+            id: response.id,
+            response: response.response,
+            conversation_id: response.conversation_id,
+            reasoning: response.reasoning,
+            usage: response.usage,
+        }
+    }
+}
+
+/// Token and cost totals for a conversation
+#[derive(Debug, Serialize)]
+pub struct ConversationStats {
+    pub message_count: usize,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    /// Based on the configured cost per 1000 tokens
+    pub estimated_cost: f64,
+}
+
+/// Outcome of `send_message_to_agent`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SendMessageResponse {
+    Sent(ChatMessageResponse),
+    /// The agent was unreachable; the message will be sent when it reconnects
+    Queued { queue_id: String },
+}
+
+/// Payload of `queued-message-sent`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedMessageSent {
+    pub queue_id: String,
+    pub response: ChatMessageResponse,
+}
+
+/// Result of a multi-message send
+#[derive(Debug, Serialize)]
+pub struct SendMessagesResponse {
+    pub responses: Vec<ChatMessageResponse>,
+    pub conversation_id: Option<String>,
+    /// Index of the message that failed, when the batch stopped early
+    pub failed_index: Option<usize>,
+    pub cancelled: bool,
+}
+
+/// One independent message in a concurrent batch
+#[derive(Debug, Deserialize)]
+pub struct BatchMessage {
+    pub message: String,
+    pub conversation_id: Option<String>,
+}
+
+/// Outcome of one message in a concurrent batch
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    /// Position of the message in the submitted batch
+    pub index: usize,
+    pub response: Option<ChatMessageResponse>,
+    pub error: Option<String>,
 }
 
 /// Process voice input audio data
 #[tauri::command]
-pub async fn process_voice_input(audio_data: Vec<u8>) -> Result<String, String> {
+pub async fn process_voice_input(
+    state: State<'_, AppState>,
+    audio_data: Vec<u8>,
+) -> Result<String, SlovoError> {
+    Ok(transcribe_audio(&state.client, &state.config, audio_data).await?.text)
+}
+
+/// Whether a transcription was confident enough to use
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionStatus {
+    Ok,
+    /// Below `min_transcription_confidence`; the user should repeat themselves
+    LowConfidence,
+}
+
+/// Transcription together with the agent's confidence in it
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceTranscription {
+    pub status: TranscriptionStatus,
+    #[serde(flatten)]
+    pub transcription: TranscriptionResponse,
+}
+
+/// Transcribe voice input, flagging results the agent was unsure of
+///
+/// Unlike `process_voice_input`, a low-confidence result is returned with
+/// status `low_confidence` so the UI can ask the user to say it again.
+#[tauri::command]
+pub async fn transcribe_voice_input(
+    app: AppHandle,
+    audio_data: Vec<u8>,
+) -> CommandResponse<VoiceTranscription> {
+    let client = &AppState::of(&app).client;
+    let config = &AppState::of(&app).config;
+    match transcribe_audio(client, config, audio_data).await {
+        Ok(transcription) => CommandResponse::ok(checked_transcription(config, transcription)),
+        Err(e) => {
+            error!("Failed to transcribe voice input: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+/// Compare a transcription against the configured confidence threshold
+fn checked_transcription(
+    config: &SharedConfig,
+    transcription: TranscriptionResponse,
+) -> VoiceTranscription {
+    let threshold = config.read().unwrap().min_transcription_confidence;
+    let status = if transcription.is_below(threshold) {
+        warn!(
+            "Transcription confidence {:?} is below {}",
+            transcription.confidence, threshold
+        );
+        TranscriptionStatus::LowConfidence
+    } else {
+        TranscriptionStatus::Ok
+    };
+    VoiceTranscription { status, transcription }
+}
+
+/// Trim silence from recorded audio and transcribe it with the agent
+async fn transcribe_audio(
+    client: &AgentClient,
+    config: &SharedConfig,
+    audio_data: Vec<u8>,
+) -> Result<TranscriptionResponse, SlovoError> {
     info!("Processing voice input: {} bytes", audio_data.len());
-    
-    // TODO: Implement actual voice processing
-    // This would:
-    // 1. Send audio to STT service
-    // 2. Return transcribed text
-    
-    // For now, return a placeholder
-    Ok("Voice input processing not yet implemented".to_string())
+
+    let format = audio::validate_audio(&audio_data)?;
+    info!("Detected audio format: {}", format.as_str());
+
+    let (threshold, min_speech_ms) = {
+        let config = config.read().unwrap();
+        (config.vad_threshold, config.vad_min_speech_ms)
+    };
+    let audio_data = match format {
+        AudioFormat::Wav if threshold > 0 => {
+            vad::trim_wav(&audio_data, threshold, min_speech_ms).unwrap_or(audio_data)
+        }
+        _ => audio_data,
+    };
+
+    client.require(CAPABILITY_TRANSCRIBE).await?;
+    let transcription = client.transcribe(audio_data, format).await?;
+    let show = config.read().unwrap().log_message_content;
+    info!("Transcribed: {}", MessageContent::new(&transcription.text, show));
+    Ok(transcription)
+}
+
+/// Message text for a log line, redacted unless `log_message_content` is on
+fn logged<'a>(app: &AppHandle, text: &'a str) -> MessageContent<'a> {
+    let show = AppState::of(app).config.read().unwrap().log_message_content;
+    MessageContent::new(text, show)
+}
+
+/// Speak text aloud, interrupting any reply that is still playing
+#[tauri::command]
+pub async fn speak(app: AppHandle, text: String, voice: Option<String>) -> Result<(), SlovoError> {
+    play_speech(&app, &text, voice).await
+}
+
+/// Speak text aloud; resolves to true once playback has finished or been interrupted
+#[tauri::command]
+pub async fn speak_text(app: AppHandle, text: String, voice: Option<String>) -> CommandResponse<bool> {
+    match play_speech(&app, &text, voice).await {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to speak text: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Synthesize `text` with the configured voice defaults and play it
+///
+/// Playback never overlaps: a new utterance interrupts the one in progress.
+/// Emits `speech-ended` when an utterance plays to the end.
+async fn play_speech(app: &AppHandle, text: &str, voice: Option<String>) -> Result<(), SlovoError> {
+    let client = &AppState::of(app).client;
+    let config = &AppState::of(app).config;
+    client.require(CAPABILITY_TTS).await.map_err(|e| match e {
+        SlovoError::AgentError { .. } => SlovoError::agent("TTS not supported"),
+        other => other,
+    })?;
+
+    let (voice, speed) = {
+        let config = config.read().unwrap();
+        (voice.or_else(|| config.tts_voice.clone()), config.tts_speed)
+    };
+    let speech = client.synthesize(text, voice.as_deref(), speed).await?;
+    info!("Playing {} bytes of synthesized speech", speech.audio.len());
+
+    let device = devices::saved_device(app, DeviceKind::Output).await;
+    if app.state::<SpeechPlayback>().play(speech, device).await? {
+        let _ = app.emit("speech-ended", ());
+    }
+    Ok(())
+}
+
+/// Stop speaking; returns false if nothing was playing
+#[tauri::command]
+pub async fn stop_speaking(app: AppHandle) -> CommandResponse<bool> {
+    CommandResponse::ok(app.state::<SpeechPlayback>().stop())
+}
+
+/// Whether a reply is currently being spoken
+#[tauri::command]
+pub async fn is_speaking(app: AppHandle) -> CommandResponse<bool> {
+    CommandResponse::ok(app.state::<SpeechPlayback>().is_speaking())
+}
+
+/// List the microphones and speakers available on this machine
+#[tauri::command]
+pub async fn list_audio_devices() -> CommandResponse<AudioDevices> {
+    match tauri::async_runtime::spawn_blocking(devices::list_devices).await {
+        Ok(Ok(devices)) => CommandResponse::ok(devices),
+        Ok(Err(e)) => {
+            error!("Failed to list audio devices: {}", e);
+            CommandResponse::err(e)
+        }
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Select the microphone to record from and save the choice
+#[tauri::command]
+pub async fn set_audio_device(app: AppHandle, id: String) -> CommandResponse<bool> {
+    let lookup = id.clone();
+    match tauri::async_runtime::spawn_blocking(move || devices::input_device_exists(&lookup)).await {
+        Ok(Ok(true)) => {}
+        Ok(Ok(false)) => {
+            return CommandResponse::err(SlovoError::VoiceError(format!("Audio device not found: {}", id)))
+        }
+        Ok(Err(e)) => return CommandResponse::err(e),
+        Err(e) => return CommandResponse::err(e),
+    }
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.preferred_input_device = Some(id);
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to save audio device preference: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Select the microphone and speaker to use and save the choice
+///
+/// `None` for either means the system default.
+#[tauri::command]
+pub async fn set_audio_devices(
+    app: AppHandle,
+    input: Option<String>,
+    output: Option<String>,
+) -> CommandResponse<bool> {
+    let (lookup_input, lookup_output) = (input.clone(), output.clone());
+    let checked = tauri::async_runtime::spawn_blocking(move || -> Result<(), SlovoError> {
+        let missing = match (&lookup_input, &lookup_output) {
+            (Some(id), _) if !devices::input_device_exists(id)? => lookup_input,
+            (_, Some(id)) if !devices::output_device_exists(id)? => lookup_output,
+            _ => return Ok(()),
+        };
+        Err(SlovoError::VoiceError(format!(
+            "Audio device not found: {}",
+            missing.unwrap_or_default()
+        )))
+    })
+    .await;
+    match checked {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return CommandResponse::err(e),
+        Err(e) => return CommandResponse::err(e),
+    }
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.preferred_input_device = input;
+    config.preferred_output_device = output;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to save audio device preferences: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Listen for `keyword` in the background and remember it for next launch
+#[tauri::command]
+pub async fn enable_wake_word(app: AppHandle, keyword: String) -> CommandResponse<bool> {
+    let keyword = keyword.trim().to_string();
+    if keyword.is_empty() {
+        return CommandResponse::err(SlovoError::ConfigError("Wake word cannot be empty".to_string()));
+    }
+
+    if let Err(e) = app
+        .state::<WakeWordDetector>()
+        .start(app.clone(), keyword.clone())
+        .await
+    {
+        error!("Failed to start wake-word detection: {}", e);
+        return CommandResponse::err(e);
+    }
+
+    match save_wake_word(&app, Some(keyword)) {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Stop wake-word detection; returns false if it was not running
+#[tauri::command]
+pub async fn disable_wake_word(app: AppHandle) -> CommandResponse<bool> {
+    let stopped = app.state::<WakeWordDetector>().stop();
+    match save_wake_word(&app, None) {
+        Ok(()) => CommandResponse::ok(stopped),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+fn save_wake_word(app: &AppHandle, keyword: Option<String>) -> Result<(), SlovoError> {
+    let shared = &AppState::of(app).config;
+    let mut config = shared.write().unwrap();
+    config.wake_word = keyword;
+    config.save(app).map_err(|e| {
+        error!("Failed to save wake word: {}", e);
+        e
+    })
+}
+
+/// Start recording from a microphone (the saved preference or system default when omitted)
+#[tauri::command]
+pub async fn start_recording(app: AppHandle, device_id: Option<String>) -> CommandResponse<bool> {
+    if let Err(e) = ensure_microphone_access(&app).await {
+        return CommandResponse::from_error(e);
+    }
+
+    let device_id = match device_id {
+        Some(id) => Some(id),
+        None => devices::saved_device(&app, DeviceKind::Input).await,
+    };
+    let format = CaptureFormat::from_config(&AppState::of(&app).config.read().unwrap());
+
+    match app.state::<RecordingState>().start(device_id, format).await {
+        Ok(session) => {
+            app.state::<TrayManager>().listening_started(&app);
+            if let Some(detector) = endpoint_detector(&app, session) {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    watch_for_end_of_speech(handle, session, detector).await;
+                });
+            }
+            CommandResponse::ok(true)
+        }
+        Err(e) => {
+            error!("Failed to start recording: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Current state of the microphone and notification permissions
+#[tauri::command]
+pub async fn check_permissions(app: AppHandle) -> CommandResponse<PermissionStatus> {
+    CommandResponse::ok(permissions::check(&app))
+}
+
+/// Ask the OS for microphone access, prompting the user on macOS if not yet asked
+#[tauri::command]
+pub async fn request_microphone_permission(app: AppHandle) -> CommandResponse<PermissionState> {
+    let state = app.state::<MicrophonePermission>().request().await;
+    info!("Microphone permission: {:?}", state);
+    CommandResponse::ok(state)
+}
+
+/// Fail unless the app may use the microphone
+///
+/// Asks on first use; a refusal is rechecked in case it was changed in system settings.
+async fn ensure_microphone_access(app: &AppHandle) -> Result<(), SlovoError> {
+    let microphone = app.state::<MicrophonePermission>();
+    let permission = match microphone.get() {
+        PermissionState::Granted => PermissionState::Granted,
+        _ => microphone.request().await,
+    };
+    if permission != PermissionState::Granted {
+        warn!("Microphone permission is {:?}; not recording", permission);
+        return Err(SlovoError::VoiceError(
+            "Microphone access is denied; allow it in the system privacy settings".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Payload of `transcript-stream-error`
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptStreamError {
+    pub message: String,
+    /// Everything transcribed before the failure, final segments and the last partial
+    pub partial: String,
+}
+
+/// Managed handle on the running live transcription, if any
+#[derive(Default)]
+pub struct LiveTranscription(Mutex<Option<LiveSession>>);
+
+struct LiveSession {
+    stop: oneshot::Sender<()>,
+    task: tauri::async_runtime::JoinHandle<Result<String, SlovoError>>,
+}
+
+/// Start recording and stream the audio to the agent for live captions
+///
+/// Captions arrive as `partial-transcript` events, replaced by a
+/// `final-transcript` once the agent settles on each segment.
+#[tauri::command]
+pub async fn start_live_transcription(
+    app: AppHandle,
+    device_id: Option<String>,
+) -> CommandResponse<bool> {
+    match begin_live_transcription(&app, device_id).await {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to start live transcription: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+async fn begin_live_transcription(
+    app: &AppHandle,
+    device_id: Option<String>,
+) -> Result<(), SlovoError> {
+    if app.state::<LiveTranscription>().0.lock().unwrap().is_some() {
+        return Err(SlovoError::VoiceError("Live transcription is already running".to_string()));
+    }
+    ensure_microphone_access(app).await?;
+    AppState::of(app).client.require(CAPABILITY_TRANSCRIBE).await?;
+
+    let device_id = match device_id {
+        Some(id) => Some(id),
+        None => devices::saved_device(app, DeviceKind::Input).await,
+    };
+    let format = CaptureFormat::from_config(&AppState::of(app).config.read().unwrap());
+    let recording = app.state::<RecordingState>();
+    let session = recording.start(device_id, format).await?;
+    let Some((sample_rate, channels)) = recording.format(session) else {
+        return Err(SlovoError::VoiceError("Recording stopped unexpectedly".to_string()));
+    };
+    app.state::<TrayManager>().listening_started(app);
+
+    let (stop, stopped) = oneshot::channel();
+    let task = tauri::async_runtime::spawn(run_live_transcription(
+        app.clone(),
+        session,
+        sample_rate,
+        channels,
+        stopped,
+    ));
+    *app.state::<LiveTranscription>().0.lock().unwrap() = Some(LiveSession { stop, task });
+    info!("Live transcription started");
+    Ok(())
+}
+
+/// Stop live transcription, returning the full transcript once the agent has flushed it
+#[tauri::command]
+pub async fn stop_live_transcription(app: AppHandle) -> CommandResponse<String> {
+    let Some(session) = app.state::<LiveTranscription>().0.lock().unwrap().take() else {
+        return CommandResponse::err("Live transcription is not running");
+    };
+    let _ = session.stop.send(());
+    match session.task.await {
+        Ok(Ok(transcript)) => CommandResponse::ok(transcript),
+        Ok(Err(e)) => CommandResponse::from_error(e),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Feed a recording to the agent until stopped, emitting transcripts as they arrive
+async fn run_live_transcription(
+    app: AppHandle,
+    session: u64,
+    sample_rate: u32,
+    channels: u16,
+    mut stopped: oneshot::Receiver<()>,
+) -> Result<String, SlovoError> {
+    let (frames_tx, frames_rx) = tokio::sync::mpsc::channel(64);
+
+    let pump = async {
+        let mut offset = 0;
+        loop {
+            let stop = tokio::select! {
+                _ = &mut stopped => true,
+                _ = tokio::time::sleep(VAD_POLL_INTERVAL) => false,
+            };
+            // Samples captured since the last poll still go out when stopping
+            let Some(samples) = app.state::<RecordingState>().samples_since(session, offset) else {
+                break;
+            };
+            offset += samples.len();
+            if !samples.is_empty() && frames_tx.send(samples).await.is_err() {
+                break;
+            }
+            if stop {
+                break;
+            }
+        }
+        // Closing the channel tells the agent the audio is over
+        drop(frames_tx);
+        if let Err(e) = finish_recording(&app).await {
+            warn!("Failed to stop live transcription recording: {}", e);
+        }
+    };
+
+    let mut finals: Vec<String> = Vec::new();
+    let mut partial = String::new();
+    let client = &AppState::of(&app).client;
+    let stream = client.transcribe_stream(sample_rate, channels, frames_rx, |update| match update {
+        TranscriptUpdate::Partial(text) => {
+            let _ = app.emit("partial-transcript", &text);
+            partial = text;
+        }
+        TranscriptUpdate::Final(text) => {
+            let _ = app.emit("final-transcript", &text);
+            finals.push(text);
+            partial.clear();
+        }
+    });
+    let ((), result) = tokio::join!(pump, stream);
+
+    if let Err(e) = &result {
+        error!("Live transcription failed: {}", e);
+        finals.push(partial);
+        let payload = TranscriptStreamError {
+            message: e.to_string(),
+            partial: finals.join(" ").trim().to_string(),
+        };
+        let _ = app.emit("transcript-stream-error", payload);
+        // Free the slot so a new session can start without an explicit stop
+        app.state::<LiveTranscription>().0.lock().unwrap().take();
+    }
+    result
+}
+
+/// Stop recording and return the captured audio as WAV bytes
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> CommandResponse<Vec<u8>> {
+    match finish_recording(&app).await {
+        Ok(audio) => CommandResponse::ok(audio),
+        Err(e) => {
+            error!("Failed to stop recording: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Stop recording and transcribe the captured audio, as `process_voice_input` would
+#[tauri::command]
+pub async fn stop_recording_and_transcribe(app: AppHandle) -> CommandResponse<String> {
+    let audio = match finish_recording(&app).await {
+        Ok(audio) => audio,
+        Err(e) => {
+            error!("Failed to stop recording: {}", e);
+            return CommandResponse::err(e);
+        }
+    };
+
+    let client = &AppState::of(&app).client;
+    let config = &AppState::of(&app).config;
+    match transcribe_audio(client, config, audio).await {
+        Ok(transcription) => CommandResponse::ok(transcription.text),
+        Err(e) => {
+            error!("Failed to transcribe recording: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// End-of-speech detector for a new recording, if auto-stop is enabled
+fn endpoint_detector(app: &AppHandle, session: u64) -> Option<EndpointDetector> {
+    let config = &AppState::of(app).config;
+    let config = config.read().unwrap();
+    if !config.vad_auto_stop || config.vad_threshold <= 0 {
+        return None;
+    }
+
+    let (sample_rate, channels) = app.state::<RecordingState>().format(session)?;
+    Some(EndpointDetector::new(
+        config.vad_threshold,
+        config.vad_min_speech_ms,
+        config.vad_silence_timeout_ms,
+        sample_rate,
+        channels,
+    ))
+}
+
+/// Watch a recording and, once speech is followed by silence, stop and transcribe it
+///
+/// Emits `recording-speech-started` / `recording-speech-ended`, then the
+/// transcript as `voice-input-transcribed` or the failure as `voice-input-failed`.
+/// A transcript below the confidence threshold is sent as `voice-input-low-confidence`.
+/// Returns quietly if the recording is stopped manually first.
+async fn watch_for_end_of_speech(app: AppHandle, session: u64, mut detector: EndpointDetector) {
+    let mut offset = 0;
+    loop {
+        tokio::time::sleep(VAD_POLL_INTERVAL).await;
+        let Some(samples) = app.state::<RecordingState>().samples_since(session, offset) else {
+            return;
+        };
+        offset += samples.len();
+
+        match detector.push(&samples) {
+            Some(VadEvent::SpeechStarted) => {
+                let _ = app.emit("recording-speech-started", ());
+            }
+            Some(VadEvent::SpeechEnded) => break,
+            None => {}
+        }
+    }
+
+    info!("End of speech detected, stopping recording");
+    let _ = app.emit("recording-speech-ended", ());
+    let audio = match finish_recording(&app).await {
+        Ok(audio) => audio,
+        Err(e) => {
+            error!("Failed to stop recording: {}", e);
+            let _ = app.emit("voice-input-failed", e.to_string());
+            return;
+        }
+    };
+
+    let client = &AppState::of(&app).client;
+    let config = &AppState::of(&app).config;
+    match transcribe_audio(client, config, audio).await {
+        Ok(transcription) => {
+            let checked = checked_transcription(config, transcription);
+            match checked.status {
+                TranscriptionStatus::Ok => {
+                    let _ = app.emit("voice-input-transcribed", checked.transcription.text);
+                }
+                TranscriptionStatus::LowConfidence => {
+                    let _ = app.emit("voice-input-low-confidence", checked);
+                }
+            }
+        }
+        Err(e) => {
+            error!("Failed to transcribe recording: {}", e);
+            let _ = app.emit("voice-input-failed", e.to_string());
+        }
+    }
+}
+
+async fn finish_recording(app: &AppHandle) -> Result<Vec<u8>, SlovoError> {
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || handle.state::<RecordingState>().stop()).await;
+    app.state::<TrayManager>().listening_finished(app);
+    result?
 }
 
 /// Check the agent runtime status
+///
+/// Answers from a health check up to `HEALTH_CACHE_TTL` old; the health
+/// monitor refreshes it on every tick.
 #[tauri::command]
-pub async fn check_agent_status() -> CommandResponse<AgentStatusResponse> {
-    let client = AgentClient::new();
-    
-    match client.health_check().await {
+pub async fn check_agent_status(app: AppHandle) -> CommandResponse<AgentStatusResponse> {
+    let client = &AppState::of(&app).client;
+
+    match client.recent_health(HEALTH_CACHE_TTL).await {
         Ok(health) => CommandResponse::ok(AgentStatusResponse {
             status: health.status,
             version: Some(health.version),
@@ -83,54 +826,1876 @@ pub async fn check_agent_status() -> CommandResponse<AgentStatusResponse> {
     }
 }
 
-/// Send a message to the agent and get a response
+/// Test the agent connection once and report the round-trip latency
+///
+/// Uses a throwaway client so the shared client's health snapshot is untouched.
 #[tauri::command]
-pub async fn send_message_to_agent(
-    message: String,
-    conversation_id: Option<String>,
-) -> CommandResponse<ChatMessageResponse> {
-    info!("Sending message to agent: {}", message);
-    
-    let client = AgentClient::new();
-    
-    match client.send_message(&message, conversation_id.as_deref()).await {
-        Ok(response) => {
-            info!("Received response from agent");
-            CommandResponse::ok(ChatMessageResponse {
-                id: response.id,
-                response: response.response,
-                conversation_id: response.conversation_id,
-                reasoning: response.reasoning,
-            })
+pub async fn ping_agent(app: AppHandle) -> CommandResponse<PingResponse> {
+    let config = AppState::of(&app).config.read().unwrap().agent.clone();
+    let client = match AgentClientBuilder::from_config(&config).build() {
+        Ok(client) => client,
+        Err(e) => return CommandResponse::err(e),
+    };
+
+    let started = Instant::now();
+    let result = client.health_check().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    CommandResponse::ok(match result {
+        Ok(health) => PingResponse {
+            reachable: true,
+            latency_ms: Some(latency_ms),
+            version: Some(health.version),
+            error: None,
+        },
+        Err(e) => PingResponse {
+            reachable: false,
+            latency_ms: None,
+            version: None,
+            error: Some(e.to_string()),
+        },
+    })
+}
+
+/// Preload the agent's model so the first message is not slow
+///
+/// Safe to call repeatedly: resolves immediately once the agent is warm.
+#[tauri::command]
+pub async fn warm_up_agent(app: AppHandle) -> CommandResponse<bool> {
+    match app.state::<AgentWarmup>().run(&app).await {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Agent warm-up failed: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+/// Get the features the connected agent supports so the UI can hide the rest
+///
+/// Uses the set fetched on connect, fetching it now if there is none yet.
+#[tauri::command]
+pub async fn get_capabilities(app: AppHandle) -> CommandResponse<Capabilities> {
+    let client = &AppState::of(&app).client;
+    if let Some(capabilities) = client.cached_capabilities().await {
+        return CommandResponse::ok(capabilities);
+    }
+
+    match client.capabilities().await {
+        Ok(capabilities) => CommandResponse::ok(capabilities),
+        Err(e) => {
+            error!("Failed to get agent capabilities: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// How long fetched model info is reused before asking the agent again
+const MODEL_INFO_TTL: Duration = Duration::from_secs(60);
+
+/// Model info with whether it may be out of date
+#[derive(Debug, Serialize)]
+pub struct ModelInfoResponse {
+    #[serde(flatten)]
+    pub info: ModelInfo,
+    /// The agent could not be reached, so this is the last known model
+    pub stale: bool,
+}
+
+/// Get which model the agent answers with
+///
+/// Reuses recent results; while the agent is unreachable, the last known model
+/// is returned marked stale instead of an error.
+#[tauri::command]
+pub async fn get_model_info(app: AppHandle) -> CommandResponse<ModelInfoResponse> {
+    let client = &AppState::of(&app).client;
+    let cached = client.cached_model_info().await;
+    if let Some((info, age)) = &cached {
+        if *age < MODEL_INFO_TTL {
+            return CommandResponse::ok(ModelInfoResponse {
+                info: info.clone(),
+                stale: false,
+            });
+        }
+    }
+
+    match (client.model_info().await, cached) {
+        (Ok(info), _) => CommandResponse::ok(ModelInfoResponse { info, stale: false }),
+        (Err(e @ (SlovoError::AgentConnection(_) | SlovoError::Timeout(_))), Some((info, _))) => {
+            warn!("Agent unreachable, returning cached model info: {}", e);
+            CommandResponse::ok(ModelInfoResponse { info, stale: true })
+        }
+        (Err(e), _) => {
+            error!("Failed to get model info: {}", e);
+            CommandResponse::from_error(e)
         }
+    }
+}
+
+/// Get the agent's version, uptime, and capabilities
+#[tauri::command]
+pub async fn get_agent_info(app: AppHandle) -> CommandResponse<AgentInfoResponse> {
+    let client = &AppState::of(&app).client;
+
+    match client.health_check().await {
+        Ok(health) => CommandResponse::ok(AgentInfoResponse {
+            uptime_percentage: AppState::of(&app).health_history.uptime_percentage(),
+            ..health.into()
+        }),
         Err(e) => {
-            error!("Failed to send message to agent: {}", e);
+            error!("Failed to get agent info: {}", e);
             CommandResponse::err(e)
         }
     }
 }
 
+/// The most recent agent health checks, oldest first; 60 unless `count` is given
+#[tauri::command]
+pub async fn get_health_history(
+    app: AppHandle,
+    count: Option<usize>,
+) -> CommandResponse<Vec<HealthSnapshot>> {
+    let count = count.unwrap_or(health_history::DEFAULT_COUNT);
+    CommandResponse::ok(AppState::of(&app).health_history.recent(count))
+}
+
+/// Change how often the agent's health is checked, in seconds (1 to 300), and save it
+#[tauri::command]
+pub async fn set_health_check_interval(app: AppHandle, secs: u64) -> CommandResponse<bool> {
+    if let Err(e) = app.state::<HealthMonitor>().set_interval(secs) {
+        return CommandResponse::from_error(e);
+    }
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.agent.health_check_interval_secs = secs;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to save health check interval: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Change how long an agent request may take, in seconds (1 to 600), and save it
+///
+/// Takes effect for the next request without reconnecting.
+#[tauri::command]
+pub async fn set_request_timeout(app: AppHandle, seconds: u64) -> CommandResponse<bool> {
+    if let Err(e) = AppState::of(&app).client.set_request_timeout(seconds) {
+        return CommandResponse::from_error(e);
+    }
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.agent.timeout_secs = seconds;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(true),
+        Err(e) => {
+            error!("Failed to save request timeout: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Collapses identical sends within a short window
+///
+/// A double-submitted message waits for the original request instead of
+/// reaching the agent twice, and gets its response once it finishes; a
+/// repeat after that, but still within the window, gets the same response.
+/// A failed send is forgotten at once so it can be retried.
+pub struct RequestDeduplicator {
+    window: Duration,
+    entries: Mutex<HashMap<u64, DedupEntry>>,
+}
+
+struct DedupEntry {
+    started: Instant,
+    response: Arc<OnceCell<CommandResponse<SendMessageResponse>>>,
+}
+
+impl RequestDeduplicator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `send` unless an identical request started within the window
+    pub async fn run<F, Fut>(
+        &self,
+        message: &str,
+        conversation_id: Option<&str>,
+        send: F,
+    ) -> CommandResponse<SendMessageResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = CommandResponse<SendMessageResponse>>,
+    {
+        let mut hasher = DefaultHasher::new();
+        (message, conversation_id).hash(&mut hasher);
+        let key = hasher.finish();
+
+        let cell = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, entry| entry.started.elapsed() < self.window);
+            entries
+                .entry(key)
+                .or_insert_with(|| DedupEntry {
+                    started: Instant::now(),
+                    response: Arc::default(),
+                })
+                .response
+                .clone()
+        };
+
+        let response = cell.get_or_init(send).await.clone();
+        if !response.success {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.get(&key).is_some_and(|entry| Arc::ptr_eq(&entry.response, &cell)) {
+                entries.remove(&key);
+            }
+        }
+        response
+    }
+}
+
+/// Send a message to the agent and get a response
+///
+/// `include_reasoning`, when given, overrides and updates the saved preference.
+/// Identical messages submitted in quick succession are only sent once.
+/// Passing a `request_id` lets `cancel_message` abort the request.
+#[tauri::command]
+pub async fn send_message_to_agent(
+    app: AppHandle,
+    message: String,
+    conversation_id: Option<String>,
+    include_reasoning: Option<bool>,
+    request_id: Option<String>,
+) -> CommandResponse<SendMessageResponse> {
+    if let Some(include) = include_reasoning {
+        set_reasoning_preference(&app, include);
+    }
+
+    // Every log line for this send, retries included, carries the id
+    let correlation_id = Uuid::new_v4().to_string();
+    let span = info_span!("agent_request", correlation_id = %correlation_id);
+
+    let deduplicator = app.state::<RequestDeduplicator>();
+    deduplicator
+        .run(&message, conversation_id.as_deref(), || {
+            deliver_message(
+                &app,
+                message.clone(),
+                conversation_id.clone(),
+                request_id.clone(),
+                correlation_id.clone(),
+            )
+        })
+        .instrument(span)
+        .await
+}
+
+/// Abort an in-flight `send_message_to_agent` call; returns false if no request has that id
+#[tauri::command]
+pub async fn cancel_message(app: AppHandle, request_id: String) -> CommandResponse<bool> {
+    let cancelled = app.state::<CancellationRegistry>().cancel(&request_id);
+    if cancelled {
+        info!("Cancelled message request {}", request_id);
+    }
+    CommandResponse::ok(cancelled)
+}
+
+async fn deliver_message(
+    app: &AppHandle,
+    message: String,
+    conversation_id: Option<String>,
+    request_id: Option<String>,
+    correlation_id: String,
+) -> CommandResponse<SendMessageResponse> {
+    info!("Sending message to agent: {}", logged(app, &message));
+
+    let client = &AppState::of(app).client;
+    let registry = app.state::<CancellationRegistry>();
+    let mut options = chat_options(app);
+    options.cancel = request_id.as_deref().map(|id| registry.register(id));
+    options.correlation_id = Some(correlation_id);
+    let response = deliver_with_options(app, client, message, conversation_id, &options).await;
+    if let Some(id) = &request_id {
+        registry.remove(id);
+    }
+    response
+}
+
+async fn deliver_with_options(
+    app: &AppHandle,
+    client: &AgentClient,
+    message: String,
+    conversation_id: Option<String>,
+    options: &ChatOptions,
+) -> CommandResponse<SendMessageResponse> {
+    if let Err(e) = client.check_message(&message) {
+        return CommandResponse::from_error(e);
+    }
+    if let Err(e) = app.state::<RateLimiter>().acquire().await {
+        warn!("Dropping message to agent: {}", e);
+        return CommandResponse::err(e);
+    }
+    let mut request = RequestGuard::start(app);
+    // Chosen here so a queued message keeps the key of the send that failed
+    let options = ChatOptions {
+        idempotency_key: Some(Uuid::new_v4().to_string()),
+        ..options.clone()
+    };
+
+    match send_and_record(app, client, &message, conversation_id.clone(), &options, Vec::new()).await {
+        Ok(response) => {
+            request.succeed();
+            info!("Received response from agent: {}", logged(app, &response.response));
+            auto_title_if_new(app, &response.conversation_id);
+            CommandResponse::ok(SendMessageResponse::Sent(response.into()))
+        }
+        Err(SlovoError::AgentConnection(e)) => {
+            // Keep the message for when the agent comes back rather than losing it.
+            // Timeouts are not queued, since the agent may still be answering.
+            warn!("Agent unreachable, queueing message: {}", e);
+            let conversation_id = conversation_id.or_else(|| AppState::of(app).conversation.get());
+            let queue = app.state::<MessageQueue>();
+            match queue.enqueue(message, conversation_id, options.idempotency_key) {
+                Ok(queued) => CommandResponse::ok(SendMessageResponse::Queued {
+                    queue_id: queued.id,
+                }),
+                Err(e) => {
+                    error!("Failed to queue message: {}", e);
+                    CommandResponse::err(e)
+                }
+            }
+        }
+        Err(e) => {
+            error!("Failed to send message to agent: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+/// Send a message with images or files attached
+///
+/// Unlike `send_message_to_agent`, a message that cannot reach the agent is
+/// not queued, since the queue does not keep attachments.
+#[tauri::command]
+pub async fn send_message_with_attachments(
+    app: AppHandle,
+    message: String,
+    attachments: Vec<AttachmentInput>,
+    conversation_id: Option<String>,
+) -> CommandResponse<SendMessageResponse> {
+    info!("Sending message with {} attachments to agent", attachments.len());
+
+    let resolved = tauri::async_runtime::spawn_blocking(move || {
+        attachments
+            .into_iter()
+            .map(AttachmentInput::resolve)
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await;
+    let attachments = match resolved {
+        Ok(Ok(attachments)) => attachments,
+        Ok(Err(e)) => return CommandResponse::from_error(e),
+        Err(e) => return CommandResponse::err(e),
+    };
+
+    let client = &AppState::of(&app).client;
+    if let Err(e) = client.check_message(&message) {
+        return CommandResponse::from_error(e);
+    }
+    if let Err(e) = app.state::<RateLimiter>().acquire().await {
+        warn!("Dropping message to agent: {}", e);
+        return CommandResponse::err(e);
+    }
+    let mut request = RequestGuard::start(&app);
+
+    let options = chat_options(&app);
+    match send_and_record(&app, client, &message, conversation_id, &options, attachments).await {
+        Ok(response) => {
+            request.succeed();
+            CommandResponse::ok(SendMessageResponse::Sent(response.into()))
+        }
+        Err(e) => {
+            error!("Failed to send message with attachments: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+/// Ask the agent for a new answer to the last user message in a conversation
+///
+/// The new reply replaces the old one in history, which is kept among the
+/// message's `alternatives`.
+#[tauri::command]
+pub async fn regenerate_last_response(
+    app: AppHandle,
+    conversation_id: String,
+) -> CommandResponse<ChatMessageResponse> {
+    let conversations = app.state::<ConversationManager>();
+    let Some(message) = conversations.last_user_message(&conversation_id) else {
+        return CommandResponse::err("Conversation has no message to regenerate a response for");
+    };
+    info!("Regenerating last response in conversation {}", conversation_id);
+
+    if let Err(e) = app.state::<RateLimiter>().acquire().await {
+        warn!("Dropping regenerate request: {}", e);
+        return CommandResponse::err(e);
+    }
+    let mut request = RequestGuard::start(&app);
+
+    let mut options = with_system_prompt(&app, &chat_options(&app), Some(&conversation_id));
+    options.idempotency_key = Some(Uuid::new_v4().to_string());
+    options.regenerate = true;
+
+    let started = Instant::now();
+    let client = &AppState::of(&app).client;
+    let mut response = match client.send_message(&message, Some(&conversation_id), &options).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Failed to regenerate response: {}", e);
+            return CommandResponse::from_error(e);
+        }
+    };
+    request.succeed();
+    app.state::<Metrics>()
+        .record_message(&conversation_id, started.elapsed(), response.usage);
+    persona_delivered(&app, &options, &conversation_id);
+
+    if !options.include_reasoning {
+        response.reasoning = None;
+    }
+    if let Err(e) = conversations.record_regenerated(&conversation_id, &response) {
+        warn!("Failed to persist regenerated response: {}", e);
+    }
+    CommandResponse::ok(response.into())
+}
+
+/// Ask the agent for a short title for a conversation and save it
+///
+/// The request is sent within the conversation as an ephemeral request, so
+/// the agent does not add it to the history. If the agent cannot be reached
+/// the derived title is returned and nothing is saved.
+#[tauri::command]
+pub async fn auto_title_conversation(
+    app: AppHandle,
+    conversation_id: String,
+) -> CommandResponse<String> {
+    let conversations = app.state::<ConversationManager>();
+    let Some(conversation) = conversations.get(&conversation_id) else {
+        return CommandResponse::err(format!("Unknown conversation: {}", conversation_id));
+    };
+    let fallback = conversation.display_title();
+    let Some(first_message) = conversations.first_user_message(&conversation_id) else {
+        return CommandResponse::ok(fallback);
+    };
+
+    let title = match generate_title(&app, &conversation_id, &first_message).await {
+        Ok(Some(title)) => title,
+        Ok(None) => {
+            warn!("Agent returned no usable title for conversation {}", conversation_id);
+            return CommandResponse::ok(fallback);
+        }
+        Err(e) => {
+            warn!("Failed to generate conversation title: {}", e);
+            return CommandResponse::ok(fallback);
+        }
+    };
+    match conversations.set_title(&conversation_id, Some(title.clone())) {
+        Ok(true) => {}
+        Ok(false) => return CommandResponse::ok(fallback),
+        Err(e) => return CommandResponse::from_error(e),
+    }
+    info!("Titled conversation {}: {}", conversation_id, logged(&app, &title));
+    let payload = ConversationTitled {
+        conversation_id,
+        title: title.clone(),
+    };
+    let _ = app.emit(ConversationTitled::EVENT, payload);
+    CommandResponse::ok(title)
+}
+
+/// Ask the agent, within the conversation but outside its history, for a title
+async fn generate_title(
+    app: &AppHandle,
+    conversation_id: &str,
+    first_message: &str,
+) -> Result<Option<String>, SlovoError> {
+    app.state::<RateLimiter>().acquire().await?;
+    let _in_flight = app.state::<InFlightRequests>().track();
+    let options = ChatOptions {
+        idempotency_key: Some(Uuid::new_v4().to_string()),
+        ephemeral: true,
+        ..ChatOptions::default()
+    };
+    let prompt = format!("Generate a 3–6 word title for this conversation: {}", first_message);
+    let client = &AppState::of(app).client;
+    let response = client.send_message(&prompt, Some(conversation_id), &options).await?;
+    Ok(conversation::clean_title(&response.response))
+}
+
+/// Title a conversation in the background once its first exchange is recorded
+fn auto_title_if_new(app: &AppHandle, conversation_id: &str) {
+    if !AppState::of(app).config.read().unwrap().auto_title {
+        return;
+    }
+    let needs_title = app
+        .state::<ConversationManager>()
+        .get(conversation_id)
+        .is_some_and(|conversation| conversation.needs_title());
+    if needs_title {
+        let app = app.clone();
+        let conversation_id = conversation_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            auto_title_conversation(app, conversation_id).await;
+        });
+    }
+}
+
+/// Outcome of `validate_message`
+#[derive(Debug, Serialize)]
+pub struct ValidationResponse {
+    pub valid: bool,
+    pub reason: Option<String>,
+}
+
+/// Check whether `send_message_to_agent` would accept a message, without contacting the agent
+#[tauri::command]
+pub async fn validate_message(app: AppHandle, message: String) -> CommandResponse<ValidationResponse> {
+    let reason = match AppState::of(&app).client.check_message(&message) {
+        Err(e) => Some(e.to_string()),
+        Ok(()) if !app.state::<RateLimiter>().has_capacity() => Some("rate limited".to_string()),
+        Ok(()) => None,
+    };
+
+    CommandResponse::ok(ValidationResponse {
+        valid: reason.is_none(),
+        reason,
+    })
+}
+
+/// Send queued messages in order now that the agent is reachable again
+///
+/// Stops at the first connection failure so the rest stay queued; messages
+/// the agent rejects outright are dropped and reported.
+pub async fn flush_queued_messages(app: &AppHandle) {
+    let queue = app.state::<MessageQueue>();
+    if !queue.begin_flush() {
+        return;
+    }
+
+    let client = &AppState::of(app).client;
+    let options = chat_options(app);
+    while let Some(queued) = queue.front() {
+        let options = ChatOptions {
+            idempotency_key: queued.idempotency_key.clone(),
+            ..options.clone()
+        };
+        let _in_flight = app.state::<InFlightRequests>().track();
+        match send_and_record(
+            app,
+            client,
+            &queued.message,
+            queued.conversation_id.clone(),
+            &options,
+            Vec::new(),
+        )
+        .await
+        {
+            Ok(response) => {
+                info!("Sent queued message {}", queued.id);
+                let _ = app.emit(
+                    "queued-message-sent",
+                    QueuedMessageSent {
+                        queue_id: queued.id.clone(),
+                        response: response.into(),
+                    },
+                );
+            }
+            Err(SlovoError::AgentConnection(e)) => {
+                warn!("Agent unreachable while flushing queue: {}", e);
+                break;
+            }
+            // Resending after a timeout is safe: the message keeps its idempotency key
+            Err(e @ (SlovoError::Timeout(_) | SlovoError::RateLimitError { .. })) => {
+                warn!("Pausing queue flush: {}", e);
+                break;
+            }
+            Err(e) => {
+                error!("Dropping queued message {}: {}", queued.id, e);
+                let _ = app.emit("queued-message-failed", &queued.id);
+            }
+        }
+        if let Err(e) = queue.remove(&queued.id) {
+            warn!("Failed to update message queue: {}", e);
+        }
+    }
+
+    queue.end_flush();
+}
+
+/// Messages waiting for the agent to reconnect, oldest first
+#[tauri::command]
+pub async fn get_queued_messages(app: AppHandle) -> CommandResponse<Vec<QueuedMessage>> {
+    CommandResponse::ok(app.state::<MessageQueue>().messages())
+}
+
+/// Discard all queued messages; returns how many were removed
+#[tauri::command]
+pub async fn clear_queue(app: AppHandle) -> CommandResponse<usize> {
+    match app.state::<MessageQueue>().clear() {
+        Ok(count) => CommandResponse::ok(count),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Send one message on the tracked conversation and record the exchange
+async fn send_and_record(
+    app: &AppHandle,
+    client: &AgentClient,
+    message: &str,
+    conversation_id: Option<String>,
+    options: &ChatOptions,
+    attachments: Vec<Attachment>,
+) -> Result<ChatResponse, SlovoError> {
+    // Fall back to the backend-tracked conversation when the frontend omits the id
+    let current = &AppState::of(app).conversation;
+    let conversation_id = conversation_id.or_else(|| current.get());
+    let mut options = with_system_prompt(app, options, conversation_id.as_deref());
+    // Fresh per send unless the caller is resending; the client reuses it for any retries
+    if options.idempotency_key.is_none() {
+        options.idempotency_key = Some(Uuid::new_v4().to_string());
+    }
+
+    let started = Instant::now();
+    // Attachments need the HTTP client's multipart upload; anything else uses the transport
+    let result = if attachments.is_empty() {
+        let transport = &AppState::of(app).transport;
+        transport.send_message(message, conversation_id.as_deref(), &options).await
+    } else {
+        let conversation_id = conversation_id.as_deref();
+        client.send_message_with_attachments(message, attachments, conversation_id, &options).await
+    };
+    if let Err(SlovoError::RateLimitError { retry_after_ms: Some(retry_after_ms) }) = &result {
+        let payload = AgentBusy {
+            retry_after_ms: *retry_after_ms,
+        };
+        let _ = app.emit(AgentBusy::EVENT, payload);
+    }
+    let mut response = result?;
+    app.state::<Metrics>()
+        .record_message(&response.conversation_id, started.elapsed(), response.usage);
+
+    // Reasoning goes to its own event so the UI can render it apart from the answer
+    if !options.include_reasoning {
+        response.reasoning = None;
+    }
+    if let Some(reasoning) = response.reasoning_chunk() {
+        let _ = app.emit("agent-reasoning-chunk", reasoning);
+    }
+
+    current.set(response.conversation_id.clone());
+    if let Err(e) = app.state::<ConversationManager>().record_exchange(message, &response) {
+        warn!("Failed to persist conversation: {}", e);
+    }
+    persona_delivered(app, &options, &response.conversation_id);
+    Ok(response)
+}
+
+/// Chat options from the saved preferences
+fn chat_options(app: &AppHandle) -> ChatOptions {
+    ChatOptions::from_config(&AppState::of(app).config.read().unwrap())
+}
+
+/// Add the conversation's persona to the chat options when the agent has yet to see it
+///
+/// Messages starting a new conversation carry the default persona.
+fn with_system_prompt(app: &AppHandle, options: &ChatOptions, conversation_id: Option<&str>) -> ChatOptions {
+    let conversations = app.state::<ConversationManager>();
+    let system_prompt = match conversation_id {
+        Some(id) if conversations.contains(id) => conversations.pending_system_prompt(id),
+        _ => AppState::of(app).config.read().unwrap().default_persona.clone(),
+    };
+    ChatOptions {
+        system_prompt,
+        ..options.clone()
+    }
+}
+
+/// Record that a persona sent with a message reached the agent
+fn persona_delivered(app: &AppHandle, options: &ChatOptions, conversation_id: &str) {
+    let Some(prompt) = &options.system_prompt else {
+        return;
+    };
+    if let Err(e) = app
+        .state::<ConversationManager>()
+        .mark_persona_sent(conversation_id, prompt)
+    {
+        warn!("Failed to persist persona state: {}", e);
+    }
+}
+
+/// Remember whether the user wants agent reasoning shown
+fn set_reasoning_preference(app: &AppHandle, include: bool) {
+    let shared = &AppState::of(app).config;
+    let mut config = shared.write().unwrap();
+    if config.include_reasoning == include {
+        return;
+    }
+
+    config.include_reasoning = include;
+    if let Err(e) = config.save(app) {
+        warn!("Failed to save reasoning preference: {}", e);
+    }
+}
+
+/// Send several messages in order on one conversation
+///
+/// Stops at the first failure, returning the responses gathered so far along
+/// with the index of the failed message. Passing a `batch_id` lets
+/// `cancel_batch` stop the remaining messages.
+#[tauri::command]
+pub async fn send_messages(
+    app: AppHandle,
+    messages: Vec<String>,
+    conversation_id: Option<String>,
+    batch_id: Option<String>,
+) -> CommandResponse<SendMessagesResponse> {
+    info!("Sending batch of {} messages to agent", messages.len());
+
+    let client = &AppState::of(&app).client;
+    let mut request = RequestGuard::start(&app);
+
+    let registry = app.state::<CancellationRegistry>();
+    let token = batch_id.as_deref().map(|id| registry.register(id));
+    // Cancelling the batch also aborts the message in flight
+    let mut options = chat_options(&app);
+    options.cancel = token.as_ref().map(|t| t.child_token());
+
+    let mut batch = SendMessagesResponse {
+        responses: Vec::new(),
+        conversation_id,
+        failed_index: None,
+        cancelled: false,
+    };
+    let mut failure = None;
+
+    for (index, message) in messages.iter().enumerate() {
+        if token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            info!("Message batch cancelled after {} of {} messages", index, messages.len());
+            batch.cancelled = true;
+            break;
+        }
+
+        match send_and_record(&app, client, message, batch.conversation_id.clone(), &options, Vec::new()).await {
+            Ok(response) => {
+                batch.conversation_id = Some(response.conversation_id.clone());
+                batch.responses.push(response.into());
+            }
+            Err(_) if token.as_ref().is_some_and(|t| t.is_cancelled()) => {
+                info!("Message batch cancelled during message {} of {}", index, messages.len());
+                batch.cancelled = true;
+                break;
+            }
+            Err(e) => {
+                error!("Batch message {} failed: {}", index, e);
+                batch.failed_index = Some(index);
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+
+    if let Some(id) = &batch_id {
+        registry.remove(id);
+    }
+
+    if failure.is_none() && !batch.cancelled {
+        request.succeed();
+    }
+    match failure {
+        Some(e) => CommandResponse {
+            data: Some(batch),
+            ..CommandResponse::from_error(e)
+        },
+        None => CommandResponse {
+            success: !batch.cancelled,
+            data: Some(batch),
+            error: None,
+            error_code: None,
+        },
+    }
+}
+
+/// Send unrelated messages to the agent concurrently, with a result per message
+///
+/// Unlike `send_messages`, one failure does not stop the rest of the batch.
+#[tauri::command]
+pub async fn send_batch_to_agent(
+    app: AppHandle,
+    messages: Vec<BatchMessage>,
+) -> CommandResponse<Vec<BatchResult>> {
+    info!("Sending {} messages to agent concurrently", messages.len());
+
+    let options = chat_options(&app);
+    let batch_options: Vec<ChatOptions> = messages
+        .iter()
+        .map(|batch| {
+            let mut options = with_system_prompt(&app, &options, batch.conversation_id.as_deref());
+            options.idempotency_key = Some(Uuid::new_v4().to_string());
+            options
+        })
+        .collect();
+
+    // Each message spends a token as a single send would; a rejected one fails alone
+    let limiter = app.state::<RateLimiter>();
+    let mut rate_limited = Vec::with_capacity(messages.len());
+    for _ in &messages {
+        rate_limited.push(limiter.acquire().await.err());
+    }
+    let requests = messages
+        .iter()
+        .zip(&batch_options)
+        .zip(&rate_limited)
+        .filter(|(_, limited)| limited.is_none())
+        .map(|((batch, options), _)| {
+            ChatRequest::new(batch.message.clone(), batch.conversation_id.clone(), options)
+        })
+        .collect();
+
+    let mut request = RequestGuard::start(&app);
+    let started = Instant::now();
+    let mut sent = AppState::of(&app).client.send_batch(requests).await.into_iter();
+    let latency = started.elapsed();
+    let conversations = app.state::<ConversationManager>();
+    let metrics = app.state::<Metrics>();
+    let results: Vec<BatchResult> = messages
+        .iter()
+        .zip(&batch_options)
+        .zip(rate_limited)
+        .enumerate()
+        .map(|(index, ((batch, sent_options), limited))| {
+            let result = match limited {
+                Some(e) => Err(e),
+                None => sent
+                    .next()
+                    .unwrap_or_else(|| Err(SlovoError::agent("missing batch result"))),
+            };
+            match result {
+                Ok(mut response) => {
+                    metrics.record_message(&response.conversation_id, latency, response.usage);
+                    if !options.include_reasoning {
+                        response.reasoning = None;
+                    }
+                    if let Err(e) = conversations.record_exchange(&batch.message, &response) {
+                        warn!("Failed to persist conversation: {}", e);
+                    }
+                    persona_delivered(&app, sent_options, &response.conversation_id);
+                    BatchResult { index, response: Some(response.into()), error: None }
+                }
+                Err(e) => {
+                    error!("Batch message {} failed: {}", index, e);
+                    BatchResult { index, response: None, error: Some(e.to_string()) }
+                }
+            }
+        })
+        .collect();
+
+    if results.iter().all(|result| result.error.is_none()) {
+        request.succeed();
+    }
+    CommandResponse::ok(results)
+}
+
+/// Cancel the remaining messages of a running `send_messages` batch
+#[tauri::command]
+pub async fn cancel_batch(app: AppHandle, batch_id: String) -> CommandResponse<bool> {
+    CommandResponse::ok(app.state::<CancellationRegistry>().cancel(&batch_id))
+}
+
+/// Create an empty conversation, make it current, and return its id
+///
+/// Without a title, one is derived from the first message.
+#[tauri::command]
+pub async fn new_conversation(app: AppHandle, title: Option<String>) -> CommandResponse<String> {
+    let title = title.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let persona = AppState::of(&app).config.read().unwrap().default_persona.clone();
+
+    match app.state::<ConversationManager>().new_conversation(title, persona) {
+        Ok(id) => {
+            let previous = AppState::of(&app).conversation.get();
+            AppState::of(&app).conversation.set(id.clone());
+            info!("Started new conversation {} (previous: {:?})", id, previous);
+            CommandResponse::ok(id)
+        }
+        Err(e) => {
+            error!("Failed to create conversation: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Branch a conversation at a message, returning the new conversation's id
+#[tauri::command]
+pub async fn fork_conversation(
+    app: AppHandle,
+    source_id: String,
+    from_message_index: usize,
+) -> CommandResponse<String> {
+    match app
+        .state::<ConversationManager>()
+        .fork_conversation(&source_id, from_message_index)
+    {
+        Ok(id) => {
+            info!("Forked conversation {} at message {} into {}", source_id, from_message_index, id);
+            CommandResponse::ok(id)
+        }
+        Err(e) => {
+            error!("Failed to fork conversation {}: {}", source_id, e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Get the id of the conversation the backend is currently tracking
+#[tauri::command]
+pub async fn current_conversation_id(app: AppHandle) -> CommandResponse<Option<String>> {
+    CommandResponse::ok(AppState::of(&app).conversation.get())
+}
+
+/// List saved conversations, newest first, one zero-based page at a time
+#[tauri::command]
+pub async fn list_conversations(
+    app: AppHandle,
+    page: u32,
+    page_size: u32,
+    search: Option<String>,
+) -> CommandResponse<ConversationListResponse> {
+    let manager = app.state::<ConversationManager>();
+    CommandResponse::ok(manager.list(page, page_size, search.as_deref()))
+}
+
+/// Find messages containing `query` across all saved conversations, ignoring case
+#[tauri::command]
+pub async fn search_conversations(
+    app: AppHandle,
+    query: String,
+    max_results: u32,
+) -> CommandResponse<Vec<SearchResult>> {
+    let max_results = max_results.min(MAX_SEARCH_RESULTS) as usize;
+    CommandResponse::ok(app.state::<ConversationManager>().search(&query, max_results))
+}
+
+/// Outcome of `delete_conversation`
+#[derive(Debug, Serialize)]
+pub struct DeleteConversationResponse {
+    /// Whether a local copy existed and was removed
+    pub deleted: bool,
+    /// Whether the agent confirmed its copy is gone
+    pub remote_deleted: bool,
+    /// Why the agent's copy may still exist, when `remote_deleted` is false
+    pub remote_error: Option<String>,
+}
+
+/// Delete a conversation locally and on the agent
+///
+/// A failure to reach the agent after the local copy is gone is reported in
+/// `remote_error` rather than failing the command.
+#[tauri::command]
+pub async fn delete_conversation(
+    app: AppHandle,
+    id: String,
+) -> CommandResponse<DeleteConversationResponse> {
+    let deleted = match app.state::<ConversationManager>().delete_conversation(&id) {
+        Ok(deleted) => deleted,
+        Err(e) => {
+            error!("Failed to delete conversation {}: {}", id, e);
+            return CommandResponse::err(e);
+        }
+    };
+    if deleted {
+        info!("Deleted conversation {}", id);
+        let current = &AppState::of(&app).conversation;
+        if current.get().as_deref() == Some(id.as_str()) {
+            current.clear();
+        }
+        let _ = app.emit("conversation-deleted", &id);
+    }
+
+    let remote_error = match AppState::of(&app).client.delete_conversation(&id).await {
+        Ok(()) => None,
+        Err(e) => {
+            warn!("Agent may still hold conversation {}: {}", id, e);
+            Some(e.to_string())
+        }
+    };
+    CommandResponse::ok(DeleteConversationResponse {
+        deleted,
+        remote_deleted: remote_error.is_none(),
+        remote_error,
+    })
+}
+
+/// Set or clear (with `None`) the system prompt for a conversation
+///
+/// Same as `set_conversation_persona`, which newer frontends use.
+#[tauri::command]
+pub async fn set_system_prompt(
+    app: AppHandle,
+    conversation_id: String,
+    prompt: Option<String>,
+) -> CommandResponse<bool> {
+    set_conversation_persona(app, conversation_id, prompt).await
+}
+
+/// Set or clear (with `None`) a conversation's persona
+///
+/// The persona is persisted with the history and sent with the next message only.
+#[tauri::command]
+pub async fn set_conversation_persona(
+    app: AppHandle,
+    conversation_id: String,
+    prompt: Option<String>,
+) -> CommandResponse<bool> {
+    let prompt = prompt.filter(|p| !p.trim().is_empty());
+
+    match app
+        .state::<ConversationManager>()
+        .set_system_prompt(&conversation_id, prompt)
+    {
+        Ok(updated) => CommandResponse::ok(updated),
+        Err(e) => {
+            error!("Failed to set system prompt for {}: {}", conversation_id, e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Get token usage, message count, and estimated cost for a conversation
+#[tauri::command]
+pub async fn get_conversation_stats(app: AppHandle, id: String) -> CommandResponse<ConversationStats> {
+    let Some(conversation) = app.state::<ConversationManager>().get(&id) else {
+        return CommandResponse::err(format!("Unknown conversation: {}", id));
+    };
+    let cost_per_1k = AppState::of(&app).config.read().unwrap().cost_per_1k_tokens;
+    let usage = conversation.usage;
+
+    CommandResponse::ok(ConversationStats {
+        message_count: conversation.messages.len(),
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        total_tokens: usage.total_tokens,
+        estimated_cost: usage.total_tokens as f64 / 1000.0 * cost_per_1k,
+    })
+}
+
+/// Render a conversation as Markdown or JSON without writing it anywhere
+#[tauri::command]
+pub async fn render_conversation(
+    app: AppHandle,
+    id: String,
+    format: ExportFormat,
+    include_reasoning: Option<bool>,
+) -> CommandResponse<String> {
+    match render_export(&app, &id, format, include_reasoning.unwrap_or(true)) {
+        Ok(contents) => CommandResponse::ok(contents),
+        Err(e) => {
+            error!("Failed to render conversation {}: {}", id, e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Export a conversation to a file and return the path written
+///
+/// Without an explicit `path` the user picks one in a save dialog that starts
+/// in their Documents folder; cancelling the dialog returns `None`.
+#[tauri::command]
+pub async fn export_conversation(
+    app: AppHandle,
+    id: String,
+    format: ExportFormat,
+    include_reasoning: Option<bool>,
+    path: Option<String>,
+) -> CommandResponse<Option<String>> {
+    match write_export(&app, &id, format, include_reasoning.unwrap_or(true), path).await {
+        Ok(Some(path)) => {
+            info!("Exported conversation {} to {}", id, path);
+            CommandResponse::ok(Some(path))
+        }
+        Ok(None) => CommandResponse::ok(None),
+        Err(e) => {
+            error!("Failed to export conversation {}: {}", id, e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+fn render_export(
+    app: &AppHandle,
+    id: &str,
+    format: ExportFormat,
+    include_reasoning: bool,
+) -> Result<String, SlovoError> {
+    let conversation = app
+        .state::<ConversationManager>()
+        .get(id)
+        .ok_or_else(|| SlovoError::ConfigError(format!("Unknown conversation: {}", id)))?;
+    export::render(&conversation, format, include_reasoning)
+}
+
+async fn write_export(
+    app: &AppHandle,
+    id: &str,
+    format: ExportFormat,
+    include_reasoning: bool,
+    path: Option<String>,
+) -> Result<Option<String>, SlovoError> {
+    // Render first so an unknown id fails before any dialog is shown
+    let contents = render_export(app, id, format, include_reasoning)?;
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match choose_export_path(app, id, format).await? {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    std::fs::write(&path, contents)?;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Ask the user where to save an export; `None` if they cancel
+async fn choose_export_path(
+    app: &AppHandle,
+    id: &str,
+    format: ExportFormat,
+) -> Result<Option<PathBuf>, SlovoError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_directory(app.path().document_dir()?)
+        .set_file_name(export::default_file_name(id, format))
+        .add_filter(format.label(), &[format.extension()])
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+
+    match rx.await.ok().flatten() {
+        Some(path) => path
+            .into_path()
+            .map(Some)
+            .map_err(|e| SlovoError::ConfigError(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Send a message over the agent stream
+///
+/// The reply arrives as `reasoning-chunk` / `answer-chunk` events followed by
+/// `agent-stream-response` and `chat-complete`.
+#[tauri::command]
+pub async fn stream_message_to_agent(
+    app: AppHandle,
+    message: String,
+    conversation_id: Option<String>,
+) -> CommandResponse<bool> {
+    info!("Streaming message to agent: {}", logged(&app, &message));
+
+    let client = &AppState::of(&app).client;
+    let stream = app.state::<AgentStreamState>().get_or_connect(&app, client);
+    let options = with_system_prompt(&app, &chat_options(&app), conversation_id.as_deref());
+    let request = ChatRequest::new(message, conversation_id.clone(), &options);
+
+    match stream.send(request) {
+        Ok(()) => {
+            if let Some(id) = &conversation_id {
+                persona_delivered(&app, &options, id);
+            }
+            CommandResponse::ok(true)
+        }
+        Err(e) => {
+            error!("Failed to stream message to agent: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Cancel the streamed response for a conversation
+#[tauri::command]
+pub async fn cancel_stream(app: AppHandle, conversation_id: String) -> CommandResponse<bool> {
+    let client = &AppState::of(&app).client;
+    let stream = app.state::<AgentStreamState>().get_or_connect(&app, client);
+
+    match stream.cancel(&conversation_id) {
+        Ok(()) => {
+            info!("Cancelled stream for conversation {}", conversation_id);
+            CommandResponse::ok(true)
+        }
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Continue a streamed response after `chat-interrupted`
+///
+/// If the agent cannot resume it, an `agent-stream-error` follows and the
+/// partial reply is kept in the conversation's history.
+#[tauri::command]
+pub async fn resume_stream(
+    app: AppHandle,
+    conversation_id: String,
+    resume_token: Option<String>,
+) -> CommandResponse<bool> {
+    let client = &AppState::of(&app).client;
+    let stream = app.state::<AgentStreamState>().get_or_connect(&app, client);
+
+    match stream.resume(&conversation_id, resume_token) {
+        Ok(()) => {
+            info!("Resuming stream for conversation {}", conversation_id);
+            CommandResponse::ok(true)
+        }
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Close the agent stream connection
+#[tauri::command]
+pub async fn close_agent_stream(app: AppHandle) -> CommandResponse<bool> {
+    CommandResponse::ok(app.state::<AgentStreamState>().close())
+}
+
+/// Store (or clear, with `None`) the agent auth token in the OS keychain
+#[tauri::command]
+pub async fn set_auth_token(app: AppHandle, token: Option<String>) -> Result<(), SlovoError> {
+    let token = token.filter(|t| !t.trim().is_empty());
+    let profile = ProfiledConfig::load(&app)?.active_profile;
+    credentials::store_auth_token(&profile, token.as_deref())?;
+
+    AppState::of(&app).client.set_auth_token(token.clone());
+    AppState::of(&app).config.write().unwrap().agent.auth_token = token;
+    // Reconnect the stream so the new token is used for the handshake
+    app.state::<AgentStreamState>().close();
+    info!("Agent auth token updated");
+    Ok(())
+}
+
+/// List the saved config profiles by name
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> CommandResponse<Vec<String>> {
+    match ProfiledConfig::load(&app) {
+        Ok(profiles) => CommandResponse::ok(profiles.names()),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Save a new config profile; returns false if the name is already taken
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, name: String, config: SlovoConfig) -> CommandResponse<bool> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return CommandResponse::err("Profile name must not be empty");
+    }
+
+    let mut profiles = match ProfiledConfig::load(&app) {
+        Ok(profiles) => profiles,
+        Err(e) => return CommandResponse::err(e),
+    };
+    if profiles.profiles.contains_key(&name) {
+        return CommandResponse::ok(false);
+    }
+
+    profiles.profiles.insert(name.clone(), config);
+    match profiles.save(&app) {
+        Ok(()) => {
+            info!("Created config profile {}", name);
+            CommandResponse::ok(true)
+        }
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Make another profile active, pointing the agent client at its agent
+///
+/// Returns false if no profile has that name. The health monitor restarts
+/// against the new agent and the stream reconnects; no app restart is needed.
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> CommandResponse<bool> {
+    let mut profiles = match ProfiledConfig::load(&app) {
+        Ok(profiles) => profiles,
+        Err(e) => return CommandResponse::err(e),
+    };
+    if !profiles.profiles.contains_key(&name) {
+        return CommandResponse::ok(false);
+    }
+    if profiles.active_profile == name {
+        return CommandResponse::ok(true);
+    }
+
+    profiles.active_profile = name.clone();
+    let mut config = profiles.active();
+    config.agent.auth_token = credentials::load_auth_token(&name).unwrap_or_else(|e| {
+        warn!("Failed to load auth token for profile {}: {}", name, e);
+        None
+    });
+    if let Err(e) = AppState::of(&app).client.reconfigure(&config.agent).await {
+        error!("Failed to switch to profile {}: {}", name, e);
+        return CommandResponse::err(e);
+    }
+
+    // Update the live config before saving so the file watcher sees no change
+    *AppState::of(&app).config.write().unwrap() = config;
+    if let Err(e) = profiles.save(&app) {
+        warn!("Failed to save active profile: {}", e);
+    }
+
+    app.state::<AgentStreamState>().close();
+    app.state::<AgentWarmup>().reset();
+    emit_agent_status(&app, AgentStatus::Disconnected);
+    app.state::<HealthMonitor>().start(&app);
+    info!("Switched to config profile {}", name);
+    CommandResponse::ok(true)
+}
+
+/// Start the Python agent runtime and wait for it to become healthy
+#[tauri::command]
+pub async fn start_agent(app: AppHandle) -> Result<(), SlovoError> {
+    let config = AppState::of(&app).config.read().unwrap().agent.clone();
+
+    let health = app.state::<AgentProcessState>().start(&config).await?;
+    info!("Agent runtime started (version {})", health.version);
+    Ok(())
+}
+
+/// Stop the Python agent runtime started by this app
+#[tauri::command]
+pub async fn stop_agent(app: AppHandle) -> Result<bool, SlovoError> {
+    let stopped = app.state::<AgentProcessState>().stop().await?;
+    if stopped {
+        info!("Agent runtime stopped");
+    }
+    Ok(stopped)
+}
+
+/// Restart the Python agent runtime and wait for it to become healthy
+#[tauri::command]
+pub async fn restart_agent(app: AppHandle) -> Result<(), SlovoError> {
+    info!("Restarting agent runtime");
+    let _ = app.emit("agent-restarting", ());
+
+    let config = AppState::of(&app).config.read().unwrap().agent.clone();
+    // Health from the old process must not be reported for the new one
+    AppState::of(&app).client.invalidate_health_cache().await;
+
+    match app.state::<AgentProcessState>().restart(&config).await {
+        Ok(health) => {
+            info!("Agent runtime restarted (version {})", health.version);
+            let _ = app.emit("agent-restarted", health);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Agent restart failed: {}", e);
+            let _ = app.emit("agent-restart-failed", e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Environment snapshot users can paste into bug reports
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsSnapshot {
+    pub app_version: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub arch: String,
+    pub agent_status: String,
+    pub agent_version: Option<String>,
+    pub config_summary: ConfigSummary,
+    pub last_error: Option<String>,
+    pub active_conversations: usize,
+    pub log_file_path: String,
+}
+
+/// Collect a diagnostics snapshot from local state only; never contacts the agent
+#[tauri::command]
+pub async fn get_diagnostics(app: AppHandle) -> CommandResponse<DiagnosticsSnapshot> {
+    let log_file_path = match app.path().app_log_dir() {
+        Ok(dir) => logging::current_log_file(&dir).to_string_lossy().into_owned(),
+        Err(e) => return CommandResponse::err(e),
+    };
+    let config_summary = ConfigSummary::from(&*AppState::of(&app).config.read().unwrap());
+    let agent_version = AppState::of(&app)
+        .client
+        .cached_health()
+        .await
+        .map(|health| health.version);
+
+    CommandResponse::ok(DiagnosticsSnapshot {
+        app_version: app.package_info().version.to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        agent_status: app.state::<LatestAgentStatus>().get().to_string(),
+        agent_version,
+        config_summary,
+        last_error: logging::last_error(),
+        active_conversations: app.state::<ConversationManager>().count(),
+        log_file_path,
+    })
+}
+
+/// Most lines `recent_logs` returns, whatever the caller asks for
+const MAX_RECENT_LOG_LINES: usize = 1000;
+
+/// Get the last `lines` lines of today's log file for in-app troubleshooting
+#[tauri::command]
+pub async fn recent_logs(app: AppHandle, lines: usize) -> Result<Vec<String>, SlovoError> {
+    let path = logging::current_log_file(&app.path().app_log_dir()?);
+    let lines = lines.min(MAX_RECENT_LOG_LINES);
+    Ok(tauri::async_runtime::spawn_blocking(move || logging::tail(&path, lines)).await??)
+}
+
+/// Get the directory log files are written to
+#[tauri::command]
+pub async fn log_path(app: AppHandle) -> Result<String, SlovoError> {
+    let dir = app.path().app_log_dir()?;
+    Ok(dir.to_string_lossy().into_owned())
+}
+
+/// Choose whether closing the window hides to the tray (true) or quits (false)
+#[tauri::command]
+pub async fn set_close_to_tray(app: AppHandle, enabled: bool) -> CommandResponse<bool> {
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.close_to_tray = enabled;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(enabled),
+        Err(e) => {
+            error!("Failed to save close behavior: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Number of agent requests currently in flight, which shutdown waits for
+#[tauri::command]
+pub async fn pending_requests(app: AppHandle) -> CommandResponse<u32> {
+    let count = app.state::<InFlightRequests>().count();
+    CommandResponse::ok(u32::try_from(count).unwrap_or(u32::MAX))
+}
+
+/// Force the tray back to Idle, for when it is stuck showing Processing
+#[tauri::command]
+pub async fn reset_tray_state(app: AppHandle) -> CommandResponse<bool> {
+    let tray = app.state::<TrayManager>();
+    info!("Resetting tray from {:?}", tray.state());
+    tray.reset(&app);
+    CommandResponse::ok(true)
+}
+
+/// How long `preview_tray_state` shows a state before reverting
+const TRAY_PREVIEW_DURATION: Duration = Duration::from_secs(3);
+
+/// Briefly show a tray state without any real activity, for icon theming
+///
+/// Only available in debug builds. Fails with a `ConfigError` naming any
+/// state icons missing from `icons/`.
+#[tauri::command]
+pub async fn preview_tray_state(app: AppHandle, state: String) -> CommandResponse<bool> {
+    if !cfg!(debug_assertions) {
+        return CommandResponse::err("Tray previews are only available in debug builds");
+    }
+    let state = match state.parse::<TrayState>() {
+        Ok(state) => state,
+        Err(e) => return CommandResponse::from_error(e),
+    };
+
+    let icons = Path::new(env!("CARGO_MANIFEST_DIR")).join("icons");
+    let missing = tray::missing_icons(&icons);
+    if !missing.is_empty() {
+        return CommandResponse::from_error(SlovoError::ConfigError(format!(
+            "Missing tray icons in {}: {}",
+            icons.display(),
+            missing.join(", ")
+        )));
+    }
+
+    // Read from disk rather than the embedded copy so theme edits show without a rebuild
+    let icon = match tray::load_icon(&icons, state) {
+        Ok(icon) => icon,
+        Err(e) => return CommandResponse::from_error(e),
+    };
+    info!("Previewing tray state {:?}", state);
+    app.state::<TrayManager>().preview(&app, state, icon, TRAY_PREVIEW_DURATION);
+    CommandResponse::ok(true)
+}
+
+/// Flip whether the main window floats above other windows, returning the new state
+///
+/// The choice is saved and reapplied on the next launch.
+#[tauri::command]
+pub async fn toggle_always_on_top(app: AppHandle) -> CommandResponse<bool> {
+    let Some(window) = app.get_webview_window("main") else {
+        return CommandResponse::err("Main window not found");
+    };
+    match window.is_always_on_top() {
+        Ok(current) => set_always_on_top(app, !current).await,
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Keep the main window above other windows, or stop doing so
+#[tauri::command]
+pub async fn set_always_on_top(app: AppHandle, enabled: bool) -> CommandResponse<bool> {
+    let Some(window) = app.get_webview_window("main") else {
+        return CommandResponse::err("Main window not found");
+    };
+    if let Err(e) = window.set_always_on_top(enabled) {
+        error!("Failed to change always-on-top: {}", e);
+        return CommandResponse::err(e);
+    }
+    app.state::<TrayManager>().set_always_on_top_checked(enabled);
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.always_on_top = enabled;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(enabled),
+        Err(e) => {
+            error!("Failed to save always-on-top preference: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Set the main window's opacity, from 0.3 (mostly transparent) to 1.0 (opaque)
+///
+/// Saved even where the platform cannot show it, so it applies once supported.
+#[tauri::command]
+pub async fn set_window_opacity(app: AppHandle, opacity: f64) -> CommandResponse<f64> {
+    if !(window_state::MIN_OPACITY..=1.0).contains(&opacity) {
+        return CommandResponse::from_error(SlovoError::ConfigError(format!(
+            "Window opacity must be between {} and 1.0, got {}",
+            window_state::MIN_OPACITY,
+            opacity
+        )));
+    }
+    window_state::apply_opacity(opacity);
+
+    let shared = &AppState::of(&app).config;
+    let mut config = shared.write().unwrap();
+    config.window_opacity = opacity;
+    match config.save(&app) {
+        Ok(()) => CommandResponse::ok(opacity),
+        Err(e) => {
+            error!("Failed to save window opacity: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Shrink the main window to a compact strip showing only the input and latest reply
+///
+/// Returns false, changing nothing, if already in mini mode.
+#[tauri::command]
+pub async fn enter_mini_mode(app: AppHandle) -> CommandResponse<bool> {
+    let Some(window) = app.get_webview_window("main") else {
+        return CommandResponse::err("Main window not found");
+    };
+    match window_state::enter_mini_mode(&window) {
+        Ok(entered) => {
+            if entered {
+                info!("Entered mini mode");
+                let _ = app.emit("mini-mode-entered", ());
+            }
+            CommandResponse::ok(entered)
+        }
+        Err(e) => {
+            error!("Failed to enter mini mode: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Restore the main window to its size and position from before mini mode
+///
+/// Returns false, changing nothing, if not in mini mode.
+#[tauri::command]
+pub async fn exit_mini_mode(app: AppHandle) -> CommandResponse<bool> {
+    let Some(window) = app.get_webview_window("main") else {
+        return CommandResponse::err("Main window not found");
+    };
+    match window_state::exit_mini_mode(&window) {
+        Ok(exited) => {
+            if exited {
+                info!("Exited mini mode");
+                let _ = app.emit("mini-mode-exited", ());
+            }
+            CommandResponse::ok(exited)
+        }
+        Err(e) => {
+            error!("Failed to exit mini mode: {}", e);
+            CommandResponse::err(e)
+        }
+    }
+}
+
+/// Current OS appearance: "light", "dark", or "unknown"
+///
+/// Later switches arrive as `theme-changed` events.
+#[tauri::command]
+pub async fn get_current_theme(app: AppHandle) -> CommandResponse<String> {
+    let (theme, _) = theme::refresh(&app).await;
+    CommandResponse::ok(theme.as_str().to_string())
+}
+
+/// Capture a display and ask the agent about it, returning the reply
+///
+/// `display_index` counts from 0 and defaults to the primary display. The
+/// exchange is added to the active conversation.
+#[tauri::command]
+pub async fn capture_screenshot_for_vision(
+    app: AppHandle,
+    display_index: Option<u32>,
+    prompt: Option<String>,
+) -> CommandResponse<ChatMessageResponse> {
+    let mut request = RequestGuard::start(&app);
+    match share_screenshot(&app, display_index, prompt).await {
+        Ok(response) => {
+            request.succeed();
+            CommandResponse::ok(response.into())
+        }
+        Err(e) => {
+            error!("Failed to share screenshot: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+async fn share_screenshot(
+    app: &AppHandle,
+    display_index: Option<u32>,
+    prompt: Option<String>,
+) -> Result<ChatResponse, SlovoError> {
+    let client = &AppState::of(app).client;
+    match client.require(CAPABILITY_VISION).await {
+        Err(SlovoError::AgentError { .. }) => return Err(SlovoError::agent("vision not supported")),
+        result => result?,
+    }
+
+    let capture = move || screenshot::capture_png(display_index);
+    let image = tauri::async_runtime::spawn_blocking(capture)
+        .await
+        .map_err(|e| SlovoError::agent(e.to_string()))??;
+    info!("Captured screenshot: {} bytes", image.len());
+
+    let prompt = prompt.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let current = &AppState::of(app).conversation;
+    let request = VisionChatRequest {
+        image_base64: STANDARD.encode(image),
+        image_mime: "image/png".to_string(),
+        message: prompt.clone(),
+        conversation_id: current.get(),
+    };
+    let started = Instant::now();
+    let response = client.send_vision(&request).await?;
+
+    // The image itself is not kept in the history, only that one was shared
+    let user_message = match &prompt {
+        Some(prompt) => format!("[Screenshot] {}", prompt),
+        None => "[Screenshot]".to_string(),
+    };
+    record_reply(app, &user_message, &response, started);
+    Ok(response)
+}
+
+/// Ask the agent about a local file, returning the reply
+///
+/// The file must be outside the operating system directories and no larger
+/// than `max_attachment_bytes`. The exchange is added to the conversation.
+#[tauri::command]
+pub async fn attach_file_to_message(
+    app: AppHandle,
+    path: String,
+    message: String,
+    conversation_id: Option<String>,
+) -> CommandResponse<ChatMessageResponse> {
+    let mut request = RequestGuard::start(&app);
+    match send_file_message(&app, PathBuf::from(path), message, conversation_id).await {
+        Ok(response) => {
+            request.succeed();
+            CommandResponse::ok(response.into())
+        }
+        Err(e) => {
+            error!("Failed to send file to agent: {}", e);
+            CommandResponse::from_error(e)
+        }
+    }
+}
+
+async fn send_file_message(
+    app: &AppHandle,
+    path: PathBuf,
+    message: String,
+    conversation_id: Option<String>,
+) -> Result<ChatResponse, SlovoError> {
+    let client = &AppState::of(app).client;
+    client.check_message(&message)?;
+
+    let path = attachment::user_file(&path)?;
+    let limit = AppState::of(app).config.read().unwrap().max_attachment_bytes;
+    if std::fs::metadata(&path)?.len() > limit {
+        return Err(SlovoError::VoiceError("file too large".to_string()));
+    }
+    let contents = tokio::fs::read(&path).await?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let request = FileAttachmentRequest {
+        file_content_base64: STANDARD.encode(contents),
+        file_mime: attachment::mime_type_for(&path).to_string(),
+        file_name,
+        message,
+        conversation_id: conversation_id.or_else(|| AppState::of(app).conversation.get()),
+    };
+    info!("Sending {} ({}) to the agent", request.file_name, request.file_mime);
+    let started = Instant::now();
+    let response = client.send_file(&request).await?;
+
+    let user_message = format!("[File: {}] {}", request.file_name, request.message);
+    record_reply(app, &user_message, &response, started);
+    Ok(response)
+}
+
+/// Metrics and history for a reply that did not go through `send_and_record`
+fn record_reply(app: &AppHandle, user_message: &str, response: &ChatResponse, started: Instant) {
+    app.state::<Metrics>()
+        .record_message(&response.conversation_id, started.elapsed(), response.usage);
+    AppState::of(app).conversation.set(response.conversation_id.clone());
+    if let Err(e) = app
+        .state::<ConversationManager>()
+        .record_exchange(user_message, response)
+    {
+        warn!("Failed to persist conversation: {}", e);
+    }
+}
+
+/// Text on the system clipboard, or `None` if there is none
+#[tauri::command]
+pub async fn read_clipboard(app: AppHandle) -> CommandResponse<Option<String>> {
+    match tauri::async_runtime::spawn_blocking(move || clipboard::read_text(&app)).await {
+        Ok(text) => CommandResponse::ok(text),
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Put text on the system clipboard
+#[tauri::command]
+pub async fn write_clipboard(app: AppHandle, text: String) -> CommandResponse<bool> {
+    match tauri::async_runtime::spawn_blocking(move || clipboard::write_text(&app, &text)).await {
+        Ok(Ok(())) => CommandResponse::ok(true),
+        Ok(Err(e)) => {
+            error!("Failed to write clipboard: {}", e);
+            CommandResponse::err(e)
+        }
+        Err(e) => CommandResponse::err(e),
+    }
+}
+
+/// Send the clipboard text to the agent as a message, returning the reply
+#[tauri::command]
+pub async fn paste_clipboard_to_chat(app: AppHandle) -> CommandResponse<String> {
+    let text = match read_clipboard(app.clone()).await {
+        CommandResponse { data: Some(Some(text)), .. } if !text.trim().is_empty() => text,
+        CommandResponse { error: Some(e), .. } => return CommandResponse::err(e),
+        _ => return CommandResponse::err("Clipboard has no text"),
+    };
+
+    let conversation_id = AppState::of(&app).conversation.get();
+    let sent = send_message_to_agent(app, text, conversation_id, None, None).await;
+    match sent.data {
+        Some(SendMessageResponse::Sent(reply)) => CommandResponse::ok(reply.response),
+        Some(SendMessageResponse::Queued { queue_id }) => CommandResponse::err(format!(
+            "Agent is unreachable; the clipboard text was queued as {}",
+            queue_id
+        )),
+        None => CommandResponse {
+            success: false,
+            data: None,
+            error: sent.error,
+            error_code: sent.error_code,
+        },
+    }
+}
+
+/// With `auto_paste_on_show`, send the clipboard text if the active conversation is empty
+///
+/// Runs in the background; the reply is emitted as `clipboard-pasted`.
+pub fn auto_paste_clipboard(app: &AppHandle) {
+    if !AppState::of(app).config.read().unwrap().auto_paste_on_show {
+        return;
+    }
+    let conversation_empty = AppState::of(app)
+        .conversation
+        .get()
+        .and_then(|id| app.state::<ConversationManager>().get(&id))
+        .map_or(true, |conversation| conversation.messages.is_empty());
+    if !conversation_empty {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let reader = app.clone();
+        let has_text = matches!(
+            tauri::async_runtime::spawn_blocking(move || clipboard::read_text(&reader)).await,
+            Ok(Some(ref text)) if !text.trim().is_empty()
+        );
+        if !has_text {
+            return;
+        }
+        info!("Sending clipboard text to the agent on show");
+        match paste_clipboard_to_chat(app.clone()).await {
+            CommandResponse { data: Some(reply), .. } => {
+                let _ = app.emit("clipboard-pasted", reply);
+            }
+            CommandResponse { error, .. } => {
+                warn!("Failed to send clipboard text: {}", error.unwrap_or_default());
+            }
+        }
+    });
+}
+
 /// Show the main window
 #[tauri::command]
-pub async fn show_window(app: AppHandle) -> Result<(), String> {
+pub async fn show_window(app: AppHandle) -> Result<(), SlovoError> {
     if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        window.show()?;
+        window.set_focus()?;
         info!("Window shown");
+        auto_paste_clipboard(&app);
         Ok(())
     } else {
-        Err("Main window not found".to_string())
+        Err(SlovoError::WindowError("Main window not found".to_string()))
     }
 }
 
 /// Hide the main window to tray
 #[tauri::command]
-pub async fn hide_window(app: AppHandle) -> Result<(), String> {
+pub async fn hide_window(app: AppHandle) -> Result<(), SlovoError> {
     if let Some(window) = app.get_webview_window("main") {
-        window.hide().map_err(|e| e.to_string())?;
+        window.hide()?;
         info!("Window hidden");
         Ok(())
     } else {
-        Err("Main window not found".to_string())
+        Err(SlovoError::WindowError("Main window not found".to_string()))
     }
 }
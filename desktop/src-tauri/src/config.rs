@@ -0,0 +1,588 @@
+//! Application configuration
+//!
+//! Settings are persisted as `config.toml` in the platform app config directory.
+//! Missing fields fall back to their defaults so older files keep loading.
+//!
+//! The file holds named profiles (e.g. work and personal agents) under
+//! `[profiles.<name>]`, with `active_profile` selecting the one in use. A file
+//! written before profiles existed is read as the `default` profile.
+//!
+//! Edits to the file are picked up while the app runs, except for settings
+//! read only at startup: the whole `agent` section, `log_max_files`,
+//! `cancellation_ttl_secs`, `dedup_window_ms`, `audit_log_full_content`,
+//! `audit_log_max_bytes`, `metrics_enabled`, and `metrics_port`. Changes to
+//! those take effect after a restart.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tracing::{info, warn};
+
+use crate::error::SlovoError;
+use crate::rate_limit::RateLimitPolicy;
+use crate::state::AppState;
+
+/// Config file name inside the app config directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Quiet period after a file change before the config is re-read
+///
+/// Editors often truncate and rewrite in separate steps.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Profile used when none has been created or selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Shared, runtime-mutable configuration held in managed state
+pub type SharedConfig = Arc<RwLock<SlovoConfig>>;
+
+/// Top-level application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlovoConfig {
+    pub agent: AgentConfig,
+    /// Number of daily log files to keep
+    pub log_max_files: usize,
+    /// Write message, response and transcription text to the log instead of just its length
+    pub log_message_content: bool,
+    /// Ask the agent to return its reasoning with each response
+    pub include_reasoning: bool,
+    /// Reasoning effort hint passed to the agent, e.g. `"low"` or `"high"`
+    pub reasoning_effort: Option<String>,
+    /// Price per 1000 tokens used to estimate conversation cost
+    pub cost_per_1k_tokens: f64,
+    /// Microphone to record from; `None` uses the system default
+    pub preferred_input_device: Option<String>,
+    /// Speaker to play speech on; `None` uses the system default
+    pub preferred_output_device: Option<String>,
+    /// Mean amplitude above which a frame counts as speech; 0 disables trimming
+    pub vad_threshold: i16,
+    /// Shortest loud run treated as speech rather than noise
+    pub vad_min_speech_ms: u32,
+    /// Stop recording automatically once the speaker falls silent
+    pub vad_auto_stop: bool,
+    /// Silence after speech that ends an auto-stopped recording
+    pub vad_silence_timeout_ms: u32,
+    /// Capture sample rate expected by speech-to-text; 0 uses the device default
+    pub recording_sample_rate: u32,
+    /// Capture channel count; 0 uses the device default
+    pub recording_channels: u16,
+    /// Transcriptions the agent is less confident about are rejected; 0.0 accepts all
+    pub min_transcription_confidence: f32,
+    /// How long a cancellable request stays registered before it is forgotten
+    pub cancellation_ttl_secs: u64,
+    /// How long an identical message is answered from the first send instead of sent again
+    pub dedup_window_ms: u64,
+    /// Write plaintext messages to the audit log instead of SHA-256 hashes
+    pub audit_log_full_content: bool,
+    /// Size at which the audit log is rotated to `audit.log.1`
+    pub audit_log_max_bytes: u64,
+    /// Show desktop notifications, e.g. when the agent goes down or comes back
+    pub notifications_enabled: bool,
+    /// How long the agent must stay unreachable before a disconnect notification
+    pub disconnect_notify_after_secs: u64,
+    /// Serve Prometheus metrics on localhost
+    pub metrics_enabled: bool,
+    /// Port for the metrics endpoint, bound to 127.0.0.1 only
+    pub metrics_port: u16,
+    /// Preload the agent's model as soon as it connects
+    pub auto_warmup: bool,
+    /// System prompt given to new conversations; `None` uses the agent's own
+    pub default_persona: Option<String>,
+    /// Largest file `attach_file_to_message` will send, in bytes
+    pub max_attachment_bytes: u64,
+    /// Hide to the tray when the window is closed instead of quitting
+    pub close_to_tray: bool,
+    /// Processing without any request activity for this long resets the tray; 0 never does
+    pub tray_processing_timeout_secs: u64,
+    /// How long a tray state must hold before the icon shows it, in milliseconds
+    pub tray_debounce_ms: u64,
+    /// Whether the user has been told the app keeps running in the tray
+    pub tray_notice_shown: bool,
+    /// Keep the main window above other windows
+    pub always_on_top: bool,
+    /// Main window opacity, from 0.3 to 1.0
+    pub window_opacity: f64,
+    /// Send the clipboard text to the agent when the window is shown on an empty conversation
+    pub auto_paste_on_show: bool,
+    /// Have the agent title a new conversation after its first exchange
+    pub auto_title: bool,
+    /// Voice used for spoken replies; `None` uses the agent's default
+    pub tts_voice: Option<String>,
+    /// Speaking rate for spoken replies, where 1.0 is normal speed
+    pub tts_speed: f32,
+    /// Keyword that activates the assistant hands-free; `None` disables wake-word detection
+    pub wake_word: Option<String>,
+}
+
+impl Default for SlovoConfig {
+    fn default() -> Self {
+        Self {
+            agent: AgentConfig::default(),
+            log_max_files: 7,
+            log_message_content: cfg!(debug_assertions),
+            include_reasoning: true,
+            reasoning_effort: None,
+            cost_per_1k_tokens: 0.0,
+            preferred_input_device: None,
+            preferred_output_device: None,
+            vad_threshold: 500,
+            vad_min_speech_ms: 100,
+            vad_auto_stop: false,
+            vad_silence_timeout_ms: 1500,
+            recording_sample_rate: 16_000,
+            recording_channels: 1,
+            min_transcription_confidence: 0.0,
+            cancellation_ttl_secs: 600,
+            dedup_window_ms: 2000,
+            audit_log_full_content: false,
+            audit_log_max_bytes: 10 * 1024 * 1024,
+            notifications_enabled: true,
+            disconnect_notify_after_secs: 30,
+            metrics_enabled: true,
+            metrics_port: 9101,
+            auto_warmup: false,
+            default_persona: None,
+            max_attachment_bytes: 10 * 1024 * 1024,
+            close_to_tray: true,
+            tray_processing_timeout_secs: 300,
+            tray_debounce_ms: 500,
+            tray_notice_shown: false,
+            always_on_top: false,
+            window_opacity: 1.0,
+            auto_paste_on_show: false,
+            auto_title: true,
+            tts_voice: None,
+            tts_speed: 1.0,
+            wake_word: None,
+        }
+    }
+}
+
+/// Agent runtime connection and process settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    pub host: String,
+    pub port: u16,
+    pub timeout_secs: u64,
+    /// Python interpreter used to launch the agent runtime
+    pub python_path: String,
+    /// Arguments passed to the interpreter to start the agent
+    pub agent_args: Vec<String>,
+    /// Working directory for the agent process
+    pub agent_dir: Option<PathBuf>,
+    /// Launch the agent runtime when the app starts
+    pub auto_start: bool,
+    /// How long to wait for a (re)started agent to report healthy
+    pub startup_timeout_secs: u64,
+    /// Failed health checks right after launch are not reported for this long
+    pub startup_grace_period_secs: u64,
+    /// Seconds between agent health checks, from 1 to 300
+    pub health_check_interval_secs: u64,
+    /// Largest agent response body accepted, in bytes
+    pub max_response_bytes: usize,
+    /// Longest chat message sent to the agent, in characters
+    pub max_message_chars: usize,
+    /// Chat requests allowed in flight to the agent at once
+    pub max_concurrent_requests: usize,
+    /// HTTP(S) proxy for agent requests; when unset, `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` apply
+    pub proxy: Option<String>,
+    /// Sustained chat messages per second allowed to reach the agent
+    pub rate_limit_per_sec: f64,
+    /// Messages that may be sent back to back before throttling starts
+    pub rate_limit_burst: u32,
+    /// Whether throttled messages wait for capacity or fail immediately
+    pub rate_limit_policy: RateLimitPolicy,
+    /// Bearer token for the agent API, loaded from the OS keychain and never written to disk
+    #[serde(skip)]
+    pub auth_token: Option<String>,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8741,
+            timeout_secs: 30,
+            python_path: "python".to_string(),
+            agent_args: vec!["-m".to_string(), "slovo_agent.main".to_string()],
+            agent_dir: None,
+            auto_start: false,
+            startup_timeout_secs: 30,
+            startup_grace_period_secs: 15,
+            health_check_interval_secs: 10,
+            max_response_bytes: 10 * 1024 * 1024,
+            max_message_chars: 32_000,
+            max_concurrent_requests: 4,
+            proxy: None,
+            rate_limit_per_sec: 1.0,
+            rate_limit_burst: 5,
+            rate_limit_policy: RateLimitPolicy::Wait,
+            auth_token: None,
+        }
+    }
+}
+
+impl SlovoConfig {
+    /// Resolve the config file location for this app
+    pub fn path(app: &AppHandle) -> Result<PathBuf, SlovoError> {
+        app.path()
+            .app_config_dir()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .map_err(|e| SlovoError::ConfigError(e.to_string()))
+    }
+
+    /// Load the active profile's config, using defaults when no file exists yet
+    pub fn load(app: &AppHandle) -> Result<Self, SlovoError> {
+        Self::load_from(&Self::path(app)?)
+    }
+
+    /// Persist the config as the active profile
+    pub fn save(&self, app: &AppHandle) -> Result<(), SlovoError> {
+        self.save_to(&Self::path(app)?)
+    }
+
+    /// Load the active profile from an explicit path
+    pub fn load_from(path: &Path) -> Result<Self, SlovoError> {
+        Ok(ProfiledConfig::load_from(path)?.active())
+    }
+
+    /// Write the config as the active profile at an explicit path, leaving other profiles intact
+    pub fn save_to(&self, path: &Path) -> Result<(), SlovoError> {
+        let mut profiled = ProfiledConfig::load_from(path)?;
+        profiled
+            .profiles
+            .insert(profiled.active_profile.clone(), self.clone());
+        profiled.save_to(path)
+    }
+
+    /// Carry over the settings that only take effect on restart
+    fn keep_startup_settings(&mut self, running: &SlovoConfig) {
+        self.agent = running.agent.clone();
+        self.log_max_files = running.log_max_files;
+        self.cancellation_ttl_secs = running.cancellation_ttl_secs;
+        self.dedup_window_ms = running.dedup_window_ms;
+        self.audit_log_full_content = running.audit_log_full_content;
+        self.audit_log_max_bytes = running.audit_log_max_bytes;
+        self.metrics_enabled = running.metrics_enabled;
+        self.metrics_port = running.metrics_port;
+    }
+}
+
+/// Every saved profile and which one is in use, as laid out in `config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfiledConfig {
+    pub active_profile: String,
+    pub profiles: HashMap<String, SlovoConfig>,
+}
+
+impl Default for ProfiledConfig {
+    fn default() -> Self {
+        Self {
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: HashMap::from([(DEFAULT_PROFILE.to_string(), SlovoConfig::default())]),
+        }
+    }
+}
+
+impl ProfiledConfig {
+    /// Load every profile from the app's config file
+    pub fn load(app: &AppHandle) -> Result<Self, SlovoError> {
+        Self::load_from(&SlovoConfig::path(app)?)
+    }
+
+    /// Persist every profile to the app's config file
+    pub fn save(&self, app: &AppHandle) -> Result<(), SlovoError> {
+        self.save_to(&SlovoConfig::path(app)?)
+    }
+
+    /// Load profiles from an explicit path, upgrading a pre-profile file to `default`
+    pub fn load_from(path: &Path) -> Result<Self, SlovoError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        let table: toml::Table =
+            toml::from_str(&contents).map_err(|e| SlovoError::ConfigError(e.to_string()))?;
+        if table.contains_key("profiles") {
+            return table
+                .try_into()
+                .map_err(|e: toml::de::Error| SlovoError::ConfigError(e.to_string()));
+        }
+
+        let legacy: SlovoConfig = table
+            .try_into()
+            .map_err(|e: toml::de::Error| SlovoError::ConfigError(e.to_string()))?;
+        Ok(Self {
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: HashMap::from([(DEFAULT_PROFILE.to_string(), legacy)]),
+        })
+    }
+
+    /// Write every profile to an explicit path, creating parent directories
+    pub fn save_to(&self, path: &Path) -> Result<(), SlovoError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| SlovoError::ConfigError(e.to_string()))?;
+        // Noted before writing so the watcher never sees the write as an outside edit
+        own_writes().lock().unwrap().insert(path.to_path_buf(), contents.clone());
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Config of the active profile, or defaults if it has gone missing
+    pub fn active(&self) -> SlovoConfig {
+        self.profiles
+            .get(&self.active_profile)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Profile names in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Non-sensitive settings shared with diagnostics and reload events
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSummary {
+    pub agent_host: String,
+    pub agent_port: u16,
+    pub agent_timeout_secs: u64,
+    pub agent_auto_start: bool,
+    pub auth_token_set: bool,
+    pub include_reasoning: bool,
+    pub reasoning_effort: Option<String>,
+    pub vad_threshold: i16,
+    pub vad_auto_stop: bool,
+    pub recording_sample_rate: u32,
+    pub recording_channels: u16,
+    pub tts_speed: f32,
+    pub wake_word_enabled: bool,
+    pub close_to_tray: bool,
+    pub notifications_enabled: bool,
+    pub metrics_enabled: bool,
+}
+
+impl From<&SlovoConfig> for ConfigSummary {
+    fn from(config: &SlovoConfig) -> Self {
+        Self {
+            agent_host: config.agent.host.clone(),
+            agent_port: config.agent.port,
+            agent_timeout_secs: config.agent.timeout_secs,
+            agent_auto_start: config.agent.auto_start,
+            auth_token_set: config.agent.auth_token.is_some(),
+            include_reasoning: config.include_reasoning,
+            reasoning_effort: config.reasoning_effort.clone(),
+            vad_threshold: config.vad_threshold,
+            vad_auto_stop: config.vad_auto_stop,
+            recording_sample_rate: config.recording_sample_rate,
+            recording_channels: config.recording_channels,
+            tts_speed: config.tts_speed,
+            wake_word_enabled: config.wake_word.is_some(),
+            close_to_tray: config.close_to_tray,
+            notifications_enabled: config.notifications_enabled,
+            metrics_enabled: config.metrics_enabled,
+        }
+    }
+}
+
+/// Keeps the config file watcher alive while held in managed state
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Watch `config.toml` and apply edits made outside the app
+///
+/// Emits `config-reloaded` with the new summary, or `config-reload-failed`
+/// with the parse error while keeping the old config.
+pub fn watch(app: &AppHandle) -> Result<ConfigWatcher, SlovoError> {
+    let path = SlovoConfig::path(app)?;
+    let app = app.clone();
+    let watcher = watch_file(path.clone(), move || reload(&app, &path))?;
+    Ok(ConfigWatcher { _watcher: watcher })
+}
+
+/// Contents last written to each config path by this process
+fn own_writes() -> &'static Mutex<HashMap<PathBuf, String>> {
+    static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+    OWN_WRITES.get_or_init(Default::default)
+}
+
+/// Call `on_change` after each burst of outside edits to `path`
+///
+/// Writes made through [`ProfiledConfig::save_to`] are recognised by their
+/// contents and ignored. Watching stops when the returned watcher is dropped.
+fn watch_file(
+    path: PathBuf,
+    on_change: impl Fn() + Send + 'static,
+) -> Result<RecommendedWatcher, SlovoError> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| SlovoError::ConfigError("Config path has no parent directory".to_string()))?;
+    std::fs::create_dir_all(dir)?;
+
+    let (tx, rx) = mpsc::channel();
+    let file_name = path.file_name().map(|name| name.to_owned());
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else { return };
+        let touches_config = event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == file_name.as_deref());
+        if touches_config && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| SlovoError::ConfigError(format!("Failed to watch config file: {}", e)))?;
+    // Watch the directory so editors that replace the file are still seen
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| SlovoError::ConfigError(format!("Failed to watch config file: {}", e)))?;
+
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+            let contents = std::fs::read_to_string(&path).ok();
+            if contents.is_some() && own_writes().lock().unwrap().get(&path) == contents.as_ref() {
+                continue;
+            }
+            on_change();
+        }
+    });
+
+    Ok(watcher)
+}
+
+/// Re-read the config file and apply it, keeping startup-only settings
+fn reload<R: Runtime>(app: &AppHandle<R>, path: &Path) {
+    if !path.exists() {
+        return;
+    }
+
+    let mut loaded = match SlovoConfig::load_from(path) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Ignoring invalid config file edit: {}", e);
+            let _ = app.emit("config-reload-failed", e.to_string());
+            return;
+        }
+    };
+
+    let shared = &AppState::of(app).config;
+    let mut config = shared.write().unwrap();
+    loaded.keep_startup_settings(&config);
+    // Edits that change nothing in effect, e.g. reformatting, are not reported
+    if toml::to_string(&loaded).ok() == toml::to_string(&*config).ok() {
+        return;
+    }
+
+    *config = loaded;
+    info!("Config reloaded from {}", path.display());
+    let _ = app.emit("config-reloaded", ConfigSummary::from(&*config));
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Listener;
+
+    use super::*;
+    use crate::agent::AgentClient;
+
+    const EVENT_TIMEOUT: Duration = Duration::from_secs(3);
+
+    #[test]
+    fn watcher_reports_outside_edits_but_not_own_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        let (tx, rx) = mpsc::channel();
+        let _watcher = watch_file(path.clone(), move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        let profiled = ProfiledConfig::default();
+        profiled.save_to(&path).unwrap();
+        assert!(rx.recv_timeout(EVENT_TIMEOUT).is_err(), "own save reported as an edit");
+
+        let edited = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("notifications_enabled = true", "notifications_enabled = false");
+        std::fs::write(&path, edited).unwrap();
+        rx.recv_timeout(EVENT_TIMEOUT).expect("outside edit not reported");
+
+        let loaded = SlovoConfig::load_from(&path).unwrap();
+        assert!(!loaded.notifications_enabled);
+        // Saving over the edit is the app's own write again
+        profiled.save_to(&path).unwrap();
+        assert!(rx.recv_timeout(EVENT_TIMEOUT).is_err(), "own save reported as an edit");
+    }
+
+    #[test]
+    fn reload_applies_valid_edits_and_reports_invalid_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        let app = tauri::test::mock_app();
+        app.manage(AppState::new(AgentClient::new(), SlovoConfig::default()));
+        let handle = app.handle();
+        let (tx, rx) = mpsc::channel();
+        for event in ["config-reloaded", "config-reload-failed"] {
+            let tx = tx.clone();
+            handle.listen(event, move |e| {
+                let _ = tx.send((event, e.payload().to_string()));
+            });
+        }
+        let notifications_enabled =
+            || AppState::of(handle).config.read().unwrap().notifications_enabled;
+
+        let edited = SlovoConfig {
+            notifications_enabled: false,
+            ..SlovoConfig::default()
+        };
+        edited.save_to(&path).unwrap();
+        reload(handle, &path);
+
+        let (event, payload) = rx.recv_timeout(EVENT_TIMEOUT).unwrap();
+        assert_eq!(event, "config-reloaded");
+        assert!(serde_json::from_str::<serde_json::Value>(&payload).unwrap().is_object());
+        assert!(!notifications_enabled());
+
+        std::fs::write(&path, "notifications_enabled = \"sometimes\"\n").unwrap();
+        reload(handle, &path);
+
+        let (event, payload) = rx.recv_timeout(EVENT_TIMEOUT).unwrap();
+        assert_eq!(event, "config-reload-failed");
+        assert!(payload.contains("notifications_enabled"), "{}", payload);
+        assert!(!notifications_enabled());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn always_on_top_round_trips_through_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        assert!(!SlovoConfig::load_from(&path).unwrap().always_on_top);
+
+        let config = SlovoConfig {
+            always_on_top: true,
+            ..SlovoConfig::default()
+        };
+        config.save_to(&path).unwrap();
+
+        assert!(SlovoConfig::load_from(&path).unwrap().always_on_top);
+    }
+}
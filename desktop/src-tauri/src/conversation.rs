@@ -0,0 +1,858 @@
+//! Conversation state module
+//!
+//! The backend owns the "current conversation" so the frontend does not have
+//! to thread conversation ids through every message, and persists every
+//! conversation as `conversations/<id>.json` in the app data directory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::agent::{ChatResponse, TokenUsage};
+use crate::error::SlovoError;
+use crate::search::{self, SearchIndex, SearchResult};
+
+/// Length of the derived conversation title
+const TITLE_MAX_CHARS: usize = 60;
+/// Words kept from an agent-generated title
+const GENERATED_TITLE_MAX_WORDS: usize = 8;
+/// Upper bound on a single page of conversation summaries
+const MAX_PAGE_SIZE: u32 = 100;
+/// Upper bound on the results of one search
+pub const MAX_SEARCH_RESULTS: u32 = 200;
+
+/// Managed slot for the active conversation id
+#[derive(Default)]
+pub struct CurrentConversation(Mutex<Option<String>>);
+
+impl CurrentConversation {
+    /// The active conversation id, if a conversation has been started
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Make the given conversation the active one
+    pub fn set(&self, conversation_id: impl Into<String>) {
+        *self.0.lock().unwrap() = Some(conversation_id.into());
+    }
+
+    /// Forget the active conversation; returns the id that was cleared
+    pub fn clear(&self) -> Option<String> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Who authored a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// A single message in a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationMessage {
+    pub role: MessageRole,
+    pub content: String,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Earlier replies this one replaced when regenerated, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<AlternativeResponse>,
+    /// The reply was cut off mid-stream and holds only what arrived
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+}
+
+/// A superseded assistant reply, kept so the user can flip back to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternativeResponse {
+    pub content: String,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+}
+
+/// A persisted conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Replaces the agent's default system prompt for this conversation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// The system prompt changed since the agent last received it
+    #[serde(default)]
+    pub persona_pending: bool,
+    #[serde(default)]
+    pub messages: Vec<ConversationMessage>,
+    /// Unix time in milliseconds
+    pub created_at: u64,
+    /// Unix time in milliseconds
+    pub updated_at: u64,
+    /// Tokens used across all exchanges
+    #[serde(default)]
+    pub usage: TokenUsage,
+}
+
+impl Conversation {
+    /// Create an empty conversation
+    pub fn new(id: impl Into<String>) -> Self {
+        let now = now_ms();
+        Self {
+            id: id.into(),
+            title: None,
+            system_prompt: None,
+            persona_pending: false,
+            messages: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            usage: TokenUsage::default(),
+        }
+    }
+
+    /// Explicit title, or the first user message truncated
+    pub fn display_title(&self) -> String {
+        if let Some(title) = &self.title {
+            return title.clone();
+        }
+
+        self.messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.chars().take(TITLE_MAX_CHARS).collect())
+            .unwrap_or_default()
+    }
+
+    /// Untitled, with just the first user message and its reply
+    pub fn needs_title(&self) -> bool {
+        self.title.is_none()
+            && self.messages.len() == 2
+            && self.messages[0].role == MessageRole::User
+            && self.messages[1].role == MessageRole::Assistant
+    }
+
+    fn matches(&self, needle: &str) -> bool {
+        self.messages
+            .iter()
+            .any(|m| m.content.to_lowercase().contains(needle))
+    }
+}
+
+/// Conversation entry shown in the history list
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub message_count: usize,
+    /// Unix time in milliseconds
+    pub last_updated: u64,
+}
+
+impl From<&Conversation> for ConversationSummary {
+    fn from(conversation: &Conversation) -> Self {
+        Self {
+            id: conversation.id.clone(),
+            title: conversation.display_title(),
+            message_count: conversation.messages.len(),
+            last_updated: conversation.updated_at,
+        }
+    }
+}
+
+/// One page of conversation summaries
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationListResponse {
+    pub items: Vec<ConversationSummary>,
+    pub total: u64,
+}
+
+#[derive(Default)]
+struct Conversations {
+    by_id: HashMap<String, Conversation>,
+    /// Conversation ids ordered most recently updated first
+    index: Vec<String>,
+    /// Built on the first search and dropped whenever a conversation changes
+    search_index: Option<SearchIndex>,
+}
+
+impl Conversations {
+    fn reindex(&mut self) {
+        let by_id = &self.by_id;
+        self.index = by_id.keys().cloned().collect();
+        self.index
+            .sort_by(|a, b| by_id[b].updated_at.cmp(&by_id[a].updated_at).then(a.cmp(b)));
+    }
+
+    /// Move a freshly updated conversation to the front of the index
+    fn touch(&mut self, id: &str) {
+        self.index.retain(|existing| existing != id);
+        self.index.insert(0, id.to_string());
+        self.search_index = None;
+    }
+}
+
+/// Persistent store of all conversations
+pub struct ConversationManager {
+    dir: PathBuf,
+    conversations: Mutex<Conversations>,
+}
+
+impl ConversationManager {
+    /// Load every conversation file in `dir`, skipping unreadable ones
+    pub fn load(dir: PathBuf) -> Result<Self, SlovoError> {
+        std::fs::create_dir_all(&dir)?;
+
+        let mut conversations = Conversations::default();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match read_conversation(&path) {
+                Ok(conversation) => {
+                    conversations.by_id.insert(conversation.id.clone(), conversation);
+                }
+                Err(e) => warn!("Skipping unreadable conversation {:?}: {}", path, e),
+            }
+        }
+        conversations.reindex();
+
+        Ok(Self {
+            dir,
+            conversations: Mutex::new(conversations),
+        })
+    }
+
+    /// Create and persist an empty conversation under a fresh UUIDv4
+    ///
+    /// Without a title, one is derived from the first message. The persona,
+    /// if any, is sent with the first message.
+    pub fn new_conversation(&self, title: Option<String>, persona: Option<String>) -> Result<String, SlovoError> {
+        let id = Uuid::new_v4().to_string();
+        let mut conversation = Conversation::new(id.clone());
+        conversation.title = title;
+        conversation.persona_pending = persona.is_some();
+        conversation.system_prompt = persona;
+
+        write_conversation(&self.path_for(&id)?, &conversation)?;
+
+        let mut conversations = self.conversations.lock().unwrap();
+        conversations.by_id.insert(id.clone(), conversation);
+        conversations.touch(&id);
+        Ok(id)
+    }
+
+    /// Copy a conversation's history up to and including `from_message_index`
+    /// into a new conversation; the source is left untouched
+    pub fn fork_conversation(&self, source_id: &str, from_message_index: usize) -> Result<String, SlovoError> {
+        let mut conversations = self.conversations.lock().unwrap();
+        let source = conversations
+            .by_id
+            .get(source_id)
+            .ok_or_else(|| SlovoError::ConfigError(format!("Unknown conversation: {}", source_id)))?;
+        if from_message_index >= source.messages.len() {
+            return Err(SlovoError::agent("index out of range"));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let mut fork = Conversation::new(id.clone());
+        fork.title = Some(format!("Fork of: {}", source.display_title()));
+        fork.system_prompt = source.system_prompt.clone();
+        // The agent has never seen the fork's id
+        fork.persona_pending = fork.system_prompt.is_some();
+        fork.messages = source.messages[..=from_message_index].to_vec();
+
+        write_conversation(&self.path_for(&id)?, &fork)?;
+        conversations.by_id.insert(id.clone(), fork);
+        conversations.touch(&id);
+        Ok(id)
+    }
+
+    /// Look up a conversation by id
+    pub fn get(&self, id: &str) -> Option<Conversation> {
+        self.conversations.lock().unwrap().by_id.get(id).cloned()
+    }
+
+    /// Accumulated token usage for a conversation
+    pub fn token_usage(&self, id: &str) -> Option<TokenUsage> {
+        self.conversations.lock().unwrap().by_id.get(id).map(|c| c.usage)
+    }
+
+    /// The custom system prompt for a conversation, if one is set
+    pub fn system_prompt(&self, id: &str) -> Option<String> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .by_id
+            .get(id)
+            .and_then(|c| c.system_prompt.clone())
+    }
+
+    /// Whether a conversation with this id has been saved
+    pub fn contains(&self, id: &str) -> bool {
+        self.conversations.lock().unwrap().by_id.contains_key(id)
+    }
+
+    /// The system prompt still to be sent for a conversation, if it changed since the agent last saw it
+    ///
+    /// A cleared prompt comes back empty, telling the agent to drop the old one.
+    pub fn pending_system_prompt(&self, id: &str) -> Option<String> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .by_id
+            .get(id)
+            .filter(|c| c.persona_pending)
+            .map(|c| c.system_prompt.clone().unwrap_or_default())
+    }
+
+    /// Note that the agent received `prompt` for a conversation
+    ///
+    /// A conversation the agent created with the default persona adopts it here.
+    pub fn mark_persona_sent(&self, id: &str, prompt: &str) -> Result<(), SlovoError> {
+        let path = self.path_for(id)?;
+        let mut conversations = self.conversations.lock().unwrap();
+        let Some(conversation) = conversations.by_id.get_mut(id) else {
+            return Ok(());
+        };
+
+        if !conversation.persona_pending && conversation.system_prompt.is_none() && !prompt.is_empty() {
+            conversation.system_prompt = Some(prompt.to_string());
+        } else if conversation.system_prompt.as_deref().unwrap_or_default() != prompt {
+            // Changed again while the message was in flight; keep it pending
+            return Ok(());
+        }
+        conversation.persona_pending = false;
+        write_conversation(&path, conversation)
+    }
+
+    /// Set or clear a conversation's system prompt; returns false if the conversation does not exist
+    pub fn set_system_prompt(&self, id: &str, prompt: Option<String>) -> Result<bool, SlovoError> {
+        let path = self.path_for(id)?;
+        let mut conversations = self.conversations.lock().unwrap();
+        let Some(conversation) = conversations.by_id.get_mut(id) else {
+            return Ok(false);
+        };
+
+        if conversation.system_prompt != prompt {
+            conversation.persona_pending = true;
+        }
+        conversation.system_prompt = prompt;
+        conversation.updated_at = now_ms();
+        write_conversation(&path, conversation)?;
+        conversations.touch(id);
+        Ok(true)
+    }
+
+    /// Set or clear a conversation's title; returns false if the conversation does not exist
+    pub fn set_title(&self, id: &str, title: Option<String>) -> Result<bool, SlovoError> {
+        let path = self.path_for(id)?;
+        let mut conversations = self.conversations.lock().unwrap();
+        let Some(conversation) = conversations.by_id.get_mut(id) else {
+            return Ok(false);
+        };
+
+        conversation.title = title;
+        conversation.updated_at = now_ms();
+        write_conversation(&path, conversation)?;
+        conversations.touch(id);
+        Ok(true)
+    }
+
+    /// Append a user message and the agent's reply, then persist the conversation
+    pub fn record_exchange(&self, user_message: &str, response: &ChatResponse) -> Result<(), SlovoError> {
+        let path = self.path_for(&response.conversation_id)?;
+        let now = now_ms();
+        let mut conversations = self.conversations.lock().unwrap();
+
+        let conversation = conversations
+            .by_id
+            .entry(response.conversation_id.clone())
+            .or_insert_with(|| Conversation::new(response.conversation_id.clone()));
+        conversation.messages.push(ConversationMessage {
+            role: MessageRole::User,
+            content: user_message.to_string(),
+            timestamp: now,
+            reasoning: None,
+            alternatives: Vec::new(),
+            interrupted: false,
+        });
+        conversation.messages.push(ConversationMessage {
+            role: MessageRole::Assistant,
+            content: response.response.clone(),
+            timestamp: now,
+            reasoning: response.reasoning.clone(),
+            alternatives: Vec::new(),
+            interrupted: false,
+        });
+        conversation.updated_at = now;
+        if let Some(usage) = response.usage {
+            conversation.usage += usage;
+        }
+
+        write_conversation(&path, conversation)?;
+        conversations.touch(&response.conversation_id);
+        Ok(())
+    }
+
+    /// Save the part of a streamed reply that arrived before the stream broke
+    ///
+    /// The user message is saved with it when known.
+    pub fn record_interrupted(
+        &self,
+        conversation_id: &str,
+        user_message: Option<&str>,
+        partial: &str,
+    ) -> Result<(), SlovoError> {
+        let path = self.path_for(conversation_id)?;
+        let now = now_ms();
+        let mut conversations = self.conversations.lock().unwrap();
+
+        let conversation = conversations
+            .by_id
+            .entry(conversation_id.to_string())
+            .or_insert_with(|| Conversation::new(conversation_id));
+        if let Some(message) = user_message {
+            conversation.messages.push(ConversationMessage {
+                role: MessageRole::User,
+                content: message.to_string(),
+                timestamp: now,
+                reasoning: None,
+                alternatives: Vec::new(),
+                interrupted: false,
+            });
+        }
+        conversation.messages.push(ConversationMessage {
+            role: MessageRole::Assistant,
+            content: partial.to_string(),
+            timestamp: now,
+            reasoning: None,
+            alternatives: Vec::new(),
+            interrupted: true,
+        });
+        conversation.updated_at = now;
+
+        write_conversation(&path, conversation)?;
+        conversations.touch(conversation_id);
+        Ok(())
+    }
+
+    /// The message that opened a conversation
+    pub fn first_user_message(&self, id: &str) -> Option<String> {
+        let conversations = self.conversations.lock().unwrap();
+        let conversation = conversations.by_id.get(id)?;
+        conversation
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.clone())
+    }
+
+    /// The most recent message the user sent in a conversation
+    pub fn last_user_message(&self, id: &str) -> Option<String> {
+        let conversations = self.conversations.lock().unwrap();
+        let conversation = conversations.by_id.get(id)?;
+        conversation
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.clone())
+    }
+
+    /// Make a regenerated reply the answer to the conversation's last user message
+    ///
+    /// The reply it replaces moves into `alternatives`; with no earlier reply it
+    /// is simply appended.
+    pub fn record_regenerated(&self, id: &str, response: &ChatResponse) -> Result<(), SlovoError> {
+        let path = self.path_for(id)?;
+        let now = now_ms();
+        let mut conversations = self.conversations.lock().unwrap();
+        let conversation = conversations
+            .by_id
+            .get_mut(id)
+            .ok_or_else(|| SlovoError::ConfigError(format!("Unknown conversation: {}", id)))?;
+        let Some(user_index) = conversation.messages.iter().rposition(|m| m.role == MessageRole::User)
+        else {
+            return Err(SlovoError::agent("conversation has no user message"));
+        };
+
+        match conversation.messages.get_mut(user_index + 1) {
+            Some(reply) if reply.role == MessageRole::Assistant => {
+                reply.alternatives.push(AlternativeResponse {
+                    content: std::mem::replace(&mut reply.content, response.response.clone()),
+                    timestamp: reply.timestamp,
+                    reasoning: std::mem::replace(&mut reply.reasoning, response.reasoning.clone()),
+                });
+                reply.timestamp = now;
+            }
+            _ => conversation.messages.insert(
+                user_index + 1,
+                ConversationMessage {
+                    role: MessageRole::Assistant,
+                    content: response.response.clone(),
+                    timestamp: now,
+                    reasoning: response.reasoning.clone(),
+                    alternatives: Vec::new(),
+                    interrupted: false,
+                },
+            ),
+        }
+        conversation.updated_at = now;
+        if let Some(usage) = response.usage {
+            conversation.usage += usage;
+        }
+
+        write_conversation(&path, conversation)?;
+        conversations.touch(id);
+        Ok(())
+    }
+
+    /// Remove a conversation from memory and disk; returns false if it did not exist
+    pub fn delete_conversation(&self, id: &str) -> Result<bool, SlovoError> {
+        let mut conversations = self.conversations.lock().unwrap();
+        if conversations.by_id.remove(id).is_none() {
+            return Ok(false);
+        }
+        conversations.index.retain(|existing| existing != id);
+        conversations.search_index = None;
+
+        match std::fs::remove_file(self.path_for(id)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(true),
+        }
+    }
+
+    /// Number of saved conversations
+    pub fn count(&self) -> usize {
+        self.conversations.lock().unwrap().by_id.len()
+    }
+
+    /// Return one zero-based page of summaries, newest first
+    ///
+    /// When `search` is set, only conversations with a message containing it
+    /// (case-insensitive) are counted and returned.
+    pub fn list(&self, page: u32, page_size: u32, search: Option<&str>) -> ConversationListResponse {
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE) as usize;
+        let offset = (page as usize).saturating_mul(page_size);
+        let needle = search
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty());
+
+        let conversations = self.conversations.lock().unwrap();
+        let matching = conversations
+            .index
+            .iter()
+            .map(|id| &conversations.by_id[id])
+            .filter(|c| needle.as_deref().map_or(true, |n| c.matches(n)));
+
+        let mut total = 0u64;
+        let mut items = Vec::new();
+        for (position, conversation) in matching.enumerate() {
+            total += 1;
+            if position >= offset && items.len() < page_size {
+                items.push(ConversationSummary::from(conversation));
+            }
+        }
+
+        ConversationListResponse { items, total }
+    }
+
+    /// Messages containing `query`, ignoring case, most recently updated conversations first
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() || max_results == 0 {
+            return Vec::new();
+        }
+
+        let mut guard = self.conversations.lock().unwrap();
+        let conversations = &mut *guard;
+        let search_index = conversations
+            .search_index
+            .get_or_insert_with(|| SearchIndex::build(conversations.by_id.values()));
+        let candidates = search_index.candidates(&needle);
+
+        let mut results = Vec::new();
+        for id in &conversations.index {
+            let conversation = &conversations.by_id[id];
+            for (position, message) in conversation.messages.iter().enumerate() {
+                let candidate = candidates
+                    .as_ref()
+                    .map_or(true, |c| c.contains(&(id.clone(), position)));
+                if !candidate {
+                    continue;
+                }
+                if let Some(snippet) = search::snippet(&message.content, &needle) {
+                    results.push(SearchResult {
+                        conversation_id: id.clone(),
+                        title: conversation.display_title(),
+                        matched_message_index: position,
+                        snippet,
+                    });
+                    if results.len() == max_results {
+                        return results;
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// File path for a conversation, rejecting ids that could escape the directory
+    fn path_for(&self, id: &str) -> Result<PathBuf, SlovoError> {
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(SlovoError::ConfigError(format!("Invalid conversation id: {:?}", id)));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
+
+fn read_conversation(path: &Path) -> Result<Conversation, SlovoError> {
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|e| SlovoError::ConfigError(e.to_string()))
+}
+
+/// Write via a temp file and rename so a crash never leaves a half-written conversation
+fn write_conversation(path: &Path, conversation: &Conversation) -> Result<(), SlovoError> {
+    let contents = serde_json::to_string_pretty(conversation)
+        .map_err(|e| SlovoError::ConfigError(e.to_string()))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Current unix time in milliseconds
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Tidy an agent-generated title: first line, no wrapping quotes or trailing period
+///
+/// Returns `None` when nothing usable is left.
+pub fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line.strip_prefix("Title:").unwrap_or(line);
+    let line = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '`'))
+        .trim_end_matches('.')
+        .trim();
+    let title: String = line
+        .split_whitespace()
+        .take(GENERATED_TITLE_MAX_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(TITLE_MAX_CHARS)
+        .collect();
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> (tempfile::TempDir, ConversationManager) {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ConversationManager::load(dir.path().to_path_buf()).unwrap();
+        (dir, manager)
+    }
+
+    fn exchange(manager: &ConversationManager, id: &str, message: &str) {
+        let response = ChatResponse {
+            id: "resp-1".to_string(),
+            response: format!("echo: {}", message),
+            conversation_id: id.to_string(),
+            reasoning: None,
+            usage: None,
+        };
+        manager.record_exchange(message, &response).unwrap();
+    }
+
+    fn ids(page: &ConversationListResponse) -> Vec<&str> {
+        page.items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    /// `(conversation_id, matched_message_index, snippet)` of a search result
+    type Hit = (String, usize, String);
+
+    fn hits(manager: &ConversationManager, query: &str, max: usize) -> Vec<Hit> {
+        manager
+            .search(query, max)
+            .into_iter()
+            .map(|result| (result.conversation_id, result.matched_message_index, result.snippet))
+            .collect()
+    }
+
+    fn hit(id: &str, index: usize, snippet: &str) -> Hit {
+        (id.to_string(), index, snippet.to_string())
+    }
+
+    #[test]
+    fn list_pages_newest_first() {
+        let (_dir, manager) = manager();
+        for id in ["a", "b", "c", "d", "e"] {
+            exchange(&manager, id, "hello");
+        }
+
+        assert_eq!(ids(&manager.list(0, 2, None)), ["e", "d"]);
+        assert_eq!(ids(&manager.list(1, 2, None)), ["c", "b"]);
+        let last = manager.list(2, 2, None);
+        assert_eq!(ids(&last), ["a"]);
+        assert_eq!(last.total, 5);
+    }
+
+    #[test]
+    fn list_past_the_end_is_empty_but_keeps_total() {
+        let (_dir, manager) = manager();
+        exchange(&manager, "a", "hello");
+
+        let page = manager.list(1, 10, None);
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 1);
+        assert!(manager.list(u32::MAX, MAX_PAGE_SIZE, None).items.is_empty());
+    }
+
+    #[test]
+    fn list_clamps_page_size() {
+        let (_dir, manager) = manager();
+        for i in 0..(MAX_PAGE_SIZE + 1) {
+            exchange(&manager, &format!("c{}", i), "hello");
+        }
+
+        assert_eq!(manager.list(0, 0, None).items.len(), 1);
+        assert_eq!(manager.list(0, u32::MAX, None).items.len(), MAX_PAGE_SIZE as usize);
+    }
+
+    #[test]
+    fn list_is_empty_without_conversations_or_matches() {
+        let (_dir, manager) = manager();
+        let page = manager.list(0, 10, None);
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 0);
+
+        exchange(&manager, "a", "hello");
+        let page = manager.list(0, 10, Some("goodbye"));
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 0);
+        assert_eq!(manager.list(0, 10, Some("  HELLO ")).total, 1);
+    }
+
+    #[test]
+    fn fork_is_independent_of_its_source() {
+        let (dir, manager) = manager();
+        exchange(&manager, "source", "first");
+        exchange(&manager, "source", "second");
+
+        let fork = manager.fork_conversation("source", 1).unwrap();
+        exchange(&manager, &fork, "only in fork");
+        exchange(&manager, "source", "only in source");
+
+        let contents = |c: Conversation| -> Vec<String> {
+            c.messages.into_iter().map(|m| m.content).collect()
+        };
+        let reloaded = ConversationManager::load(dir.path().to_path_buf()).unwrap();
+        for manager in [&manager, &reloaded] {
+            assert_eq!(
+                contents(manager.get(&fork).unwrap()),
+                ["first", "echo: first", "only in fork", "echo: only in fork"]
+            );
+            assert_eq!(
+                contents(manager.get("source").unwrap()),
+                [
+                    "first",
+                    "echo: first",
+                    "second",
+                    "echo: second",
+                    "only in source",
+                    "echo: only in source",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn fork_rejects_an_index_past_the_end() {
+        let (_dir, manager) = manager();
+        exchange(&manager, "source", "first");
+
+        assert!(manager.fork_conversation("source", 2).is_err());
+        assert!(manager.fork_conversation("missing", 0).is_err());
+        assert_eq!(manager.count(), 1);
+    }
+
+    #[test]
+    fn search_returns_the_matching_message_index_and_snippet() {
+        let (_dir, manager) = manager();
+        exchange(&manager, "a", "The quick brown fox");
+        exchange(&manager, "a", "A lazy dog");
+        exchange(&manager, "b", "Nothing to see");
+
+        assert_eq!(
+            hits(&manager, "  LAZY ", 10),
+            [hit("a", 2, "A lazy dog"), hit("a", 3, "echo: A lazy dog")]
+        );
+        assert_eq!(manager.search("lazy", 1)[0].title, manager.get("a").unwrap().display_title());
+        assert!(hits(&manager, "giraffe", 10).is_empty());
+        assert!(hits(&manager, "  ", 10).is_empty());
+    }
+
+    #[test]
+    fn search_stops_at_max_results_newest_conversation_first() {
+        let (_dir, manager) = manager();
+        for id in ["a", "b", "c", "d", "e"] {
+            exchange(&manager, id, "hello there");
+        }
+
+        let found = hits(&manager, "hello", 3);
+
+        assert_eq!(
+            found,
+            [
+                hit("e", 0, "hello there"),
+                hit("e", 1, "echo: hello there"),
+                hit("d", 0, "hello there"),
+            ]
+        );
+        assert!(hits(&manager, "hello", 0).is_empty());
+    }
+
+    #[test]
+    fn search_sees_exchanges_recorded_after_the_index_was_built() {
+        let (_dir, manager) = manager();
+        exchange(&manager, "a", "hello");
+        // Builds and caches the index
+        assert!(hits(&manager, "giraffe", 10).is_empty());
+
+        exchange(&manager, "a", "a giraffe");
+        exchange(&manager, "b", "giraffes too");
+
+        assert_eq!(
+            hits(&manager, "giraffe", 10),
+            [
+                hit("b", 0, "giraffes too"),
+                hit("b", 1, "echo: giraffes too"),
+                hit("a", 2, "a giraffe"),
+                hit("a", 3, "echo: a giraffe"),
+            ]
+        );
+    }
+}
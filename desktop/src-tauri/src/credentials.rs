@@ -0,0 +1,47 @@
+//! OS keychain access for agent credentials
+//!
+//! The agent auth token is kept in the platform credential store (Keychain,
+//! Credential Manager, Secret Service) rather than in `config.toml`.
+
+use keyring::Entry;
+
+use crate::config::DEFAULT_PROFILE;
+use crate::error::SlovoError;
+
+/// Keychain service and account names for the agent token
+const KEYCHAIN_SERVICE: &str = "com.slovo.assistant";
+const AUTH_TOKEN_ACCOUNT: &str = "agent-auth-token";
+
+/// Each profile keeps its own token; the default profile uses the original account
+fn auth_token_entry(profile: &str) -> Result<Entry, SlovoError> {
+    let account = if profile == DEFAULT_PROFILE {
+        AUTH_TOKEN_ACCOUNT.to_string()
+    } else {
+        format!("{}:{}", AUTH_TOKEN_ACCOUNT, profile)
+    };
+    Entry::new(KEYCHAIN_SERVICE, &account)
+        .map_err(|e| SlovoError::ConfigError(format!("Keychain unavailable: {}", e)))
+}
+
+/// Load a profile's agent auth token, if one has been stored
+pub fn load_auth_token(profile: &str) -> Result<Option<String>, SlovoError> {
+    match auth_token_entry(profile)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(SlovoError::ConfigError(format!("Failed to read auth token: {}", e))),
+    }
+}
+
+/// Store or clear a profile's agent auth token
+pub fn store_auth_token(profile: &str, token: Option<&str>) -> Result<(), SlovoError> {
+    let entry = auth_token_entry(profile)?;
+    let result = match token {
+        Some(token) => entry.set_password(token),
+        None => match entry.delete_credential() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            other => other,
+        },
+    };
+
+    result.map_err(|e| SlovoError::ConfigError(format!("Failed to update auth token: {}", e)))
+}
@@ -0,0 +1,181 @@
+//! `slovo://` deep links
+//!
+//! `slovo://conversation/<id>` opens an existing conversation and
+//! `slovo://new?prompt=<text>` starts a new one with the input pre-filled.
+//! Windows and Linux pass the link as a launch argument; macOS delivers it as
+//! an open-URL event.
+
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+use url::Url;
+
+use crate::commands;
+use crate::error::SlovoError;
+use crate::events::NavigateToConversation;
+
+/// URL scheme registered for the app
+pub const SCHEME: &str = "slovo";
+
+/// What a deep link asks the app to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlovoAction {
+    /// Open the conversation with this id
+    OpenConversation { id: String },
+    /// Start a new conversation, optionally with text waiting in the input
+    NewConversation { prompt: Option<String> },
+}
+
+/// Parse a `slovo://` URL into the action it names
+pub fn parse_slovo_url(raw: &str) -> Result<SlovoAction, SlovoError> {
+    let url = Url::parse(raw.trim()).map_err(|e| invalid(raw, e))?;
+    if url.scheme() != SCHEME {
+        return Err(invalid(raw, format!("scheme is not {}", SCHEME)));
+    }
+
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    match (url.host_str().unwrap_or_default(), segments.as_slice()) {
+        ("conversation", [id]) if is_conversation_id(id) => Ok(SlovoAction::OpenConversation {
+            id: id.to_string(),
+        }),
+        ("conversation", _) => Err(invalid(raw, "expected slovo://conversation/<id>")),
+        ("new", []) => {
+            let prompt = url
+                .query_pairs()
+                .find(|(key, _)| key == "prompt")
+                .map(|(_, value)| value.into_owned())
+                .filter(|prompt| !prompt.trim().is_empty());
+            Ok(SlovoAction::NewConversation { prompt })
+        }
+        (action, _) => Err(invalid(raw, format!("unknown action '{}'", action))),
+    }
+}
+
+/// Conversation ids are generated by us, so anything outside their alphabet is refused
+fn is_conversation_id(id: &str) -> bool {
+    id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn invalid(raw: &str, reason: impl std::fmt::Display) -> SlovoError {
+    SlovoError::InvalidLink(format!("{}: {}", raw, reason))
+}
+
+/// Handle every `slovo://` link among the process's launch arguments
+pub fn handle_launch_args(app: &AppHandle) {
+    let prefix = format!("{}://", SCHEME);
+    for arg in std::env::args().skip(1).filter(|arg| arg.starts_with(&prefix)) {
+        open(app, arg);
+    }
+}
+
+/// Carry out the action named by a deep link in the background
+pub fn open(app: &AppHandle, raw: String) {
+    let action = match parse_slovo_url(&raw) {
+        Ok(action) => action,
+        Err(e) => {
+            warn!("Ignoring deep link: {}", e);
+            return;
+        }
+    };
+    info!("Opening deep link {:?}", action);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = perform(&app, action).await {
+            warn!("Failed to open deep link: {}", e);
+        }
+    });
+}
+
+async fn perform(app: &AppHandle, action: SlovoAction) -> Result<(), SlovoError> {
+    let navigation = match action {
+        SlovoAction::OpenConversation { id } => NavigateToConversation {
+            conversation_id: id,
+            prompt: None,
+        },
+        SlovoAction::NewConversation { prompt } => {
+            // The command logs its own failure
+            let Some(id) = commands::new_conversation(app.clone(), None).await.data else {
+                return Ok(());
+            };
+            NavigateToConversation {
+                conversation_id: id,
+                prompt,
+            }
+        }
+    };
+
+    // Not `show_window`, whose clipboard auto-paste would preempt the link
+    if let Some(window) = app.get_webview_window("main") {
+        window.show()?;
+        window.set_focus()?;
+    }
+    app.emit(NavigateToConversation::EVENT, navigation)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_conversation(id: &str) -> SlovoAction {
+        SlovoAction::OpenConversation { id: id.to_string() }
+    }
+
+    fn new_conversation(prompt: Option<&str>) -> SlovoAction {
+        SlovoAction::NewConversation {
+            prompt: prompt.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_conversation_links() {
+        let id = "5f0c1a9e-2b1d-4c55-9d1e-6a3f0e2b7c10";
+        let link = format!("slovo://conversation/{}", id);
+        assert_eq!(parse_slovo_url(&link).unwrap(), open_conversation(id));
+        assert_eq!(parse_slovo_url(&format!(" {}/ ", link)).unwrap(), open_conversation(id));
+    }
+
+    #[test]
+    fn decodes_percent_encoded_prompts() {
+        assert_eq!(
+            parse_slovo_url("slovo://new?prompt=What%27s%20the%20weather%3F").unwrap(),
+            new_conversation(Some("What's the weather?"))
+        );
+        let cyrillic = "slovo://new?prompt=%D0%BF%D1%80%D0%B8%D0%B2%D1%96%D1%82+%26+bye";
+        assert_eq!(parse_slovo_url(cyrillic).unwrap(), new_conversation(Some("привіт & bye")));
+        assert_eq!(parse_slovo_url("slovo://new?prompt=%20+").unwrap(), new_conversation(None));
+        assert_eq!(parse_slovo_url("slovo://new").unwrap(), new_conversation(None));
+    }
+
+    #[test]
+    fn refuses_encoded_path_tricks_in_conversation_ids() {
+        for link in [
+            "slovo://conversation/..%2F..%2Fconfig",
+            "slovo://conversation/abc%00",
+            "slovo://conversation/abc%20def",
+        ] {
+            assert!(parse_slovo_url(link).is_err(), "{} was accepted", link);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        for link in [
+            "",
+            "not a url",
+            "slovo:",
+            "https://conversation/abc",
+            "slovo://conversation",
+            "slovo://conversation/a/b",
+            "slovo://new/extra",
+            "slovo://settings",
+        ] {
+            let error = parse_slovo_url(link).unwrap_err();
+            assert!(matches!(error, SlovoError::InvalidLink(_)), "{}: {:?}", link, error);
+        }
+    }
+}
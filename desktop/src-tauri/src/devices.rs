@@ -0,0 +1,190 @@
+//! Audio device enumeration
+//!
+//! Lists the host's microphones and speakers so the user can pick which ones
+//! to record from and play speech on.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+use crate::error::SlovoError;
+use crate::events::AudioDeviceMissing;
+use crate::state::AppState;
+
+/// Common sample rates reported when a device supports a continuous range
+const STANDARD_SAMPLE_RATES: [u32; 8] = [8_000, 16_000, 22_050, 32_000, 44_100, 48_000, 88_200, 96_000];
+
+/// Whether a device records or plays audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Input,
+    Output,
+}
+
+/// An audio device available on this machine
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceInfo {
+    /// Stable identifier; cpal exposes no ids, so this is the device name
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub sample_rates: Vec<u32>,
+}
+
+/// Microphones and speakers, as returned by `list_audio_devices`
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDevices {
+    pub inputs: Vec<AudioDeviceInfo>,
+    pub outputs: Vec<AudioDeviceInfo>,
+}
+
+/// Enumerate both input and output devices
+///
+/// This talks to the OS audio stack synchronously, so call it off the async runtime.
+pub fn list_devices() -> Result<AudioDevices, SlovoError> {
+    Ok(AudioDevices {
+        inputs: list_input_devices()?,
+        outputs: list_output_devices()?,
+    })
+}
+
+/// Enumerate the default host's input devices
+///
+/// This talks to the OS audio stack synchronously, so call it off the async runtime.
+pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>, SlovoError> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| SlovoError::VoiceError(format!("Failed to list input devices: {}", e)))?;
+    Ok(describe(devices, default_name, DeviceKind::Input))
+}
+
+/// Enumerate the default host's output devices
+///
+/// This talks to the OS audio stack synchronously, so call it off the async runtime.
+pub fn list_output_devices() -> Result<Vec<AudioDeviceInfo>, SlovoError> {
+    let host = cpal::default_host();
+    let default_name = host.default_output_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .output_devices()
+        .map_err(|e| SlovoError::VoiceError(format!("Failed to list output devices: {}", e)))?;
+    Ok(describe(devices, default_name, DeviceKind::Output))
+}
+
+fn describe(
+    devices: impl Iterator<Item = cpal::Device>,
+    default_name: Option<String>,
+    kind: DeviceKind,
+) -> Vec<AudioDeviceInfo> {
+    devices
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some(AudioDeviceInfo {
+                id: name.clone(),
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                sample_rates: sample_rates(&device, kind),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Whether an input device with this id is currently connected
+pub fn input_device_exists(id: &str) -> Result<bool, SlovoError> {
+    Ok(list_input_devices()?.iter().any(|device| device.id == id))
+}
+
+/// Whether an output device with this id is currently connected
+pub fn output_device_exists(id: &str) -> Result<bool, SlovoError> {
+    Ok(list_output_devices()?.iter().any(|device| device.id == id))
+}
+
+/// Look up an output device by id, falling back to the system default when `id` is `None`
+pub fn find_output_device(id: Option<&str>) -> Result<cpal::Device, SlovoError> {
+    let host = cpal::default_host();
+    let device = match id {
+        Some(id) => host
+            .output_devices()
+            .map_err(|e| SlovoError::VoiceError(format!("Failed to list output devices: {}", e)))?
+            .find(|device| device.name().is_ok_and(|name| name == id)),
+        None => host.default_output_device(),
+    };
+
+    device.ok_or_else(|| match id {
+        Some(id) => SlovoError::VoiceError(format!("Audio device not found: {}", id)),
+        None => SlovoError::VoiceError("No default output device".to_string()),
+    })
+}
+
+/// The saved device of `kind`, or `None` for the system default
+///
+/// A saved device that is no longer connected falls back to the default and
+/// is reported with `audio-device-missing`; the choice itself is kept in case
+/// the device comes back.
+pub async fn saved_device(app: &AppHandle, kind: DeviceKind) -> Option<String> {
+    let saved = {
+        let config = &AppState::of(app).config;
+        let config = config.read().unwrap();
+        match kind {
+            DeviceKind::Input => config.preferred_input_device.clone(),
+            DeviceKind::Output => config.preferred_output_device.clone(),
+        }
+    }?;
+
+    let lookup = saved.clone();
+    let exists = tauri::async_runtime::spawn_blocking(move || match kind {
+        DeviceKind::Input => input_device_exists(&lookup),
+        DeviceKind::Output => output_device_exists(&lookup),
+    })
+    .await;
+    match exists {
+        Ok(Ok(false)) => {
+            warn!("Saved {:?} device '{}' is gone, using the system default", kind, saved);
+            let payload = AudioDeviceMissing { kind, device: saved };
+            let _ = app.emit(AudioDeviceMissing::EVENT, payload);
+            None
+        }
+        // When enumeration fails, let opening the device report the problem
+        _ => Some(saved),
+    }
+}
+
+/// Look up an input device by id, falling back to the system default when `id` is `None`
+pub fn find_input_device(id: Option<&str>) -> Result<cpal::Device, SlovoError> {
+    let host = cpal::default_host();
+    let device = match id {
+        Some(id) => host
+            .input_devices()
+            .map_err(|e| SlovoError::VoiceError(format!("Failed to list input devices: {}", e)))?
+            .find(|device| device.name().is_ok_and(|name| name == id)),
+        None => host.default_input_device(),
+    };
+
+    device.ok_or_else(|| match id {
+        Some(id) => SlovoError::VoiceError(format!("Audio device not found: {}", id)),
+        None => SlovoError::VoiceError("No default input device".to_string()),
+    })
+}
+
+/// Standard rates inside any of the device's supported ranges
+fn sample_rates(device: &cpal::Device, kind: DeviceKind) -> Vec<u32> {
+    let ranges: Vec<(u32, u32)> = match kind {
+        DeviceKind::Input => device.supported_input_configs().map(|configs| {
+            configs.map(|c| (c.min_sample_rate().0, c.max_sample_rate().0)).collect()
+        }),
+        DeviceKind::Output => device.supported_output_configs().map(|configs| {
+            configs.map(|c| (c.min_sample_rate().0, c.max_sample_rate().0)).collect()
+        }),
+    }
+    .unwrap_or_default();
+
+    STANDARD_SAMPLE_RATES
+        .into_iter()
+        .filter(|rate| ranges.iter().any(|(min, max)| (min..=max).contains(&rate)))
+        .collect()
+}
@@ -1,5 +1,6 @@
 //! Error types for Slovo
 
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -7,8 +8,20 @@ pub enum SlovoError {
     #[error("Failed to connect to agent: {0}")]
     AgentConnection(String),
 
-    #[error("Agent error: {0}")]
-    AgentError(String),
+    /// The agent accepted the connection but did not answer in time
+    #[error("Agent did not respond in time: {0}")]
+    Timeout(String),
+
+    /// `code` is the agent's machine-readable error code, e.g. `model_overloaded`
+    #[error("Agent error: {message}")]
+    AgentError { code: Option<String>, message: String },
+
+    /// `retry_after_ms` is the agent's `Retry-After`, when it sent one
+    #[error("Rate limited by agent")]
+    RateLimitError { retry_after_ms: Option<u64> },
+
+    #[error("Authentication failed: {0}")]
+    AuthError(String),
 
     #[error("Voice processing error: {0}")]
     VoiceError(String),
@@ -16,6 +29,94 @@ pub enum SlovoError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Window error: {0}")]
+    WindowError(String),
+
+    #[error("Failed to parse agent response: {0}")]
+    ParseError(String),
+
+    #[error("Invalid link: {0}")]
+    InvalidLink(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
+
+impl SlovoError {
+    /// Agent error without a code, for failures detected on our side
+    pub fn agent(message: impl Into<String>) -> Self {
+        SlovoError::AgentError {
+            code: None,
+            message: message.into(),
+        }
+    }
+
+    /// Parse failure for a response body, quoting its start to show what came back
+    pub fn parse(error: impl std::fmt::Display, body: &[u8]) -> Self {
+        const SNIPPET_BYTES: usize = 200;
+        let snippet = String::from_utf8_lossy(&body[..body.len().min(SNIPPET_BYTES)]);
+        let ellipsis = if body.len() > SNIPPET_BYTES { "..." } else { "" };
+        SlovoError::ParseError(format!("{} (body: {}{})", error, snippet, ellipsis))
+    }
+
+    /// Stable variant name used as the `kind` tag when crossing the IPC boundary
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SlovoError::AgentConnection(_) => "AgentConnection",
+            SlovoError::Timeout(_) => "Timeout",
+            SlovoError::AgentError { .. } => "AgentError",
+            SlovoError::RateLimitError { .. } => "RateLimitError",
+            SlovoError::AuthError(_) => "AuthError",
+            SlovoError::VoiceError(_) => "VoiceError",
+            SlovoError::ConfigError(_) => "ConfigError",
+            SlovoError::WindowError(_) => "WindowError",
+            SlovoError::ParseError(_) => "ParseError",
+            SlovoError::InvalidLink(_) => "InvalidLink",
+            SlovoError::IoError(_) => "IoError",
+        }
+    }
+}
+
+/// Timeouts are kept apart so callers can tell a slow agent from one that is down
+impl From<reqwest::Error> for SlovoError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            SlovoError::Timeout(err.to_string())
+        } else {
+            SlovoError::AgentConnection(err.to_string())
+        }
+    }
+}
+
+impl From<tauri::Error> for SlovoError {
+    fn from(err: tauri::Error) -> Self {
+        SlovoError::WindowError(err.to_string())
+    }
+}
+
+/// Serializes as `{ "kind": "<Variant>", "message": "<display text>" }` so the
+/// frontend can branch on `kind` instead of parsing error strings. Agent errors
+/// also carry the agent's `code`, and rate limit errors `retry_after_ms` for a
+/// countdown.
+impl Serialize for SlovoError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let extra = matches!(
+            self,
+            SlovoError::AgentError { .. } | SlovoError::RateLimitError { .. }
+        );
+        let mut state = serializer.serialize_struct("SlovoError", 2 + usize::from(extra))?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        match self {
+            SlovoError::AgentError { code, .. } => state.serialize_field("code", code)?,
+            SlovoError::RateLimitError { retry_after_ms } => {
+                state.serialize_field("retry_after_ms", retry_after_ms)?
+            }
+            _ => {}
+        }
+        state.end()
+    }
+}
@@ -0,0 +1,199 @@
+//! Typed frontend events
+//!
+//! Event payloads and names live here so emitters and the frontend share one
+//! definition instead of scattered string literals.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::devices::DeviceKind;
+use crate::shutdown::{InFlight, InFlightRequests};
+use crate::tray::TrayManager;
+
+/// Connection state of the agent runtime as shown to the user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentStatus {
+    Connected,
+    /// Reachable but reporting itself unhealthy
+    Degraded,
+    #[default]
+    Disconnected,
+    /// Reachable but older than the minimum supported version
+    Incompatible,
+}
+
+impl AgentStatus {
+    /// Event emitted whenever the agent status changes
+    pub const EVENT: &'static str = "agent-status-changed";
+
+    /// Stable lowercase name, matching the serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentStatus::Connected => "connected",
+            AgentStatus::Degraded => "degraded",
+            AgentStatus::Disconnected => "disconnected",
+            AgentStatus::Incompatible => "incompatible",
+        }
+    }
+}
+
+impl std::fmt::Display for AgentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Managed record of the last agent status sent to the frontend
+#[derive(Default)]
+pub struct LatestAgentStatus(Mutex<AgentStatus>);
+
+impl LatestAgentStatus {
+    pub fn get(&self) -> AgentStatus {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Notify the frontend of a new agent status
+pub fn emit_agent_status(app: &AppHandle, status: AgentStatus) {
+    *app.state::<LatestAgentStatus>().0.lock().unwrap() = status;
+    if let Err(e) = app.emit(AgentStatus::EVENT, status) {
+        warn!("Failed to emit agent status: {}", e);
+    }
+}
+
+/// Payload of `request-started`
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestStarted {
+    pub request_id: String,
+}
+
+impl RequestStarted {
+    pub const EVENT: &'static str = "request-started";
+}
+
+/// Payload of `request-finished`
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestFinished {
+    pub request_id: String,
+    pub success: bool,
+}
+
+impl RequestFinished {
+    pub const EVENT: &'static str = "request-finished";
+}
+
+/// Payload of `agent-busy`, sent when the agent turns a message away for now
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentBusy {
+    /// How long the agent asked us to wait
+    pub retry_after_ms: u64,
+}
+
+impl AgentBusy {
+    pub const EVENT: &'static str = "agent-busy";
+}
+
+/// Payload of `audio-device-missing`, sent when a saved device is no longer connected
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceMissing {
+    pub kind: DeviceKind,
+    pub device: String,
+}
+
+impl AudioDeviceMissing {
+    pub const EVENT: &'static str = "audio-device-missing";
+}
+
+/// Payload of `navigate-to-conversation`, sent when a deep link opens a conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct NavigateToConversation {
+    pub conversation_id: String,
+    /// Text to place in the message input, from `slovo://new?prompt=`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl NavigateToConversation {
+    pub const EVENT: &'static str = "navigate-to-conversation";
+}
+
+/// Payload of `monitor-heartbeat`, sent by the health monitor on every check
+///
+/// Unlike the agent status this says the monitor itself is alive: when
+/// heartbeats stop arriving, the last status the UI saw is stale.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorHeartbeat {
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+impl MonitorHeartbeat {
+    pub const EVENT: &'static str = "monitor-heartbeat";
+}
+
+/// Payload of `conversation-titled`, sent when the agent names a new conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationTitled {
+    pub conversation_id: String,
+    pub title: String,
+}
+
+impl ConversationTitled {
+    pub const EVENT: &'static str = "conversation-titled";
+}
+
+/// Brackets an agent request with `request-started`/`request-finished` events
+///
+/// The finished event fires on drop, so an early return, error, or cancelled
+/// future still clears the tray's Processing state and the in-flight count
+/// that shutdown waits on. The request counts as
+/// failed unless [`RequestGuard::succeed`] was called.
+pub struct RequestGuard {
+    app: AppHandle,
+    request_id: String,
+    success: bool,
+    _in_flight: InFlight,
+}
+
+impl RequestGuard {
+    /// Emit `request-started` under a fresh request id
+    pub fn start(app: &AppHandle) -> Self {
+        let request_id = Uuid::new_v4().to_string();
+        let _ = app.emit(
+            RequestStarted::EVENT,
+            RequestStarted {
+                request_id: request_id.clone(),
+            },
+        );
+        app.state::<TrayManager>().request_started(app);
+        Self {
+            app: app.clone(),
+            request_id,
+            success: false,
+            _in_flight: app.state::<InFlightRequests>().track(),
+        }
+    }
+
+    /// Mark the request as successful
+    pub fn succeed(&mut self) {
+        self.success = true;
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        let _ = self.app.emit(
+            RequestFinished::EVENT,
+            RequestFinished {
+                request_id: self.request_id.clone(),
+                success: self.success,
+            },
+        );
+        self.app.state::<TrayManager>().request_finished(&self.app);
+    }
+}
@@ -0,0 +1,143 @@
+//! Conversation export module
+//!
+//! Renders saved conversations as Markdown (for notes) or JSON (lossless).
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::conversation::{Conversation, MessageRole};
+use crate::error::SlovoError;
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    /// Human-readable name shown in file dialogs
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    /// File extension for exported files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Render a conversation in the requested format, optionally dropping reasoning
+pub fn render(
+    conversation: &Conversation,
+    format: ExportFormat,
+    include_reasoning: bool,
+) -> Result<String, SlovoError> {
+    let stripped;
+    let conversation = if include_reasoning {
+        conversation
+    } else {
+        let mut copy = conversation.clone();
+        for message in &mut copy.messages {
+            message.reasoning = None;
+        }
+        stripped = copy;
+        &stripped
+    };
+
+    match format {
+        ExportFormat::Markdown => Ok(render_markdown(conversation)),
+        ExportFormat::Json => serde_json::to_string_pretty(conversation)
+            .map_err(|e| SlovoError::ConfigError(e.to_string())),
+    }
+}
+
+/// Default export file name: `slovo-<id>-<timestamp>.<ext>`
+pub fn default_file_name(conversation_id: &str, format: ExportFormat) -> String {
+    format!(
+        "slovo-{}-{}.{}",
+        conversation_id,
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    )
+}
+
+fn render_markdown(conversation: &Conversation) -> String {
+    let mut out = String::new();
+
+    out.push_str("---\n");
+    out.push_str(&format!("id: {}\n", conversation.id));
+    out.push_str(&format!("exported_at: {}\n", Utc::now().to_rfc3339()));
+    out.push_str(&format!("message_count: {}\n", conversation.messages.len()));
+    out.push_str("---\n\n");
+
+    let title = conversation.display_title();
+    if !title.is_empty() {
+        out.push_str(&format!("# {}\n\n", title));
+    }
+
+    for message in &conversation.messages {
+        let speaker = match message.role {
+            MessageRole::User => "You",
+            MessageRole::Assistant => "Slovo",
+        };
+        out.push_str(&format!("**{}** · {}\n\n", speaker, format_timestamp(message.timestamp)));
+
+        if let Some(reasoning) = message.reasoning.as_deref().filter(|r| !r.trim().is_empty()) {
+            out.push_str(&blockquote(&close_fences(reasoning)));
+            out.push_str("\n\n");
+        }
+
+        out.push_str(&close_fences(&message.content));
+        out.push_str("\n\n");
+    }
+
+    out
+}
+
+fn format_timestamp(ms: u64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(ms as i64)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+/// Close a code fence left open so it cannot swallow the rest of the document
+fn close_fences(text: &str) -> String {
+    let mut open: Option<&str> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            continue;
+        };
+        match open {
+            None => open = Some(marker),
+            Some(current) if current == marker => open = None,
+            Some(_) => {}
+        }
+    }
+
+    let text = text.trim_end();
+    match open {
+        Some(marker) => format!("{}\n{}", text, marker),
+        None => text.to_string(),
+    }
+}
+
+/// Prefix every line with `> ` so the text renders as a blockquote
+fn blockquote(text: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,134 @@
+//! Recent agent health checks
+//!
+//! The health monitor appends one snapshot per check, so a flapping agent
+//! shows up as a pattern rather than only its latest status.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Serializer};
+
+/// Snapshots kept, an hour's worth at the usual check interval
+pub const DEFAULT_MAX_LEN: usize = 360;
+/// Snapshots `get_health_history` returns when no count is given
+pub const DEFAULT_COUNT: usize = 60;
+
+/// Outcome of one health check
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthSnapshot {
+    /// Serialized as Unix time in milliseconds
+    #[serde(serialize_with = "unix_ms")]
+    pub timestamp: SystemTime,
+    /// Agent status the check resulted in, e.g. `"connected"`
+    pub status: String,
+    pub latency_ms: u64,
+}
+
+/// Bounded record of recent health checks, oldest first
+pub struct HealthHistory {
+    buffer: Mutex<VecDeque<HealthSnapshot>>,
+    max_len: usize,
+}
+
+impl HealthHistory {
+    pub fn new(max_len: usize) -> Self {
+        let max_len = max_len.max(1);
+        Self {
+            buffer: Mutex::new(VecDeque::with_capacity(max_len)),
+            max_len,
+        }
+    }
+
+    /// Append a snapshot, evicting the oldest once full
+    pub fn record(&self, snapshot: HealthSnapshot) {
+        let mut buffer = self.buffer.lock().unwrap();
+        while buffer.len() >= self.max_len {
+            buffer.pop_front();
+        }
+        buffer.push_back(snapshot);
+    }
+
+    /// Up to `count` of the most recent snapshots, oldest first
+    pub fn recent(&self, count: usize) -> Vec<HealthSnapshot> {
+        let buffer = self.buffer.lock().unwrap();
+        buffer.iter().skip(buffer.len().saturating_sub(count)).cloned().collect()
+    }
+
+    /// Share of recorded checks that reached the agent, from 0 to 100
+    ///
+    /// `None` until the first check has been recorded.
+    pub fn uptime_percentage(&self) -> Option<f64> {
+        let buffer = self.buffer.lock().unwrap();
+        if buffer.is_empty() {
+            return None;
+        }
+        let up = buffer.iter().filter(|s| s.status != "disconnected").count();
+        Some(up as f64 * 100.0 / buffer.len() as f64)
+    }
+}
+
+impl Default for HealthHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LEN)
+    }
+}
+
+fn unix_ms<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    let ms = time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default();
+    serializer.serialize_u64(ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(status: &str, latency_ms: u64) -> HealthSnapshot {
+        HealthSnapshot {
+            timestamp: SystemTime::now(),
+            status: status.to_string(),
+            latency_ms,
+        }
+    }
+
+    fn latencies(snapshots: &[HealthSnapshot]) -> Vec<u64> {
+        snapshots.iter().map(|s| s.latency_ms).collect()
+    }
+
+    #[test]
+    fn full_history_evicts_the_oldest_snapshot() {
+        let history = HealthHistory::new(3);
+        for latency_ms in 1..=5 {
+            history.record(snapshot("connected", latency_ms));
+        }
+
+        assert_eq!(latencies(&history.recent(10)), [3, 4, 5]);
+        assert_eq!(latencies(&history.recent(2)), [4, 5]);
+    }
+
+    #[test]
+    fn uptime_covers_only_retained_snapshots() {
+        let history = HealthHistory::new(4);
+        assert_eq!(history.uptime_percentage(), None);
+
+        history.record(snapshot("disconnected", 0));
+        history.record(snapshot("disconnected", 0));
+        history.record(snapshot("connected", 10));
+        history.record(snapshot("degraded", 10));
+        assert_eq!(history.uptime_percentage(), Some(50.0));
+
+        // Both disconnected checks age out
+        history.record(snapshot("connected", 10));
+        history.record(snapshot("connected", 10));
+        assert_eq!(history.uptime_percentage(), Some(100.0));
+    }
+
+    #[test]
+    fn zero_capacity_still_keeps_the_latest_snapshot() {
+        let history = HealthHistory::new(0);
+        history.record(snapshot("connected", 1));
+        history.record(snapshot("connected", 2));
+
+        assert_eq!(latencies(&history.recent(DEFAULT_COUNT)), [2]);
+    }
+}
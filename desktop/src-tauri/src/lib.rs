@@ -3,8 +3,44 @@
 //! This library provides the core functionality for the Slovo desktop application.
 
 pub mod agent;
+pub mod agent_process;
+pub mod attachment;
+pub mod audio;
+pub mod audit_log;
+pub mod cancellation;
+pub mod clipboard;
 pub mod commands;
+pub mod config;
+pub mod conversation;
+pub mod credentials;
+pub mod deep_link;
+pub mod devices;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod health_history;
+pub mod logging;
+pub mod metrics;
+pub mod middleware;
+pub mod notify;
+pub mod permissions;
+pub mod queue;
+pub mod rate_limit;
+pub mod recording;
+pub mod screenshot;
+pub mod search;
+pub mod shutdown;
+pub mod speech;
+pub mod state;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
+pub mod theme;
 pub mod tray;
+pub mod vad;
+pub mod wake_word;
+pub mod window_state;
+pub mod ws;
+#[cfg(feature = "ws-transport")]
+pub mod ws_transport;
 
 pub use error::SlovoError;
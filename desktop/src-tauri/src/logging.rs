@@ -0,0 +1,206 @@
+//! Logging setup
+//!
+//! Writes daily-rotated log files to the app log directory so packaged builds
+//! (which have no console) still leave a trail for bug reports. Debug builds
+//! additionally log to stdout, and the `opentelemetry` feature exports spans.
+
+use std::fmt::{self as std_fmt, Display, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use tracing::field::{Field, Visit};
+use tracing::{warn, Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::{fmt, util::SubscriberInitExt, EnvFilter};
+
+/// Log files are named `slovo.<date>.log`
+pub const LOG_FILE_PREFIX: &str = "slovo";
+pub const LOG_FILE_SUFFIX: &str = "log";
+
+/// Message of the most recent error-level log event
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// The most recent error logged anywhere in the app, for diagnostics
+pub fn last_error() -> Option<String> {
+    LAST_ERROR.lock().unwrap().clone()
+}
+
+/// User or agent text as it should appear in a log line
+///
+/// Shows only the length unless `log_message_content` is enabled.
+pub struct MessageContent<'a> {
+    text: &'a str,
+    show: bool,
+}
+
+impl<'a> MessageContent<'a> {
+    pub fn new(text: &'a str, show: bool) -> Self {
+        Self { text, show }
+    }
+}
+
+impl Display for MessageContent<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std_fmt::Result {
+        if self.show {
+            f.write_str(self.text)
+        } else {
+            write!(f, "<{} chars>", self.text.chars().count())
+        }
+    }
+}
+
+/// Path of the log file being written today
+pub fn current_log_file(log_dir: &Path) -> PathBuf {
+    // The daily appender names files by UTC date
+    log_dir.join(format!(
+        "{}.{}.{}",
+        LOG_FILE_PREFIX,
+        Utc::now().format("%Y-%m-%d"),
+        LOG_FILE_SUFFIX
+    ))
+}
+
+/// Most bytes read from the end of a log file when tailing it
+const MAX_TAIL_BYTES: u64 = 512 * 1024;
+
+/// Last `lines` lines of a log file, with bearer tokens redacted
+///
+/// A missing file yields no lines rather than an error.
+pub fn tail(path: &Path, lines: usize) -> std::io::Result<Vec<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(MAX_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut all: Vec<&str> = text.lines().collect();
+    if start > 0 && !all.is_empty() {
+        // The first line was cut by the byte window
+        all.remove(0);
+    }
+    let skip = all.len().saturating_sub(lines);
+    Ok(all[skip..].iter().map(|line| redact_bearer(line)).collect())
+}
+
+/// Replace anything following `Bearer ` with a placeholder
+fn redact_bearer(line: &str) -> String {
+    const MARKER: &str = "bearer ";
+    let lower = line.to_ascii_lowercase();
+    let mut out = String::with_capacity(line.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find(MARKER) {
+        let token_start = pos + found + MARKER.len();
+        let token_end = line[token_start..]
+            .find(|c: char| c.is_whitespace() || c == '"' || c == '\'')
+            .map_or(line.len(), |i| token_start + i);
+        out.push_str(&line[pos..token_start]);
+        out.push_str("[REDACTED]");
+        pos = token_end;
+    }
+    out.push_str(&line[pos..]);
+    out
+}
+
+/// Remembers the message of each error-level event in [`LAST_ERROR`]
+struct LastErrorLayer;
+
+impl<S: Subscriber> Layer<S> for LastErrorLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let mut visitor = MessageVisitor(None);
+        event.record(&mut visitor);
+        if let Some(message) = visitor.0 {
+            *LAST_ERROR.lock().unwrap() = Some(message);
+        }
+    }
+}
+
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Keeps the background log writer (and span exporter) alive; held in managed state
+pub struct LogGuard {
+    // Fields drop in order, so the exporter can still log its shutdown to the file
+    #[cfg(feature = "opentelemetry")]
+    _telemetry: Option<crate::telemetry::TelemetryGuard>,
+    _file: Option<WorkerGuard>,
+}
+
+/// Install the global tracing subscriber
+///
+/// Falls back to stdout-only logging if the log directory cannot be used.
+pub fn init(log_dir: &Path, max_files: usize) -> LogGuard {
+    let filter = || EnvFilter::new(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()));
+
+    #[cfg(feature = "opentelemetry")]
+    let (otel, telemetry, otel_error) = match crate::telemetry::layer() {
+        Ok((layer, guard)) => (Some(layer), Some(guard), None),
+        Err(e) => (None, None, Some(e)),
+    };
+    #[cfg(not(feature = "opentelemetry"))]
+    let otel = None::<tracing_subscriber::layer::Identity>;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(max_files.max(1))
+        .build(log_dir);
+
+    let file = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let stdout = cfg!(debug_assertions).then(fmt::layer);
+
+            tracing_subscriber::registry()
+                .with(otel)
+                .with(filter())
+                .with(LastErrorLayer)
+                .with(stdout)
+                .with(fmt::layer().with_ansi(false).with_writer(writer))
+                .init();
+            Some(guard)
+        }
+        Err(e) => {
+            tracing_subscriber::registry()
+                .with(otel)
+                .with(filter())
+                .with(LastErrorLayer)
+                .with(fmt::layer())
+                .init();
+            warn!("File logging unavailable, logging to stdout only: {}", e);
+            None
+        }
+    };
+
+    #[cfg(feature = "opentelemetry")]
+    if let Some(e) = otel_error {
+        warn!("Span export unavailable: {}", e);
+    }
+
+    LogGuard {
+        #[cfg(feature = "opentelemetry")]
+        _telemetry: telemetry,
+        _file: file,
+    }
+}
@@ -1,25 +1,30 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod agent;
-mod commands;
-mod error;
-mod tray;
+use std::sync::Arc;
+use std::time::Duration;
 
+use slovo_lib::audit_log::AuditLogger;
+use slovo_lib::cancellation::CancellationRegistry;
+use slovo_lib::config::{AgentConfig, ProfiledConfig};
+use slovo_lib::conversation::ConversationManager;
+use slovo_lib::agent::{init_default_client, AgentClient, AgentClientBuilder};
+use slovo_lib::agent_process::AgentProcessState;
+use slovo_lib::metrics::{self, Metrics};
+use slovo_lib::permissions::MicrophonePermission;
+use slovo_lib::queue::MessageQueue;
+use slovo_lib::rate_limit::RateLimiter;
+use slovo_lib::recording::RecordingState;
+use slovo_lib::speech::SpeechPlayback;
+use slovo_lib::state::AppState;
+use slovo_lib::wake_word::WakeWordDetector;
+use slovo_lib::{
+    agent, commands, config, credentials, deep_link, events, logging, shutdown,
+    theme, tray, window_state, ws,
+};
 use tauri::Manager;
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{info, warn};
 
 fn main() {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    info!("Starting Slovo Voice Assistant");
-
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
@@ -28,9 +33,105 @@ fn main() {
             Some(vec!["--autostart"]),
         ))
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(ws::AgentStreamState::default())
+        .manage(AgentProcessState::default())
+        .manage(tray::TrayManager::default())
+        .manage(SpeechPlayback::default())
+        .manage(RecordingState::default())
+        .manage(commands::LiveTranscription::default())
+        .manage(WakeWordDetector::default())
+        .manage(events::LatestAgentStatus::default())
+        .manage(agent::HealthMonitor::default())
+        .manage(shutdown::InFlightRequests::default())
+        .manage(shutdown::ShutdownState::default())
+        .manage(agent::AgentWarmup::default())
+        .manage(window_state::WindowModeState::default())
+        .manage(MicrophonePermission::default())
+        .manage(theme::LastTheme::default())
         .setup(|app| {
             let handle = app.handle().clone();
-            
+
+            let (profiles, config_error) = match ProfiledConfig::load(&handle) {
+                Ok(profiles) => (profiles, None),
+                Err(e) => (ProfiledConfig::default(), Some(e)),
+            };
+            let mut config = profiles.active();
+
+            // Logging needs the resolved log directory, so it starts once the app exists
+            app.manage(logging::init(&app.path().app_log_dir()?, config.log_max_files));
+            info!("Starting Slovo Voice Assistant");
+            if let Some(e) = config_error {
+                warn!("Failed to load config, using defaults: {}", e);
+            }
+
+            info!("Using config profile {}", profiles.active_profile);
+            config.agent.auth_token = credentials::load_auth_token(&profiles.active_profile)
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load agent auth token: {}", e);
+                    None
+                });
+            let conversations_dir = app.path().app_data_dir()?.join("conversations");
+            app.manage(ConversationManager::load(conversations_dir)?);
+            app.manage(MessageQueue::load(app.path().app_data_dir()?.join("queue.json")));
+
+            let audit = Arc::new(AuditLogger::from_config(&app.path().app_data_dir()?, &config));
+            app.manage(audit.clone());
+
+            let client = AgentClientBuilder::from_config(&config.agent)
+                .audit_logger(audit)
+                .build()
+                .unwrap_or_else(|e| {
+                    warn!("Invalid agent config, using defaults: {}", e);
+                    AgentClient::new()
+                });
+            let state = AppState::new(client, config.clone());
+            #[cfg(feature = "ws-transport")]
+            let state = state.with_transport(Arc::new(
+                slovo_lib::ws_transport::WebSocketAgentClient::connect(handle.clone(), &config.agent),
+            ));
+            app.manage(state);
+            if init_default_client(config.agent.clone()).is_err() {
+                let _ = init_default_client(AgentConfig::default());
+            }
+            app.manage(RateLimiter::from_config(&config.agent));
+            app.manage(Metrics::new()?);
+            if config.metrics_enabled {
+                let port = config.metrics_port;
+                let handle_clone = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    metrics::serve(handle_clone, port).await;
+                });
+            }
+            app.manage(CancellationRegistry::new(Duration::from_secs(
+                config.cancellation_ttl_secs,
+            )));
+            app.manage(commands::RequestDeduplicator::new(Duration::from_millis(
+                config.dedup_window_ms,
+            )));
+
+            let auto_start = config.agent.auto_start.then(|| config.agent.clone());
+            match config::watch(&handle) {
+                Ok(watcher) => {
+                    app.manage(watcher);
+                }
+                Err(e) => warn!("Config hot-reload disabled: {}", e),
+            }
+
+            if let Some(agent_config) = auto_start {
+                let handle_clone = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    let processes = handle_clone.state::<AgentProcessState>();
+                    if let Err(e) = processes.start(&agent_config).await {
+                        warn!("Failed to auto-start agent runtime: {}", e);
+                    }
+                });
+            }
+
+            tray::setup_tray(&handle)?;
+            tauri::async_runtime::spawn(tray::watch_processing(handle.clone()));
+
             // Check if launched with autostart flag
             let args: Vec<String> = std::env::args().collect();
             let is_autostart = args.contains(&"--autostart".to_string());
@@ -48,34 +149,149 @@ fn main() {
                     let _ = window.show();
                     let _ = window.set_focus();
                 }
+                window_state::restore(&window);
+                let (always_on_top, opacity) = {
+                    let config = AppState::of(&handle).config.read().unwrap();
+                    (config.always_on_top, config.window_opacity)
+                };
+                if always_on_top {
+                    let _ = window.set_always_on_top(true);
+                }
+                window_state::apply_opacity(opacity);
+                window_state::track(&window);
 
-                // Handle window close - minimize to tray instead
-                let window_clone = window.clone();
-                window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                        // Prevent actual close, hide to tray instead
+                // Handle window close - hide to tray or quit, per the user's setting
+                let handle_clone = handle.clone();
+                window.on_window_event(move |event| match event {
+                    tauri::WindowEvent::CloseRequested { api, .. } => {
+                        // Quitting also goes through here so the agent is stopped cleanly first
                         api.prevent_close();
-                        let _ = window_clone.hide();
-                        info!("Window hidden to tray");
+                        tray::handle_close_requested(&handle_clone);
                     }
+                    // Linux polls `gsettings` instead, which GTK's theme can disagree with
+                    #[cfg(any(target_os = "macos", target_os = "windows"))]
+                    tauri::WindowEvent::ThemeChanged(theme) => {
+                        theme::report(&handle_clone, (*theme).into());
+                    }
+                    _ => {}
                 });
             }
 
             // Spawn agent health check task
-            let handle_clone = handle.clone();
-            tauri::async_runtime::spawn(async move {
-                agent::monitor_agent_health(handle_clone).await;
-            });
+            let monitor = app.state::<agent::HealthMonitor>();
+            let interval =
+                AppState::of(&handle).config.read().unwrap().agent.health_check_interval_secs;
+            if let Err(e) = monitor.set_interval(interval) {
+                warn!("Ignoring saved health check interval: {}", e);
+            }
+            monitor.start(&handle);
+
+            // macOS and Windows report theme changes through the window instead
+            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+            tauri::async_runtime::spawn(theme::watch_theme(handle.clone()));
+            #[cfg(unix)]
+            tauri::async_runtime::spawn(shutdown::watch_signals(handle.clone()));
+
+            deep_link::handle_launch_args(&handle);
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::process_voice_input,
+            commands::transcribe_voice_input,
+            commands::speak,
+            commands::speak_text,
+            commands::stop_speaking,
+            commands::is_speaking,
+            commands::list_audio_devices,
+            commands::set_audio_device,
+            commands::set_audio_devices,
+            commands::enable_wake_word,
+            commands::disable_wake_word,
+            commands::start_recording,
+            commands::stop_recording,
+            commands::check_permissions,
+            commands::request_microphone_permission,
+            commands::stop_recording_and_transcribe,
+            commands::start_live_transcription,
+            commands::stop_live_transcription,
             commands::check_agent_status,
+            commands::get_agent_info,
+            commands::get_health_history,
+            commands::set_health_check_interval,
+            commands::set_request_timeout,
+            commands::get_capabilities,
+            commands::get_model_info,
+            commands::list_profiles,
+            commands::create_profile,
+            commands::switch_profile,
+            commands::warm_up_agent,
+            commands::ping_agent,
             commands::send_message_to_agent,
+            commands::validate_message,
+            commands::get_queued_messages,
+            commands::clear_queue,
+            commands::send_messages,
+            commands::send_batch_to_agent,
+            commands::send_message_with_attachments,
+            commands::regenerate_last_response,
+            commands::auto_title_conversation,
+            commands::capture_screenshot_for_vision,
+            commands::attach_file_to_message,
+            commands::cancel_batch,
+            commands::cancel_message,
+            commands::new_conversation,
+            commands::fork_conversation,
+            commands::current_conversation_id,
+            commands::list_conversations,
+            commands::search_conversations,
+            commands::delete_conversation,
+            commands::get_conversation_stats,
+            commands::set_system_prompt,
+            commands::set_conversation_persona,
+            commands::render_conversation,
+            commands::export_conversation,
+            commands::stream_message_to_agent,
+            commands::cancel_stream,
+            commands::resume_stream,
+            commands::close_agent_stream,
+            commands::set_auth_token,
+            commands::start_agent,
+            commands::stop_agent,
+            commands::restart_agent,
+            commands::log_path,
+            commands::recent_logs,
+            commands::get_diagnostics,
+            commands::set_close_to_tray,
+            commands::reset_tray_state,
+            commands::pending_requests,
+            commands::preview_tray_state,
+            commands::toggle_always_on_top,
+            commands::set_always_on_top,
+            commands::set_window_opacity,
+            commands::enter_mini_mode,
+            commands::exit_mini_mode,
+            commands::get_current_theme,
+            commands::read_clipboard,
+            commands::write_clipboard,
+            commands::paste_clipboard_to_chat,
             commands::show_window,
             commands::hide_window,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { code, api, .. } = &event {
+                if shutdown::exit_requested(app, *code) {
+                    api.prevent_exit();
+                }
+            }
+            // macOS hands deep links to the running app rather than as arguments
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = event {
+                for url in urls {
+                    deep_link::open(app, url.to_string());
+                }
+            }
+        });
 }
@@ -0,0 +1,274 @@
+//! Prometheus metrics
+//!
+//! Counts chat traffic and agent health, and serves them in the Prometheus
+//! text format at `GET /metrics` on a localhost-only port.
+
+use std::time::Duration;
+
+use prometheus::{
+    exponential_buckets, Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+use crate::agent::TokenUsage;
+use crate::conversation::ConversationManager;
+use crate::error::SlovoError;
+
+/// Largest request head we read before answering
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Managed collection of app metrics
+pub struct Metrics {
+    registry: Registry,
+    messages_total: IntCounter,
+    message_latency: Histogram,
+    agent_health: IntGauge,
+    active_conversations: IntGauge,
+    tokens_used: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self, SlovoError> {
+        let metric_error = |e: prometheus::Error| SlovoError::ConfigError(format!("Invalid metric: {}", e));
+
+        let messages_total = IntCounter::new("slovo_messages_total", "Chat messages answered by the agent")
+            .map_err(metric_error)?;
+        let buckets = exponential_buckets(0.1, 2.0, 10).map_err(metric_error)?;
+        let message_latency = Histogram::with_opts(
+            HistogramOpts::new("slovo_message_latency_seconds", "Time for the agent to answer a chat message")
+                .buckets(buckets),
+        )
+        .map_err(metric_error)?;
+        let agent_health = IntGauge::new(
+            "slovo_agent_health_status",
+            "Whether the agent runtime is connected (1) or not (0)",
+        )
+        .map_err(metric_error)?;
+        let active_conversations = IntGauge::new("slovo_active_conversations", "Saved conversations")
+            .map_err(metric_error)?;
+        let tokens_used = IntCounterVec::new(
+            Opts::new("slovo_tokens_used_total", "Tokens reported by the agent"),
+            &["conversation_id"],
+        )
+        .map_err(metric_error)?;
+
+        let registry = Registry::new();
+        registry.register(Box::new(messages_total.clone())).map_err(metric_error)?;
+        registry.register(Box::new(message_latency.clone())).map_err(metric_error)?;
+        registry.register(Box::new(agent_health.clone())).map_err(metric_error)?;
+        registry.register(Box::new(active_conversations.clone())).map_err(metric_error)?;
+        registry.register(Box::new(tokens_used.clone())).map_err(metric_error)?;
+
+        Ok(Self {
+            registry,
+            messages_total,
+            message_latency,
+            agent_health,
+            active_conversations,
+            tokens_used,
+        })
+    }
+
+    /// Count an answered chat message
+    pub fn record_message(&self, conversation_id: &str, latency: Duration, usage: Option<TokenUsage>) {
+        self.messages_total.inc();
+        self.message_latency.observe(latency.as_secs_f64());
+        if let Some(usage) = usage {
+            self.tokens_used
+                .with_label_values(&[conversation_id])
+                .inc_by(u64::from(usage.total_tokens));
+        }
+    }
+
+    pub fn set_agent_connected(&self, connected: bool) {
+        self.agent_health.set(i64::from(connected));
+    }
+
+    /// Render every metric in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut out) {
+            warn!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(out).unwrap_or_default()
+    }
+}
+
+/// Serve `GET /metrics` on `127.0.0.1:port` until the app exits
+pub async fn serve(app: AppHandle, port: u16) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to start metrics server on port {}: {}", port, e);
+            return;
+        }
+    };
+    info!("Serving metrics at http://127.0.0.1:{}/metrics", port);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let conversations = app.state::<ConversationManager>().count();
+                    let metrics = app.state::<Metrics>();
+                    if let Err(e) = handle_connection(&metrics, conversations, stream).await {
+                        warn!("Metrics request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+/// Answer one request, reporting `conversations` as the active conversation count
+async fn handle_connection(
+    metrics: &Metrics,
+    conversations: usize,
+    mut stream: TcpStream,
+) -> std::io::Result<()> {
+    let mut buf = vec![0; MAX_REQUEST_BYTES];
+    let mut len = 0;
+    // Only the request line matters; stop once the head is complete
+    while len < buf.len() && !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut buf[len..]).await? {
+            0 => break,
+            n => len += n,
+        }
+    }
+
+    let head = String::from_utf8_lossy(&buf[..len]);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (status, content_type, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            metrics.active_conversations.set(conversations as i64);
+            ("200 OK", TextEncoder::new().format_type().to_string(), metrics.render())
+        }
+        _ => ("404 Not Found", "text/plain".to_string(), "Not Found\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Sample values keyed by metric name with labels, e.g. `name{label="x"}`
+    fn parse(text: &str) -> HashMap<String, f64> {
+        text.lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (name, value) = line.rsplit_once(' ').unwrap();
+                (name.to_string(), value.parse().unwrap())
+            })
+            .collect()
+    }
+
+    fn usage(total_tokens: u32) -> Option<TokenUsage> {
+        Some(TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: total_tokens,
+            total_tokens,
+        })
+    }
+
+    /// Serve one connection with `handle_connection` and return the raw response
+    async fn exchange(metrics: &Metrics, request: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = async {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(metrics, 4, stream).await.unwrap();
+        };
+        tokio::join!(client, server).0
+    }
+
+    /// Status line, headers and body of a response
+    fn split(response: &str) -> (&str, HashMap<&str, &str>, &str) {
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let mut lines = head.split("\r\n");
+        let status = lines.next().unwrap();
+        let headers = lines.map(|line| line.split_once(": ").unwrap()).collect();
+        (status, headers, body)
+    }
+
+    #[tokio::test]
+    async fn get_metrics_serves_prometheus_text() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record_message("conv-1", Duration::from_millis(150), usage(12));
+
+        let response = exchange(&metrics, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        let (status, headers, body) = split(&response);
+        let samples = parse(body);
+
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(headers["Content-Type"].starts_with("text/plain; version=0.0.4"));
+        assert_eq!(headers["Content-Length"], body.len().to_string());
+        assert_eq!(samples["slovo_messages_total"], 1.0);
+        assert_eq!(samples["slovo_active_conversations"], 4.0);
+    }
+
+    #[tokio::test]
+    async fn other_paths_are_not_found() {
+        let metrics = Metrics::new().unwrap();
+
+        let requests = ["GET /health HTTP/1.1\r\n\r\n", "POST /metrics HTTP/1.1\r\n\r\n", "\r\n\r\n"];
+        for request in requests {
+            let response = exchange(&metrics, request).await;
+            let (status, headers, body) = split(&response);
+
+            assert_eq!(status, "HTTP/1.1 404 Not Found", "{:?}", request);
+            assert_eq!(headers["Content-Length"], body.len().to_string());
+            assert_eq!(body, "Not Found\n");
+        }
+    }
+
+    #[test]
+    fn render_is_valid_prometheus_text() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record_message("conv-1", Duration::from_millis(150), usage(12));
+        metrics.record_message("conv-1", Duration::from_millis(450), usage(30));
+        metrics.record_message("conv-2", Duration::from_secs(200), None);
+        metrics.set_agent_connected(true);
+
+        let text = metrics.render();
+        let samples = parse(&text);
+
+        assert_eq!(samples["slovo_messages_total"], 3.0);
+        assert_eq!(samples["slovo_agent_health_status"], 1.0);
+        assert_eq!(samples["slovo_tokens_used_total{conversation_id=\"conv-1\"}"], 42.0);
+        assert!(!samples.contains_key("slovo_tokens_used_total{conversation_id=\"conv-2\"}"));
+        assert_eq!(samples["slovo_message_latency_seconds_count"], 3.0);
+        assert!((samples["slovo_message_latency_seconds_sum"] - 200.6).abs() < 1e-9);
+        assert_eq!(samples["slovo_message_latency_seconds_bucket{le=\"0.2\"}"], 1.0);
+        assert_eq!(samples["slovo_message_latency_seconds_bucket{le=\"0.8\"}"], 2.0);
+        assert_eq!(samples["slovo_message_latency_seconds_bucket{le=\"+Inf\"}"], 3.0);
+        let names = ["slovo_messages_total", "slovo_message_latency_seconds", "slovo_agent_health_status"];
+        for name in names {
+            assert!(text.contains(&format!("# TYPE {} ", name)), "no TYPE line for {}", name);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Pluggable hooks around every HTTP request the agent client makes
+//!
+//! Middleware is added through [`AgentClientBuilder::middleware`] and runs in
+//! the order it was added: `before_send` just before a request leaves, and
+//! `after_response` once its response headers arrive. Integrations such as a
+//! custom enterprise auth scheme can hook in here without touching the client.
+//!
+//! [`AgentClientBuilder::middleware`]: crate::agent::AgentClientBuilder::middleware
+
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{Method, RequestBuilder, Response};
+use tracing::info;
+
+/// Hook applied to each request the agent client sends
+pub trait RequestMiddleware: Send + Sync {
+    /// Adjust the request before it is sent
+    fn before_send(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+    }
+
+    /// Inspect the response before the client handles it
+    fn after_response(&self, _response: &Response) {}
+
+    /// Extra attempts for a request that failed to reach the agent
+    ///
+    /// The client uses the largest value any middleware asks for, and only
+    /// retries requests that are safe to repeat.
+    fn max_retries(&self) -> u32 {
+        0
+    }
+}
+
+/// Send a fixed `Authorization` header value, such as `"Negotiate <ticket>"`
+///
+/// Replaces the bearer token from the config, which should then be left unset.
+pub struct AuthHeaderMiddleware(pub String);
+
+impl RequestMiddleware for AuthHeaderMiddleware {
+    fn before_send(&self, request: RequestBuilder) -> RequestBuilder {
+        match HeaderValue::from_str(&self.0) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                request.header(AUTHORIZATION, value)
+            }
+            Err(_) => request,
+        }
+    }
+}
+
+/// Log the URL, status and size of every agent response
+pub struct LoggingMiddleware;
+
+impl RequestMiddleware for LoggingMiddleware {
+    fn after_response(&self, response: &Response) {
+        info!(
+            url = %response.url(),
+            status = response.status().as_u16(),
+            content_length = response.content_length(),
+            "agent HTTP response"
+        );
+    }
+}
+
+/// Retry requests that could not reach the agent, up to `max_retries` more times
+///
+/// Requests that would not be safe to repeat, such as a chat POST without an
+/// idempotency key, are still sent only once.
+pub struct RetryMiddleware {
+    pub max_retries: u32,
+}
+
+impl RequestMiddleware for RetryMiddleware {
+    fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+}
+
+/// Whether a request may be sent again without risking a duplicate effect
+pub(crate) fn is_repeatable(request: &reqwest::Request, idempotency_header: &str) -> bool {
+    matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    ) || request.headers().contains_key(idempotency_header)
+}
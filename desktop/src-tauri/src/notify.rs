@@ -0,0 +1,71 @@
+//! Desktop notifications
+//!
+//! All notifications go through [`notify`] so the `notifications_enabled`
+//! setting is honoured in one place. Agent connectivity notices are debounced:
+//! a disconnect is only reported once the agent has stayed down for the
+//! configured grace period, and a reconnect only if that disconnect was shown.
+
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use tracing::warn;
+
+use crate::events::AgentStatus;
+use crate::state::AppState;
+
+/// Show a desktop notification unless the user has turned them off
+pub fn notify(app: &AppHandle, title: &str, body: &str) {
+    if !AppState::of(app).config.read().unwrap().notifications_enabled {
+        return;
+    }
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// Debounces connectivity notifications for a flapping agent
+#[derive(Debug, Default)]
+pub struct ConnectivityNotifier {
+    /// Whether the agent has been reachable at all; a runtime that was never
+    /// started is not an outage
+    seen_up: bool,
+    /// When the current outage started, if the agent is down
+    down_since: Option<Instant>,
+    /// Whether the user has been told about the current outage
+    notified_down: bool,
+}
+
+impl ConnectivityNotifier {
+    /// Feed the latest health-check status, notifying when a debounced transition happens
+    pub fn update(&mut self, app: &AppHandle, status: AgentStatus) {
+        if status == AgentStatus::Disconnected {
+            if !self.seen_up {
+                return;
+            }
+            let down_since = *self.down_since.get_or_insert_with(Instant::now);
+            if !self.notified_down && down_since.elapsed() >= grace_period(app) {
+                notify(
+                    app,
+                    "Slovo agent disconnected",
+                    "The agent runtime is not responding. Slovo will reconnect automatically.",
+                );
+                self.notified_down = true;
+            }
+            return;
+        }
+
+        if self.notified_down {
+            notify(app, "Slovo agent reconnected", "The agent runtime is available again.");
+        }
+        self.seen_up = true;
+        self.down_since = None;
+        self.notified_down = false;
+    }
+}
+
+fn grace_period(app: &AppHandle) -> Duration {
+    let secs = AppState::of(app).config.read().unwrap().disconnect_notify_after_secs;
+    Duration::from_secs(secs)
+}
@@ -0,0 +1,145 @@
+//! Operating system permissions the app depends on
+//!
+//! Only macOS gates the microphone behind a prompt; Windows and Linux
+//! grant it to every desktop app, so there it is always reported granted.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Whether the user has allowed something
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    /// Not asked yet; requesting it will prompt the user
+    NotDetermined,
+}
+
+impl From<tauri::plugin::PermissionState> for PermissionState {
+    fn from(state: tauri::plugin::PermissionState) -> Self {
+        match state {
+            tauri::plugin::PermissionState::Granted => PermissionState::Granted,
+            tauri::plugin::PermissionState::Denied => PermissionState::Denied,
+            _ => PermissionState::NotDetermined,
+        }
+    }
+}
+
+/// Current state of every permission the app uses
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PermissionStatus {
+    pub microphone: PermissionState,
+    pub notifications: PermissionState,
+}
+
+/// Managed record of the last known microphone permission
+#[derive(Default)]
+pub struct MicrophonePermission(Mutex<Option<PermissionState>>);
+
+impl MicrophonePermission {
+    /// Last known state, querying the OS the first time
+    pub fn get(&self) -> PermissionState {
+        *self.0.lock().unwrap().get_or_insert_with(platform::microphone)
+    }
+
+    /// Query the OS again, since the user may have changed it in system settings
+    pub fn refresh(&self) -> PermissionState {
+        let state = platform::microphone();
+        *self.0.lock().unwrap() = Some(state);
+        state
+    }
+
+    /// Ask for microphone access, prompting the user if they have not been asked yet
+    pub async fn request(&self) -> PermissionState {
+        let state = match self.refresh() {
+            PermissionState::NotDetermined => platform::request_microphone().await,
+            state => state,
+        };
+        *self.0.lock().unwrap() = Some(state);
+        state
+    }
+}
+
+/// Query every permission from the OS
+pub fn check(app: &AppHandle) -> PermissionStatus {
+    let notifications = app
+        .notification()
+        .permission_state()
+        .map(PermissionState::from)
+        .unwrap_or(PermissionState::NotDetermined);
+    PermissionStatus {
+        microphone: app.state::<MicrophonePermission>().refresh(),
+        notifications,
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::Mutex;
+
+    use block2::RcBlock;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+
+    use super::PermissionState;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: &'static AnyObject;
+    }
+
+    // AVAuthorizationStatus values
+    const NOT_DETERMINED: isize = 0;
+    const AUTHORIZED: isize = 3;
+
+    pub fn microphone() -> PermissionState {
+        let status: isize = unsafe {
+            msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+        };
+        match status {
+            NOT_DETERMINED => PermissionState::NotDetermined,
+            AUTHORIZED => PermissionState::Granted,
+            // Denied by the user, or restricted by parental controls or MDM
+            _ => PermissionState::Denied,
+        }
+    }
+
+    /// Show the system prompt and wait for the user's answer
+    pub async fn request_microphone() -> PermissionState {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = Mutex::new(Some(tx));
+        let handler = RcBlock::new(move |granted: Bool| {
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(granted.as_bool());
+            }
+        });
+        unsafe {
+            let _: () = msg_send![
+                class!(AVCaptureDevice),
+                requestAccessForMediaType: AVMediaTypeAudio,
+                completionHandler: &*handler
+            ];
+        }
+        match rx.await {
+            Ok(true) => PermissionState::Granted,
+            _ => PermissionState::Denied,
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::PermissionState;
+
+    pub fn microphone() -> PermissionState {
+        PermissionState::Granted
+    }
+
+    pub async fn request_microphone() -> PermissionState {
+        PermissionState::Granted
+    }
+}
@@ -0,0 +1,151 @@
+//! Offline message queue
+//!
+//! Messages sent while the agent is unreachable are kept here (and persisted
+//! as `queue.json` in the app data directory) until the health monitor sees
+//! the agent come back, then flushed in order.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::conversation::now_ms;
+use crate::error::SlovoError;
+
+/// Most messages held before the oldest are dropped
+const MAX_QUEUED_MESSAGES: usize = 50;
+
+/// A message waiting for the agent to reconnect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    pub id: String,
+    pub message: String,
+    pub conversation_id: Option<String>,
+    /// Key of the original send, reused on flush so the agent can dedupe it
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Unix time in milliseconds
+    pub queued_at: u64,
+}
+
+/// Persistent FIFO of unsent messages
+pub struct MessageQueue {
+    path: PathBuf,
+    messages: Mutex<VecDeque<QueuedMessage>>,
+    flushing: AtomicBool,
+}
+
+impl MessageQueue {
+    /// Load the queue file, starting empty if it is missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let messages = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Discarding unreadable message queue {:?}: {}", path, e);
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+
+        Self {
+            path,
+            messages: Mutex::new(messages),
+            flushing: AtomicBool::new(false),
+        }
+    }
+
+    /// Queue a message, dropping the oldest one when full
+    pub fn enqueue(
+        &self,
+        message: String,
+        conversation_id: Option<String>,
+        idempotency_key: Option<String>,
+    ) -> Result<QueuedMessage, SlovoError> {
+        let queued = QueuedMessage {
+            id: Uuid::new_v4().to_string(),
+            message,
+            conversation_id,
+            idempotency_key,
+            queued_at: now_ms(),
+        };
+
+        let mut messages = self.messages.lock().unwrap();
+        messages.push_back(queued.clone());
+        while messages.len() > MAX_QUEUED_MESSAGES {
+            if let Some(dropped) = messages.pop_front() {
+                warn!("Message queue full, dropping oldest message {}", dropped.id);
+            }
+        }
+        self.persist(&messages)?;
+        Ok(queued)
+    }
+
+    /// Snapshot of the queued messages, oldest first
+    pub fn messages(&self) -> Vec<QueuedMessage> {
+        self.messages.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The oldest queued message, without removing it
+    pub fn front(&self) -> Option<QueuedMessage> {
+        self.messages.lock().unwrap().front().cloned()
+    }
+
+    /// Remove a message once it has been handled
+    pub fn remove(&self, id: &str) -> Result<(), SlovoError> {
+        let mut messages = self.messages.lock().unwrap();
+        messages.retain(|m| m.id != id);
+        self.persist(&messages)
+    }
+
+    /// Drop every queued message; returns how many were removed
+    pub fn clear(&self) -> Result<usize, SlovoError> {
+        let mut messages = self.messages.lock().unwrap();
+        let count = messages.len();
+        messages.clear();
+        self.persist(&messages)?;
+        Ok(count)
+    }
+
+    /// Claim the right to flush; returns false if a flush is already running
+    pub fn begin_flush(&self) -> bool {
+        !self.flushing.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn end_flush(&self) {
+        self.flushing.store(false, Ordering::SeqCst);
+    }
+
+    fn persist(&self, messages: &VecDeque<QueuedMessage>) -> Result<(), SlovoError> {
+        let contents =
+            serde_json::to_string_pretty(messages).map_err(|e| SlovoError::ConfigError(e.to_string()))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_message_keeps_its_idempotency_key_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.json");
+        let queue = MessageQueue::load(path.clone());
+        queue
+            .enqueue("hello".to_string(), None, Some("key-1".to_string()))
+            .unwrap();
+
+        let reloaded = MessageQueue::load(path);
+
+        let front = reloaded.front().unwrap();
+        assert_eq!(front.message, "hello");
+        assert_eq!(front.idempotency_key.as_deref(), Some("key-1"));
+    }
+}
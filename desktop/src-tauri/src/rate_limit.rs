@@ -0,0 +1,159 @@
+//! Outgoing request rate limiting
+//!
+//! A token bucket shared through managed state caps how fast the frontend can
+//! send chat messages, so a runaway UI loop cannot flood the agent.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AgentConfig;
+use crate::error::SlovoError;
+
+/// What to do with a request once the bucket is empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitPolicy {
+    /// Hold the request until a token frees up
+    #[default]
+    Wait,
+    /// Fail immediately with an `AgentError` saying "rate limited"
+    Reject,
+}
+
+/// Global token bucket for agent requests
+pub struct RateLimiter {
+    /// Tokens added per second
+    rate: f64,
+    /// Bucket capacity, i.e. how many requests may go out back to back
+    burst: f64,
+    policy: RateLimitPolicy,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// May go negative while callers wait on reserved tokens
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32, policy: RateLimitPolicy) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate: requests_per_second.max(0.0),
+            burst,
+            policy,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Limiter configured from the user's agent settings
+    pub fn from_config(config: &AgentConfig) -> Self {
+        Self::new(config.rate_limit_per_sec, config.rate_limit_burst, config.rate_limit_policy)
+    }
+
+    /// Take a token, waiting or failing per the policy when none is left
+    pub async fn acquire(&self) -> Result<(), SlovoError> {
+        match self.reserve(Instant::now()) {
+            Ok(Duration::ZERO) => Ok(()),
+            Ok(wait) => {
+                tokio::time::sleep(wait).await;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether a request made now would go out without waiting, without taking a token
+    pub fn has_capacity(&self) -> bool {
+        let bucket = self.bucket.lock().unwrap();
+        let elapsed = bucket.refilled_at.elapsed().as_secs_f64();
+        (bucket.tokens + elapsed * self.rate).min(self.burst) >= 1.0
+    }
+
+    /// Claim a token at `now`, returning how long the caller must wait before using it
+    ///
+    /// Taking the clock as a parameter keeps the bucket arithmetic deterministic.
+    fn reserve(&self, now: Instant) -> Result<Duration, SlovoError> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+
+        if self.policy == RateLimitPolicy::Reject || self.rate == 0.0 {
+            return Err(SlovoError::agent("rate limited"));
+        }
+
+        // Reserve the next token so concurrent waiters queue up in order
+        let deficit = 1.0 - bucket.tokens;
+        bucket.tokens -= 1.0;
+        Ok(Duration::from_secs_f64(deficit / self.rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn burst_goes_out_back_to_back() {
+        let limiter = RateLimiter::new(2.0, 3, RateLimitPolicy::Wait);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve(start).unwrap(), Duration::ZERO);
+        }
+        // The fourth waits for one token at 2 per second
+        assert_eq!(limiter.reserve(start).unwrap(), ms(500));
+    }
+
+    #[test]
+    fn steady_state_follows_the_rate() {
+        let limiter = RateLimiter::new(4.0, 1, RateLimitPolicy::Wait);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve(start).unwrap(), Duration::ZERO);
+
+        for i in 1..=5 {
+            assert_eq!(limiter.reserve(start + ms(250 * i)).unwrap(), Duration::ZERO);
+        }
+        assert_eq!(limiter.reserve(start + ms(1_250 + 100)).unwrap(), ms(150));
+    }
+
+    #[test]
+    fn waiters_queue_behind_reserved_tokens() {
+        let limiter = RateLimiter::new(1.0, 1, RateLimitPolicy::Wait);
+        let start = Instant::now();
+        limiter.reserve(start).unwrap();
+
+        assert_eq!(limiter.reserve(start).unwrap(), ms(1_000));
+        assert_eq!(limiter.reserve(start).unwrap(), ms(2_000));
+    }
+
+    #[test]
+    fn idle_time_refills_no_more_than_the_burst() {
+        let limiter = RateLimiter::new(10.0, 2, RateLimitPolicy::Reject);
+        let start = Instant::now();
+        limiter.reserve(start).unwrap();
+        limiter.reserve(start).unwrap();
+        assert!(limiter.reserve(start).is_err());
+
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(later).unwrap(), Duration::ZERO);
+        assert_eq!(limiter.reserve(later).unwrap(), Duration::ZERO);
+        assert!(limiter.reserve(later).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+//! Native microphone capture
+//!
+//! Records from an input device with cpal so voice input works without the
+//! webview's `MediaRecorder`. The cpal stream is not `Send`, so each recording
+//! owns a dedicated thread that keeps the stream alive until stopped.
+
+use std::io::Cursor;
+use std::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{BuildStreamError, SampleFormat, SampleRate, StreamConfig, SupportedStreamConfig};
+use hound::{WavSpec, WavWriter};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::config::SlovoConfig;
+use crate::devices;
+use crate::error::SlovoError;
+
+/// Requested capture format; `None` fields use the device default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureFormat {
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+}
+
+impl CaptureFormat {
+    /// Format from the user's recording settings, where 0 means device default
+    pub fn from_config(config: &SlovoConfig) -> Self {
+        Self {
+            sample_rate: (config.recording_sample_rate > 0).then_some(config.recording_sample_rate),
+            channels: (config.recording_channels > 0).then_some(config.recording_channels),
+        }
+    }
+}
+
+/// Managed slot for the recording in progress
+#[derive(Default)]
+pub struct RecordingState {
+    active: Mutex<Option<Recording>>,
+    /// Incremented per recording so background watchers can tell sessions apart
+    sessions: AtomicU64,
+}
+
+struct Recording {
+    session: u64,
+    stop_tx: mpsc::Sender<()>,
+    thread: JoinHandle<()>,
+    samples: Arc<Mutex<Vec<i16>>>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl RecordingState {
+    /// Start capturing from `device_id`, or the default input device
+    ///
+    /// Returns the session id of the new recording.
+    pub async fn start(&self, device_id: Option<String>, format: CaptureFormat) -> Result<u64, SlovoError> {
+        if self.active.lock().unwrap().is_some() {
+            return Err(SlovoError::VoiceError("Already recording".to_string()));
+        }
+
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = oneshot::channel();
+
+        let buffer = samples.clone();
+        let thread = std::thread::spawn(move || {
+            let stream = match open_stream(device_id.as_deref(), format, buffer) {
+                Ok((stream, config)) => {
+                    let _ = ready_tx.send(Ok(config));
+                    stream
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            // Block until stopped (or the state is dropped), then close the stream
+            let _ = stop_rx.recv();
+            drop(stream);
+        });
+
+        let config = ready_rx
+            .await
+            .map_err(|_| SlovoError::VoiceError("Recording thread exited unexpectedly".to_string()))??;
+        info!("Recording started ({} Hz, {} channels)", config.sample_rate.0, config.channels);
+
+        let mut active = self.active.lock().unwrap();
+        if active.is_some() {
+            // Lost a race with a concurrent start; keep the first recording
+            let _ = stop_tx.send(());
+            return Err(SlovoError::VoiceError("Already recording".to_string()));
+        }
+        let session = self.sessions.fetch_add(1, Ordering::SeqCst) + 1;
+        *active = Some(Recording {
+            session,
+            stop_tx,
+            thread,
+            samples,
+            sample_rate: config.sample_rate.0,
+            channels: config.channels,
+        });
+        Ok(session)
+    }
+
+    /// Sample rate and channel count of the recording `session`, if it is still running
+    pub fn format(&self, session: u64) -> Option<(u32, u16)> {
+        let active = self.active.lock().unwrap();
+        active
+            .as_ref()
+            .filter(|recording| recording.session == session)
+            .map(|recording| (recording.sample_rate, recording.channels))
+    }
+
+    /// Copy the samples of `session` captured after `offset`
+    ///
+    /// Returns `None` once that recording has stopped or been replaced.
+    pub fn samples_since(&self, session: u64, offset: usize) -> Option<Vec<i16>> {
+        let active = self.active.lock().unwrap();
+        let recording = active.as_ref().filter(|recording| recording.session == session)?;
+        let samples = recording.samples.lock().unwrap();
+        Some(samples.get(offset..).unwrap_or_default().to_vec())
+    }
+
+    /// Whether a recording is in progress
+    pub fn is_recording(&self) -> bool {
+        self.active.lock().unwrap().is_some()
+    }
+
+    /// Stop capturing and return the recording as 16-bit PCM WAV bytes
+    pub fn stop(&self) -> Result<Vec<u8>, SlovoError> {
+        let recording = self
+            .active
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| SlovoError::VoiceError("Not recording".to_string()))?;
+
+        let _ = recording.stop_tx.send(());
+        if recording.thread.join().is_err() {
+            warn!("Recording thread panicked");
+        }
+
+        let samples = std::mem::take(&mut *recording.samples.lock().unwrap());
+        info!("Recording stopped ({} samples)", samples.len());
+        encode_wav(&samples, recording.sample_rate, recording.channels)
+    }
+}
+
+/// Open and start an input stream that appends samples to `buffer`
+pub(crate) fn open_stream(
+    device_id: Option<&str>,
+    capture: CaptureFormat,
+    buffer: Arc<Mutex<Vec<i16>>>,
+) -> Result<(cpal::Stream, StreamConfig), SlovoError> {
+    let device = devices::find_input_device(device_id)?;
+    let supported = select_config(&device, capture)?;
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+
+    let on_error = |e| warn!("Input stream error: {}", e);
+    let stream = match format {
+        SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _| buffer.lock().unwrap().extend_from_slice(data),
+            on_error,
+            None,
+        ),
+        SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _| {
+                buffer
+                    .lock()
+                    .unwrap()
+                    .extend(data.iter().map(|&s| (s as i32 - 32768) as i16))
+            },
+            on_error,
+            None,
+        ),
+        SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _| {
+                buffer
+                    .lock()
+                    .unwrap()
+                    .extend(data.iter().map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            },
+            on_error,
+            None,
+        ),
+        other => {
+            return Err(SlovoError::VoiceError(format!(
+                "Unsupported input sample format: {:?}",
+                other
+            )))
+        }
+    }
+    .map_err(build_stream_error)?;
+
+    stream
+        .play()
+        .map_err(|e| SlovoError::VoiceError(format!("Failed to start input stream: {}", e)))?;
+    Ok((stream, config))
+}
+
+/// Pick a device config matching the requested format, else the device default
+fn select_config(device: &cpal::Device, capture: CaptureFormat) -> Result<SupportedStreamConfig, SlovoError> {
+    let default = device.default_input_config().map_err(|e| match e {
+        cpal::DefaultStreamConfigError::DeviceNotAvailable => unavailable(),
+        other => SlovoError::VoiceError(format!("Failed to read input config: {}", other)),
+    })?;
+    if capture == CaptureFormat::default() {
+        return Ok(default);
+    }
+
+    let sample_rate = capture.sample_rate.unwrap_or(default.sample_rate().0);
+    let channels = capture.channels.unwrap_or(default.channels());
+    let matching = device.supported_input_configs().ok().and_then(|mut configs| {
+        configs.find(|range| {
+            range.channels() == channels
+                && (range.min_sample_rate().0..=range.max_sample_rate().0).contains(&sample_rate)
+                && matches!(range.sample_format(), SampleFormat::I16 | SampleFormat::U16 | SampleFormat::F32)
+        })
+    });
+
+    match matching {
+        Some(range) => Ok(range.with_sample_rate(SampleRate(sample_rate))),
+        None => {
+            warn!(
+                "Input device does not support {} Hz / {} channels, using {} Hz / {} channels",
+                sample_rate,
+                channels,
+                default.sample_rate().0,
+                default.channels()
+            );
+            Ok(default)
+        }
+    }
+}
+
+fn unavailable() -> SlovoError {
+    SlovoError::VoiceError("Microphone is unavailable or was disconnected".to_string())
+}
+
+/// Tell missing devices and denied microphone access apart from other failures
+fn build_stream_error(e: BuildStreamError) -> SlovoError {
+    match e {
+        BuildStreamError::DeviceNotAvailable => unavailable(),
+        BuildStreamError::BackendSpecific { err } => {
+            let description = err.description.to_lowercase();
+            if description.contains("permission") || description.contains("denied") {
+                SlovoError::VoiceError("Microphone access was denied; allow it in system settings".to_string())
+            } else {
+                SlovoError::VoiceError(format!("Failed to open input stream: {}", err))
+            }
+        }
+        other => SlovoError::VoiceError(format!("Failed to open input stream: {}", other)),
+    }
+}
+
+pub(crate) fn encode_wav(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<u8>, SlovoError> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let encode_error = |e: hound::Error| SlovoError::VoiceError(format!("Failed to encode recording: {}", e));
+
+    let mut out = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut out, spec).map_err(encode_error)?;
+    for &sample in samples {
+        writer.write_sample(sample).map_err(encode_error)?;
+    }
+    writer.finalize().map_err(encode_error)?;
+    Ok(out.into_inner())
+}
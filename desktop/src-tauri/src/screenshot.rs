@@ -0,0 +1,96 @@
+//! Screen capture for sharing with the agent
+//!
+//! Uses each platform's own screenshot tool, writing to a temporary PNG:
+//! `screencapture` on macOS, .NET through PowerShell on Windows, and `grim`
+//! (Wayland) or ImageMagick's `import` (X11) on Linux.
+
+use std::path::Path;
+use std::process::Command;
+
+use uuid::Uuid;
+
+use crate::error::SlovoError;
+
+/// Capture a display as PNG bytes; `None` captures the primary display
+///
+/// Display indices start at 0. Blocks until the capture is written.
+pub fn capture_png(display_index: Option<u32>) -> Result<Vec<u8>, SlovoError> {
+    let path = std::env::temp_dir().join(format!("slovo-screenshot-{}.png", Uuid::new_v4()));
+    let result = capture_to(&path, display_index).and_then(|()| std::fs::read(&path).map_err(SlovoError::from));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn capture_to(path: &Path, display_index: Option<u32>) -> Result<(), SlovoError> {
+    let output = capture_command(path, display_index).output()?;
+    if !output.status.success() {
+        return Err(SlovoError::IoError(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!(
+                "screenshot failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn capture_command(path: &Path, display_index: Option<u32>) -> Command {
+    let mut command = Command::new("screencapture");
+    // Silent, and displays are numbered from 1
+    command.args(["-x", "-t", "png"]);
+    if let Some(index) = display_index {
+        command.arg(format!("-D{}", index + 1));
+    }
+    command.arg(path);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn capture_command(path: &Path, display_index: Option<u32>) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    // Inputs arrive as environment variables so nothing needs quoting
+    const SCRIPT: &str = "$ErrorActionPreference = 'Stop'; \
+        Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+        $screen = if ($env:SLOVO_DISPLAY) { \
+            [System.Windows.Forms.Screen]::AllScreens[[int]$env:SLOVO_DISPLAY] \
+        } else { [System.Windows.Forms.Screen]::PrimaryScreen }; \
+        if (-not $screen) { throw 'No such display' }; \
+        $bounds = $screen.Bounds; \
+        $bitmap = New-Object System.Drawing.Bitmap $bounds.Width, $bounds.Height; \
+        $graphics = [System.Drawing.Graphics]::FromImage($bitmap); \
+        $graphics.CopyFromScreen($bounds.Location, [System.Drawing.Point]::Empty, $bounds.Size); \
+        $bitmap.Save($env:SLOVO_SCREENSHOT, [System.Drawing.Imaging.ImageFormat]::Png)";
+
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("SLOVO_SCREENSHOT", path)
+        .env(
+            "SLOVO_DISPLAY",
+            display_index.map(|index| index.to_string()).unwrap_or_default(),
+        )
+        .creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_command(path: &Path, display_index: Option<u32>) -> Command {
+    // Neither tool can pick a display by number, so the whole desktop is taken
+    if display_index.is_some_and(|index| index > 0) {
+        tracing::warn!("Choosing a display is not supported here; capturing all displays");
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("grim");
+        command.arg(path);
+        command
+    } else {
+        let mut command = Command::new("import");
+        command.args(["-window", "root"]).arg(path);
+        command
+    }
+}
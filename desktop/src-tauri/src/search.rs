@@ -0,0 +1,212 @@
+//! Full-text search over saved conversations
+//!
+//! An inverted index from lowercase words, and from every suffix of those
+//! words, to the messages containing them narrows a query down to a few
+//! candidate messages, which are then checked with a plain case-insensitive
+//! substring match.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Bound;
+
+use serde::Serialize;
+
+use crate::conversation::Conversation;
+
+/// Characters of context kept on each side of a match
+const SNIPPET_CONTEXT_CHARS: usize = 50;
+
+/// A message matching a search query
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub conversation_id: String,
+    pub title: String,
+    pub matched_message_index: usize,
+    /// The match with up to 50 characters either side
+    pub snippet: String,
+}
+
+/// A message, as `(conversation_id, message_index)`
+type MessageRef = (String, usize);
+
+type WordMap = BTreeMap<String, HashSet<MessageRef>>;
+
+/// Lowercase words and their suffixes → messages containing them
+#[derive(Default)]
+pub struct SearchIndex {
+    words: WordMap,
+    /// Every suffix of every word, the whole word included
+    suffixes: WordMap,
+}
+
+impl SearchIndex {
+    /// Index every message of the given conversations
+    pub fn build<'a>(conversations: impl IntoIterator<Item = &'a Conversation>) -> Self {
+        let mut index = Self::default();
+        for conversation in conversations {
+            for (position, message) in conversation.messages.iter().enumerate() {
+                let message_ref = (conversation.id.clone(), position);
+                for (_, word) in words(&message.content.to_lowercase()) {
+                    index.words.entry(word.to_string()).or_default().insert(message_ref.clone());
+                    for (start, _) in word.char_indices() {
+                        index
+                            .suffixes
+                            .entry(word[start..].to_string())
+                            .or_default()
+                            .insert(message_ref.clone());
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    /// Messages that could contain `needle`, or `None` if the index cannot narrow it down
+    ///
+    /// `needle` must already be lowercase. A query word with a separator
+    /// before it in `needle` starts an indexed word, so it is looked up among
+    /// the words; otherwise it may start partway through one, so it is looked
+    /// up among the suffixes. A query word with a separator after it must
+    /// match exactly, any other only as a prefix.
+    pub fn candidates(&self, needle: &str) -> Option<HashSet<MessageRef>> {
+        let mut candidates: Option<HashSet<MessageRef>> = None;
+        for (start, query_word) in words(needle) {
+            let starts_word = start > 0;
+            let ends_word = start + query_word.len() < needle.len();
+            let map = if starts_word { &self.words } else { &self.suffixes };
+            let found = if ends_word {
+                map.get(query_word).cloned().unwrap_or_default()
+            } else {
+                with_prefix(map, query_word)
+            };
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&found).cloned().collect(),
+                None => found,
+            });
+        }
+        candidates
+    }
+}
+
+/// Messages under every key of `map` that starts with `prefix`
+fn with_prefix(map: &WordMap, prefix: &str) -> HashSet<MessageRef> {
+    map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(|(word, _)| word.starts_with(prefix))
+        .flat_map(|(_, messages)| messages.iter().cloned())
+        .collect()
+}
+
+/// Runs of letters and digits, with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        // Each word is a slice of `text`, so its offset is the distance between them
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// The first case-insensitive match of `needle` in `content` with surrounding context
+///
+/// `needle` must already be lowercase. Returns `None` if there is no match.
+pub fn snippet(content: &str, needle: &str) -> Option<String> {
+    let lower = content.to_lowercase();
+    let byte_start = lower.find(needle)?;
+    // Lowercasing can change the length of some characters, so count in chars
+    // and clamp to the original text
+    let chars: Vec<char> = content.chars().collect();
+    let start = lower[..byte_start].chars().count().min(chars.len());
+    let end = (start + needle.chars().count()).min(chars.len());
+    let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let to = (end + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    Some(chars[from..to].iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::{ConversationMessage, MessageRole};
+
+    fn conversation(id: &str, messages: &[&str]) -> Conversation {
+        let mut conversation = Conversation::new(id);
+        conversation.messages = messages
+            .iter()
+            .map(|content| ConversationMessage {
+                role: MessageRole::User,
+                content: content.to_string(),
+                timestamp: 0,
+                reasoning: None,
+                alternatives: Vec::new(),
+                interrupted: false,
+            })
+            .collect();
+        conversation
+    }
+
+    fn index() -> SearchIndex {
+        SearchIndex::build(&[
+            conversation("a", &["Hello world", "The weather is nice"]),
+            conversation("b", &["Say hello to the new world order"]),
+        ])
+    }
+
+    fn found(index: &SearchIndex, needle: &str) -> Vec<MessageRef> {
+        let mut found: Vec<_> = index.candidates(needle).unwrap().into_iter().collect();
+        found.sort();
+        found
+    }
+
+    fn at(id: &str, position: usize) -> MessageRef {
+        (id.to_string(), position)
+    }
+
+    #[test]
+    fn candidates_match_any_part_of_a_single_word() {
+        let index = index();
+
+        assert_eq!(found(&index, "hello"), [at("a", 0), at("b", 0)]);
+        assert_eq!(found(&index, "ell"), [at("a", 0), at("b", 0)]);
+        assert_eq!(found(&index, "eath"), [at("a", 1)]);
+        assert!(found(&index, "goodbye").is_empty());
+    }
+
+    #[test]
+    fn candidates_pin_query_words_to_word_boundaries() {
+        let index = index();
+
+        // "lo" ends a word and "wor" starts one
+        assert_eq!(found(&index, "lo wor"), [at("a", 0), at("b", 0)]);
+        // "world" must be a whole word here, and "order" must start one
+        assert_eq!(found(&index, "world order"), [at("b", 0)]);
+        // "ell" does not end any word
+        assert!(found(&index, "ell world").is_empty());
+        // "orld" does not start any word
+        assert!(found(&index, "hello orld").is_empty());
+    }
+
+    #[test]
+    fn candidates_cannot_narrow_a_needle_without_words() {
+        assert!(index().candidates("?!").is_none());
+    }
+
+    #[test]
+    fn snippet_keeps_fifty_characters_of_context_each_side() {
+        let content = format!("{}needle{}", "a".repeat(80), "b".repeat(80));
+
+        let snippet = snippet(&content, "needle").unwrap();
+
+        assert_eq!(snippet, format!("{}needle{}", "a".repeat(50), "b".repeat(50)));
+    }
+
+    #[test]
+    fn snippet_matches_case_insensitively_and_keeps_the_original_case() {
+        assert_eq!(snippet("Say HELLO there", "hello").as_deref(), Some("Say HELLO there"));
+        assert_eq!(snippet("Say hello there", "goodbye"), None);
+    }
+
+    #[test]
+    fn snippet_counts_characters_not_bytes() {
+        let content = format!("{}Привет{}", "ж".repeat(60), "ё".repeat(60));
+
+        let snippet = snippet(&content, "привет").unwrap();
+
+        assert_eq!(snippet, format!("{}Привет{}", "ж".repeat(50), "ё".repeat(50)));
+    }
+}
@@ -0,0 +1,119 @@
+//! Graceful app exit
+//!
+//! Every exit path (the tray's Quit, the OS quitting the app, SIGTERM) ends in
+//! `RunEvent::ExitRequested`. The first request is held back while agent
+//! requests still in flight get a few seconds to finish, so a reply that is
+//! on its way is still saved to the conversation.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::agent::HealthMonitor;
+use crate::agent_process;
+use crate::audit_log::AuditLogger;
+
+/// Longest wait for in-flight requests before exiting anyway
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Managed count of agent requests in flight
+///
+/// Every path that talks to the agent holds an [`InFlight`] from
+/// [`InFlightRequests::track`] until it is done; `RequestGuard` holds one too.
+#[derive(Default)]
+pub struct InFlightRequests(Arc<AtomicUsize>);
+
+impl InFlightRequests {
+    /// Count a request as in flight until the returned guard is dropped
+    pub fn track(&self) -> InFlight {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        InFlight(self.0.clone())
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Wait up to `timeout` for every in-flight request to finish
+    ///
+    /// Returns false if some were still running when the time ran out.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let started = Instant::now();
+        while self.count() > 0 {
+            if started.elapsed() >= timeout {
+                return false;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+        true
+    }
+}
+
+/// One request counted by [`InFlightRequests`], until dropped
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Managed flag set once the app has begun shutting down
+#[derive(Default)]
+pub struct ShutdownState(AtomicBool);
+
+/// Handle `RunEvent::ExitRequested`, returning true if the exit must wait
+///
+/// The first request starts [`shutdown`] in the background, which exits
+/// again when done; that second request is let through.
+pub fn exit_requested(app: &AppHandle, code: Option<i32>) -> bool {
+    if app.state::<ShutdownState>().0.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        shutdown(&app).await;
+        app.exit(code.unwrap_or(0));
+    });
+    true
+}
+
+/// Stop background work, let in-flight requests finish, and flush the logs
+async fn shutdown(app: &AppHandle) {
+    // No new health checks or reconnects while requests wind down
+    app.state::<HealthMonitor>().stop();
+
+    let requests = app.state::<InFlightRequests>();
+    if requests.count() > 0 {
+        info!("Waiting for {} agent request(s) before exiting", requests.count());
+    }
+    if !requests.drain(DRAIN_TIMEOUT).await {
+        warn!("Exiting with {} agent request(s) still in flight", requests.count());
+    }
+
+    agent_process::shutdown(app).await;
+    app.state::<Arc<AuditLogger>>().flush();
+}
+
+/// Turn SIGTERM into an ordinary exit request so it shuts down gracefully too
+#[cfg(unix)]
+pub async fn watch_signals(app: AppHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            warn!("Failed to listen for SIGTERM: {}", e);
+            return;
+        }
+    };
+    if terminate.recv().await.is_some() {
+        info!("Received SIGTERM, shutting down");
+        app.exit(0);
+    }
+}
@@ -0,0 +1,98 @@
+//! Text-to-speech playback module
+//!
+//! Plays synthesized replies on the chosen output device. Only one utterance
+//! plays at a time: starting a new one (or stopping) halts the previous sink.
+
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use rodio::{Decoder, OutputStream, Sink};
+use tokio::sync::oneshot;
+
+use crate::agent::SpeechAudio;
+use crate::devices;
+use crate::error::SlovoError;
+
+/// Managed handle to the utterance currently playing
+#[derive(Default)]
+pub struct SpeechPlayback {
+    current: Mutex<Current>,
+}
+
+#[derive(Default)]
+struct Current {
+    /// Bumped for every `play` so a superseded utterance can tell it lost
+    generation: u64,
+    sink: Option<Arc<Sink>>,
+}
+
+impl SpeechPlayback {
+    /// Play synthesized speech on `device` (or the default output), interrupting
+    /// anything already playing
+    ///
+    /// Resolves when playback finishes or is interrupted; the result is true
+    /// only if the utterance played to the end.
+    pub async fn play(&self, speech: SpeechAudio, device: Option<String>) -> Result<bool, SlovoError> {
+        let generation = {
+            let mut current = self.current.lock().unwrap();
+            if let Some(sink) = current.sink.take() {
+                sink.stop();
+            }
+            current.generation += 1;
+            current.generation
+        };
+
+        // The output stream is not Send, so it lives on a blocking thread for
+        // the whole utterance and hands the sink back for cancellation
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let playback = tauri::async_runtime::spawn_blocking(move || -> Result<(), SlovoError> {
+            let device = devices::find_output_device(device.as_deref())?;
+            let (_stream, handle) = OutputStream::try_from_device(&device)
+                .map_err(|e| SlovoError::VoiceError(format!("No audio output device: {}", e)))?;
+            let sink = Sink::try_new(&handle)
+                .map_err(|e| SlovoError::VoiceError(format!("Failed to start audio playback: {}", e)))?;
+            let source = Decoder::new(Cursor::new(speech.audio))
+                .map_err(|e| SlovoError::VoiceError(format!("Failed to decode speech audio: {}", e)))?;
+
+            let sink = Arc::new(sink);
+            sink.append(source);
+            let _ = ready_tx.send(sink.clone());
+            sink.sleep_until_end();
+            Ok(())
+        });
+
+        if let Ok(sink) = ready_rx.await {
+            let mut current = self.current.lock().unwrap();
+            if current.generation == generation {
+                current.sink = Some(sink);
+            } else {
+                sink.stop();
+            }
+        }
+
+        playback
+            .await
+            .map_err(|e| SlovoError::VoiceError(format!("Audio playback failed: {}", e)))??;
+
+        // `stop` and newer utterances take the sink, so still holding it means
+        // nobody interrupted us
+        let mut current = self.current.lock().unwrap();
+        Ok(current.generation == generation && current.sink.take().is_some())
+    }
+
+    /// Whether an utterance is currently playing
+    pub fn is_speaking(&self) -> bool {
+        self.current.lock().unwrap().sink.is_some()
+    }
+
+    /// Halt the current utterance; returns false if nothing was playing
+    pub fn stop(&self) -> bool {
+        match self.current.lock().unwrap().sink.take() {
+            Some(sink) => {
+                sink.stop();
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Shared app state
+//!
+//! The agent client and transport, config, current conversation and health
+//! history are built together in `setup` and managed as one [`AppState`].
+//! Commands that return `CommandResponse` cannot borrow `State`, so they
+//! reach it through [`AppState::of`] instead.
+
+use std::sync::{Arc, RwLock};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::agent::{AgentClient, AgentTransport};
+use crate::config::{SharedConfig, SlovoConfig};
+use crate::conversation::CurrentConversation;
+use crate::health_history::HealthHistory;
+
+pub struct AppState {
+    /// The one client for the whole app, so commands share its connection pool
+    pub client: AgentClient,
+    /// Carries health checks and plain chat messages; `client` itself unless
+    /// the `ws-transport` feature swaps in a WebSocket
+    pub transport: Arc<dyn AgentTransport>,
+    pub config: SharedConfig,
+    pub conversation: CurrentConversation,
+    /// Recent health checks, newest last
+    pub health_history: HealthHistory,
+}
+
+impl AppState {
+    pub fn new(client: AgentClient, config: SlovoConfig) -> Self {
+        Self {
+            transport: Arc::new(client.clone()),
+            client,
+            config: Arc::new(RwLock::new(config)),
+            conversation: CurrentConversation::default(),
+            health_history: HealthHistory::default(),
+        }
+    }
+
+    /// Send health checks and plain chat messages over `transport` instead
+    pub fn with_transport(mut self, transport: Arc<dyn AgentTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// The state managed for `app`
+    ///
+    /// Panics if called before `setup` has managed it.
+    pub fn of<R: Runtime>(app: &AppHandle<R>) -> &AppState {
+        app.state::<AppState>().inner()
+    }
+}
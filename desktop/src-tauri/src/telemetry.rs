@@ -0,0 +1,54 @@
+//! OpenTelemetry span export
+//!
+//! Only built with the `opentelemetry` feature. Spans are batched to an OTLP
+//! collector over gRPC at `OTEL_EXPORTER_OTLP_ENDPOINT` (default
+//! `http://localhost:4317`), so a local Jaeger can show latency across the
+//! desktop app, the agent, and the model.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::runtime::Tokio;
+use opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor;
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use opentelemetry_sdk::Resource;
+use tracing::{warn, Subscriber};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Service name spans are reported under
+const SERVICE_NAME: &str = "slovo-desktop";
+
+/// Flushes pending spans and shuts the exporter down when dropped
+pub struct TelemetryGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            warn!("Failed to shut down span exporter: {}", e);
+        }
+    }
+}
+
+/// Build the layer exporting spans to the OTLP collector
+pub fn layer<S>() -> Result<(OpenTelemetryLayer<S, Tracer>, TelemetryGuard), String>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    // The tonic channel and batch processor both run on the Tokio runtime
+    let _runtime = tauri::async_runtime::handle().inner().enter();
+
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let provider = SdkTracerProvider::builder()
+        .with_span_processor(BatchSpanProcessor::builder(exporter, Tokio).build())
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+
+    let tracer = provider.tracer(SERVICE_NAME);
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), TelemetryGuard { provider }))
+}
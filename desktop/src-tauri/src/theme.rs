@@ -0,0 +1,136 @@
+//! OS light/dark appearance tracking
+//!
+//! On macOS and Windows the window reports the appearance and its changes
+//! (`WindowEvent::ThemeChanged`). Linux desktops do not reliably do either,
+//! so there it is read with `gsettings` and polled.
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use std::process::Command;
+use std::sync::Mutex;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info};
+
+/// How often the OS appearance is checked for changes on Linux
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// OS appearance setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OsTheme {
+    Light,
+    Dark,
+    /// The setting could not be read
+    #[default]
+    Unknown,
+}
+
+impl OsTheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OsTheme::Light => "light",
+            OsTheme::Dark => "dark",
+            OsTheme::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<tauri::Theme> for OsTheme {
+    fn from(theme: tauri::Theme) -> Self {
+        match theme {
+            tauri::Theme::Light => OsTheme::Light,
+            tauri::Theme::Dark => OsTheme::Dark,
+            _ => OsTheme::Unknown,
+        }
+    }
+}
+
+/// Managed record of the last theme reported to the frontend
+#[derive(Default)]
+pub struct LastTheme(Mutex<OsTheme>);
+
+impl LastTheme {
+    /// Record `theme`, returning whether it differs from the previous one
+    fn update(&self, theme: OsTheme) -> bool {
+        let mut last = self.0.lock().unwrap();
+        let changed = *last != theme;
+        *last = theme;
+        changed
+    }
+}
+
+/// Read the OS appearance as the main window sees it
+///
+/// Tauri asks `NSAppearance` on macOS and the system dark mode setting on Windows.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn current_os_theme(app: &AppHandle) -> OsTheme {
+    app.get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .map_or(OsTheme::Unknown, OsTheme::from)
+}
+
+/// Read the OS appearance; blocks briefly while `gsettings` runs
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn current_os_theme(_app: &AppHandle) -> OsTheme {
+    // GNOME 42+ exposes an explicit preference; older desktops only a theme name
+    if let Some(scheme) = gsettings("color-scheme") {
+        match scheme.as_str() {
+            "prefer-dark" => return OsTheme::Dark,
+            "prefer-light" => return OsTheme::Light,
+            _ => {}
+        }
+    }
+    match gsettings("gtk-theme") {
+        Some(name) if name.to_lowercase().contains("dark") => OsTheme::Dark,
+        Some(_) => OsTheme::Light,
+        None => OsTheme::Unknown,
+    }
+}
+
+/// A GNOME interface setting with its quotes removed
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn gsettings(key: &str) -> Option<String> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", key])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'').to_string())
+}
+
+/// Read the OS appearance off the async runtime
+async fn read(app: &AppHandle) -> OsTheme {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || current_os_theme(&app))
+        .await
+        .unwrap_or_default()
+}
+
+/// Read the OS appearance and remember it, returning whether it changed
+pub async fn refresh(app: &AppHandle) -> (OsTheme, bool) {
+    let theme = read(app).await;
+    (theme, app.state::<LastTheme>().update(theme))
+}
+
+/// Emit `theme-changed` with "light" or "dark" if `theme` differs from the last one
+pub fn report(app: &AppHandle, theme: OsTheme) {
+    if app.state::<LastTheme>().update(theme) && theme != OsTheme::Unknown {
+        info!("OS theme changed to {}", theme.as_str());
+        if let Err(e) = app.emit("theme-changed", theme.as_str()) {
+            debug!("Failed to emit theme change: {}", e);
+        }
+    }
+}
+
+/// Poll the OS appearance and report it when it flips
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub async fn watch_theme(app: AppHandle) {
+    loop {
+        report(&app, read(&app).await);
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -1,14 +1,45 @@
 //! System tray management module
-//! 
-//! Note: Most tray functionality is now handled via the frontend using @tauri-apps/api/tray
-//! This module contains any native tray utilities if needed.
+//!
+//! The tray icon is declared in `tauri.conf.json`; its context menu is built
+//! natively here so it works even before the webview has loaded.
 
-use tracing::info;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{info, warn};
+
+use crate::commands;
+use crate::error::SlovoError;
+use crate::state::AppState;
+use crate::window_state::WindowModeState;
+
+/// Tray icon id from `tauri.conf.json`
+pub const TRAY_ID: &str = "slovo-tray";
+
+/// Tray menu item ids
+const MENU_TOGGLE_WINDOW: &str = "toggle_window";
+const MENU_SETTINGS: &str = "settings";
+const MENU_ALWAYS_ON_TOP: &str = "always_on_top";
+const MENU_MINI_MODE: &str = "mini_mode";
+const MENU_RESTART_AGENT: &str = "restart_agent";
+const MENU_QUIT: &str = "quit";
+
+/// How often the watchdog looks for a stuck Processing state
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Tray icon states
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum TrayState {
     /// Normal idle state
+    #[default]
     Idle,
     /// Listening for voice input
     Listening,
@@ -19,6 +50,13 @@ pub enum TrayState {
 }
 
 impl TrayState {
+    pub const ALL: [TrayState; 4] = [
+        TrayState::Idle,
+        TrayState::Listening,
+        TrayState::Processing,
+        TrayState::Error,
+    ];
+
     /// Get the icon filename for this state
     pub fn icon_name(&self) -> &'static str {
         match self {
@@ -29,6 +67,16 @@ impl TrayState {
         }
     }
 
+    /// The icon shown for this state, embedded at build time
+    pub fn icon(&self) -> Image<'static> {
+        match self {
+            TrayState::Idle => tauri::include_image!("./icons/icon.png"),
+            TrayState::Listening => tauri::include_image!("./icons/icon-listening.png"),
+            TrayState::Processing => tauri::include_image!("./icons/icon-processing.png"),
+            TrayState::Error => tauri::include_image!("./icons/icon-error.png"),
+        }
+    }
+
     /// Get the tooltip text for this state
     pub fn tooltip(&self) -> &'static str {
         match self {
@@ -40,7 +88,422 @@ impl TrayState {
     }
 }
 
+impl FromStr for TrayState {
+    type Err = SlovoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "idle" => Ok(TrayState::Idle),
+            "listening" => Ok(TrayState::Listening),
+            "processing" => Ok(TrayState::Processing),
+            "error" => Ok(TrayState::Error),
+            _ => Err(SlovoError::ConfigError(format!("Unknown tray state '{}'", s))),
+        }
+    }
+}
+
+/// Icon files for the tray states that are missing from `dir` or do not load as PNG images
+pub fn missing_icons(dir: &Path) -> Vec<&'static str> {
+    TrayState::ALL
+        .iter()
+        .map(TrayState::icon_name)
+        .filter(|name| Image::from_path(dir.join(name)).is_err())
+        .collect()
+}
+
+/// Load the icon for `state` from `dir` rather than the embedded copy, e.g. to try out a theme
+pub fn load_icon(dir: &Path, state: TrayState) -> Result<Image<'static>, SlovoError> {
+    let path = dir.join(state.icon_name());
+    Image::from_path(&path)
+        .map_err(|e| SlovoError::ConfigError(format!("Failed to load {}: {}", path.display(), e)))
+}
+
+/// Managed tray state, driven by the request lifecycle
+#[derive(Default)]
+pub struct TrayManager {
+    state: Mutex<TrayState>,
+    /// State the tray icon last showed; lags `state` by the debounce window
+    displayed: Mutex<TrayState>,
+    /// Bumped on every change so a superseded pending update is dropped
+    generation: AtomicU64,
+    /// Requests currently waiting on the agent
+    in_flight: AtomicUsize,
+    /// Last state change or request start/finish, for the watchdog
+    last_activity: Mutex<Option<Instant>>,
+    /// "Always on Top" menu item, kept in sync when the setting changes elsewhere
+    always_on_top_item: Mutex<Option<CheckMenuItem<tauri::Wry>>>,
+}
+
+impl TrayManager {
+    /// The state the tray currently shows
+    pub fn state(&self) -> TrayState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Switch the tray to a new state
+    ///
+    /// The icon follows only once the state has held for `tray_debounce_ms`,
+    /// so a quick flap such as Idle → Processing → Idle never shows.
+    pub fn set_state(&self, app: &AppHandle, state: TrayState) {
+        self.touch();
+        {
+            let mut current = self.state.lock().unwrap();
+            if *current == state {
+                return;
+            }
+            *current = state;
+        }
+
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let debounce = AppState::of(app).config.read().unwrap().tray_debounce_ms;
+        if debounce == 0 {
+            self.display(app, state);
+            return;
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(debounce)).await;
+            let tray = app.state::<TrayManager>();
+            // A later change restarted the wait
+            if tray.generation.load(Ordering::SeqCst) == generation {
+                tray.display(&app, state);
+            }
+        });
+    }
+
+    /// Update the tray icon itself, if it does not already show `state`
+    fn display(&self, app: &AppHandle, state: TrayState) {
+        {
+            let mut displayed = self.displayed.lock().unwrap();
+            if *displayed == state {
+                return;
+            }
+            *displayed = state;
+        }
+
+        show(app, state, state.icon());
+    }
+
+    /// Show `state` on the tray with `icon` for `duration`, then return to the real state
+    ///
+    /// Used to preview icon themes; request tracking carries on underneath.
+    pub fn preview(
+        &self,
+        app: &AppHandle,
+        state: TrayState,
+        icon: Image<'static>,
+        duration: Duration,
+    ) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        *self.displayed.lock().unwrap() = state;
+        show(app, state, icon);
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(duration).await;
+            let tray = app.state::<TrayManager>();
+            // A real change since then has already replaced the preview
+            if tray.generation.load(Ordering::SeqCst) == generation {
+                let state = tray.state();
+                *tray.displayed.lock().unwrap() = state;
+                show(&app, state, state.icon());
+            }
+        });
+    }
+
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Force the tray back to Idle, forgetting any requests believed to be in flight
+    pub fn reset(&self, app: &AppHandle) {
+        self.in_flight.store(0, Ordering::SeqCst);
+        self.set_state(app, TrayState::Idle);
+    }
+
+    /// Reflect the always-on-top setting in the tray menu
+    pub fn set_always_on_top_checked(&self, checked: bool) {
+        if let Some(item) = self.always_on_top_item.lock().unwrap().as_ref() {
+            if let Err(e) = item.set_checked(checked) {
+                warn!("Failed to update tray menu: {}", e);
+            }
+        }
+    }
+
+    /// Show Listening while the microphone is recording
+    pub fn listening_started(&self, app: &AppHandle) {
+        self.set_state(app, TrayState::Listening);
+    }
+
+    /// Leave Listening, falling back to Processing if requests are still in flight
+    pub fn listening_finished(&self, app: &AppHandle) {
+        if self.state() != TrayState::Listening {
+            return;
+        }
+        let state = if self.in_flight.load(Ordering::SeqCst) > 0 {
+            TrayState::Processing
+        } else {
+            TrayState::Idle
+        };
+        self.set_state(app, state);
+    }
+
+    /// Show Processing while at least one request is in flight
+    pub fn request_started(&self, app: &AppHandle) {
+        self.touch();
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        self.set_state(app, TrayState::Processing);
+    }
+
+    /// Return to Idle once the last in-flight request finishes
+    pub fn request_finished(&self, app: &AppHandle) {
+        self.touch();
+        let previous = self
+            .in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_sub(1)))
+            .unwrap_or_default();
+        if previous <= 1 && self.state() == TrayState::Processing {
+            self.set_state(app, TrayState::Idle);
+        }
+    }
+}
+
+/// Put `icon` and the tooltip for `state` on the tray
+fn show(app: &AppHandle, state: TrayState, icon: Image<'static>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    // macOS renders template icons monochrome, which would hide the state colours
+    let template = state == TrayState::Idle;
+    let result = tray
+        .set_icon(Some(icon))
+        .and_then(|()| tray.set_icon_as_template(template));
+    if let Err(e) = result {
+        warn!("Failed to update tray icon: {}", e);
+    }
+    if let Err(e) = tray.set_tooltip(Some(state.tooltip())) {
+        warn!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+/// Reset the tray to Idle if it shows Processing with no activity for too long
+///
+/// Processing should always end with a finished request; when it does not,
+/// a request future was dropped somewhere, so the warning is worth chasing.
+pub async fn watch_processing(app: AppHandle) {
+    loop {
+        tokio::time::sleep(WATCHDOG_INTERVAL).await;
+        let timeout = AppState::of(&app).config.read().unwrap().tray_processing_timeout_secs;
+        if timeout == 0 {
+            continue;
+        }
+
+        let tray = app.state::<TrayManager>();
+        if tray.state() != TrayState::Processing {
+            continue;
+        }
+        let idle = tray
+            .last_activity
+            .lock()
+            .unwrap()
+            .map(|at| at.elapsed())
+            .unwrap_or_default();
+        if idle >= Duration::from_secs(timeout) {
+            warn!(
+                "Tray stuck in Processing for {}s with {} request(s) in flight; resetting to Idle",
+                idle.as_secs(),
+                tray.in_flight.load(Ordering::SeqCst)
+            );
+            tray.reset(&app);
+        }
+    }
+}
+
 /// Initialize tray state tracking
 pub fn init_tray() {
     info!("Tray state tracking initialized");
 }
+
+/// Build the native tray context menu
+pub fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let toggle = MenuItem::with_id(app, MENU_TOGGLE_WINDOW, "Show/Hide Window", true, None::<&str>)?;
+    let settings = MenuItem::with_id(app, MENU_SETTINGS, "Settings", true, None::<&str>)?;
+    let mini_mode = MenuItem::with_id(app, MENU_MINI_MODE, "Toggle Mini Mode", true, None::<&str>)?;
+    let always_on_top = AppState::of(app).config.read().unwrap().always_on_top;
+    let on_top =
+        CheckMenuItem::with_id(app, MENU_ALWAYS_ON_TOP, "Always on Top", true, always_on_top, None::<&str>)?;
+    let restart = MenuItem::with_id(app, MENU_RESTART_AGENT, "Restart Agent", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit Slovo", true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &toggle,
+            &PredefinedMenuItem::separator(app)?,
+            &settings,
+            &mini_mode,
+            &on_top,
+            &restart,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )
+}
+
+/// Attach the native menu and click handling to the configured tray icon
+pub fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        warn!("Tray icon '{}' not found, skipping tray menu", TRAY_ID);
+        return Ok(());
+    };
+
+    let menu = build_tray_menu(app)?;
+    let on_top = menu
+        .get(MENU_ALWAYS_ON_TOP)
+        .and_then(|item| item.as_check_menuitem().cloned());
+    *app.state::<TrayManager>().always_on_top_item.lock().unwrap() = on_top;
+    tray.set_menu(Some(menu))?;
+    tray.set_show_menu_on_left_click(false)?;
+    tray.on_menu_event(handle_menu_event);
+    tray.on_tray_icon_event(|tray, event| {
+        if let TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } = event
+        {
+            toggle_main_window(tray.app_handle());
+        }
+    });
+    Ok(())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_TOGGLE_WINDOW => toggle_main_window(app),
+        MENU_SETTINGS => {
+            show_main_window(app);
+            let _ = app.emit("open-settings", ());
+        }
+        MENU_MINI_MODE => {
+            show_main_window(app);
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if app.state::<WindowModeState>().in_mini_mode() {
+                    let _ = commands::exit_mini_mode(app).await;
+                } else {
+                    let _ = commands::enter_mini_mode(app).await;
+                }
+            });
+        }
+        MENU_ALWAYS_ON_TOP => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::toggle_always_on_top(app).await;
+            });
+        }
+        MENU_RESTART_AGENT => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                // Failures are logged and emitted by the command itself
+                let _ = commands::restart_agent(app).await;
+            });
+        }
+        MENU_QUIT => quit(app),
+        _ => {}
+    }
+}
+
+/// Handle the main window's close button according to the `close_to_tray` setting
+pub fn handle_close_requested(app: &AppHandle) {
+    let close_to_tray = AppState::of(app).config.read().unwrap().close_to_tray;
+    if close_to_tray {
+        hide_to_tray(app);
+    } else {
+        quit(app);
+    }
+}
+
+/// Exit the app; the exit handler stops the agent runtime gracefully first
+pub fn quit(app: &AppHandle) {
+    app.exit(0);
+}
+
+/// Hide the main window, explaining the first time that the app is still running
+fn hide_to_tray(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+        info!("Window hidden to tray");
+    }
+
+    let shared = &AppState::of(app).config;
+    let mut config = shared.write().unwrap();
+    if config.tray_notice_shown {
+        return;
+    }
+
+    let _ = app
+        .notification()
+        .builder()
+        .title("Slovo is still running")
+        .body("Slovo keeps listening from the system tray. Use Quit in the tray menu to exit.")
+        .show();
+    config.tray_notice_shown = true;
+    if let Err(e) = config.save(app) {
+        warn!("Failed to save tray notice preference: {}", e);
+    }
+}
+
+/// Show the main window if hidden, hide it otherwise
+fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        show_main_window(app);
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        commands::auto_paste_clipboard(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn icons_dir() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("icons")
+    }
+
+    #[test]
+    fn every_state_icon_ships_and_loads() {
+        assert!(missing_icons(&icons_dir()).is_empty());
+        for state in TrayState::ALL {
+            let icon = load_icon(&icons_dir(), state).unwrap();
+            assert!(icon.width() > 0 && icon.height() > 0);
+        }
+    }
+
+    #[test]
+    fn missing_icons_lists_absent_and_broken_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for state in [TrayState::Idle, TrayState::Listening] {
+            std::fs::copy(icons_dir().join(state.icon_name()), dir.path().join(state.icon_name()))
+                .unwrap();
+        }
+        // Right signature, but no image after it
+        std::fs::write(dir.path().join("icon-error.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+        assert_eq!(missing_icons(dir.path()), ["icon-processing.png", "icon-error.png"]);
+        assert!(load_icon(dir.path(), TrayState::Error).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Voice activity detection
+//!
+//! Trims leading and trailing silence from recordings before transcription,
+//! and detects end of speech on live capture so recording can stop itself.
+//! Both use per-frame average amplitude as a cheap energy measure.
+
+use std::io::Cursor;
+
+use hound::{SampleFormat, WavReader, WavWriter};
+use tracing::debug;
+
+/// Length of one analysis frame
+const FRAME_MS: u32 = 10;
+
+/// Speech boundary reported by [`EndpointDetector`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEvent {
+    SpeechStarted,
+    SpeechEnded,
+}
+
+/// Streaming end-of-speech detector fed with captured samples as they arrive
+pub struct EndpointDetector {
+    threshold: i64,
+    frame_len: usize,
+    min_speech_frames: usize,
+    silence_frames: usize,
+    /// Samples left over from the last push that do not fill a frame yet
+    pending: Vec<i16>,
+    loud_run: usize,
+    quiet_run: usize,
+    speaking: bool,
+}
+
+impl EndpointDetector {
+    /// Speech starts after `min_speech_ms` of loud frames and ends after
+    /// `silence_ms` of quiet ones; interleaved channels are measured together.
+    pub fn new(threshold: i16, min_speech_ms: u32, silence_ms: u32, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            threshold: threshold as i64,
+            frame_len: ((sample_rate * FRAME_MS / 1000) as usize * channels.max(1) as usize).max(1),
+            min_speech_frames: (min_speech_ms / FRAME_MS).max(1) as usize,
+            silence_frames: (silence_ms / FRAME_MS).max(1) as usize,
+            pending: Vec::new(),
+            loud_run: 0,
+            quiet_run: 0,
+            speaking: false,
+        }
+    }
+
+    /// Feed newly captured samples, returning the first boundary they cross
+    pub fn push(&mut self, samples: &[i16]) -> Option<VadEvent> {
+        self.pending.extend_from_slice(samples);
+        let mut event = None;
+        let mut consumed = 0;
+
+        for frame in self.pending.chunks_exact(self.frame_len) {
+            consumed += frame.len();
+            let energy = frame.iter().map(|&s| (s as i64).abs()).sum::<i64>() / frame.len() as i64;
+            if energy > self.threshold {
+                self.loud_run += 1;
+                self.quiet_run = 0;
+            } else {
+                self.quiet_run += 1;
+                self.loud_run = 0;
+            }
+
+            if !self.speaking && self.loud_run >= self.min_speech_frames {
+                self.speaking = true;
+                event = Some(VadEvent::SpeechStarted);
+                break;
+            }
+            if self.speaking && self.quiet_run >= self.silence_frames {
+                self.speaking = false;
+                event = Some(VadEvent::SpeechEnded);
+                break;
+            }
+        }
+
+        self.pending.drain(..consumed);
+        event
+    }
+}
+
+/// Return the tightest slice of `samples` that contains speech
+///
+/// A frame counts as speech when its mean absolute amplitude exceeds
+/// `threshold`; runs shorter than `min_speech_ms` (clicks, pops) are ignored.
+/// Returns an empty slice when no speech is found.
+pub fn trim_silence(samples: &[i16], threshold: i16, min_speech_ms: u32, sample_rate: u32) -> &[i16] {
+    let frame_len = ((sample_rate * FRAME_MS / 1000) as usize).max(1);
+    let min_frames = (min_speech_ms / FRAME_MS).max(1) as usize;
+
+    let loud: Vec<bool> = samples
+        .chunks(frame_len)
+        .map(|frame| {
+            let energy = frame.iter().map(|&s| (s as i64).abs()).sum::<i64>() / frame.len() as i64;
+            energy > threshold as i64
+        })
+        .collect();
+
+    // Collect runs of loud frames long enough to count as speech
+    let mut first = None;
+    let mut last = None;
+    let mut run_start = 0;
+    for (i, &is_loud) in loud.iter().chain(std::iter::once(&false)).enumerate() {
+        if is_loud {
+            if i == 0 || !loud[i - 1] {
+                run_start = i;
+            }
+        } else if i > 0 && loud.get(i - 1) == Some(&true) && i - run_start >= min_frames {
+            first.get_or_insert(run_start);
+            last = Some(i);
+        }
+    }
+
+    match (first, last) {
+        (Some(first), Some(last)) => {
+            let start = first * frame_len;
+            let end = (last * frame_len).min(samples.len());
+            &samples[start..end]
+        }
+        _ => &samples[..0],
+    }
+}
+
+/// Trim silence from a mono 16-bit PCM WAV buffer
+///
+/// Returns `None` for other encodings, unreadable files, or recordings with
+/// no detectable speech, in which case the original audio should be used.
+pub fn trim_wav(data: &[u8], threshold: i16, min_speech_ms: u32) -> Option<Vec<u8>> {
+    let reader = WavReader::new(Cursor::new(data)).ok()?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.bits_per_sample != 16 || spec.sample_format != SampleFormat::Int {
+        return None;
+    }
+
+    let samples: Vec<i16> = reader.into_samples::<i16>().collect::<Result<_, _>>().ok()?;
+    let speech = trim_silence(&samples, threshold, min_speech_ms, spec.sample_rate);
+    if speech.is_empty() {
+        debug!("No speech detected, sending audio untrimmed");
+        return None;
+    }
+    debug!("Trimmed {} of {} samples of silence", samples.len() - speech.len(), samples.len());
+
+    let mut out = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut out, spec).ok()?;
+    for &sample in speech {
+        writer.write_sample(sample).ok()?;
+    }
+    writer.finalize().ok()?;
+    Some(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use hound::WavSpec;
+
+    use super::*;
+
+    const RATE: u32 = 16_000;
+    const THRESHOLD: i16 = 500;
+
+    /// `ms` of a 200 Hz square wave at `amplitude`
+    fn tone(ms: u32, amplitude: i16) -> Vec<i16> {
+        (0..RATE * ms / 1000)
+            .map(|i| if (i / 40) % 2 == 0 { amplitude } else { -amplitude })
+            .collect()
+    }
+
+    fn wav(samples: &[i16], channels: u16) -> Vec<u8> {
+        let spec = WavSpec {
+            channels,
+            sample_rate: RATE,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut out = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut out, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn trims_silence_around_speech() {
+        let speech = tone(500, 4000);
+        let samples = [tone(300, 20), speech.clone(), tone(200, 20)].concat();
+
+        assert_eq!(trim_silence(&samples, THRESHOLD, 50, RATE), &speech[..]);
+    }
+
+    #[test]
+    fn ignores_clicks_shorter_than_min_speech() {
+        let click = tone(20, 8000);
+        let samples = [tone(100, 0), click, tone(300, 0), tone(200, 4000), tone(100, 0)].concat();
+
+        let trimmed = trim_silence(&samples, THRESHOLD, 50, RATE);
+
+        assert_eq!(trimmed.len(), tone(200, 0).len());
+        assert!(trim_silence(&tone(500, 20), THRESHOLD, 50, RATE).is_empty());
+    }
+
+    #[test]
+    fn trim_wav_keeps_only_speech() {
+        let samples = [tone(300, 0), tone(400, 4000), tone(300, 0)].concat();
+
+        let trimmed = trim_wav(&wav(&samples, 1), THRESHOLD, 50).unwrap();
+
+        let reader = WavReader::new(Cursor::new(trimmed)).unwrap();
+        assert_eq!(reader.spec().sample_rate, RATE);
+        assert_eq!(reader.len(), RATE * 400 / 1000);
+    }
+
+    #[test]
+    fn trim_wav_skips_stereo_and_silent_audio() {
+        assert!(trim_wav(&wav(&tone(500, 4000), 2), THRESHOLD, 50).is_none());
+        assert!(trim_wav(&wav(&tone(500, 0), 1), THRESHOLD, 50).is_none());
+        assert!(trim_wav(b"not a wav", THRESHOLD, 50).is_none());
+    }
+
+    #[test]
+    fn endpoint_detector_reports_start_then_end() {
+        let mut detector = EndpointDetector::new(THRESHOLD, 50, 300, RATE, 1);
+
+        assert_eq!(detector.push(&tone(200, 0)), None);
+        assert_eq!(detector.push(&tone(200, 4000)), Some(VadEvent::SpeechStarted));
+        assert_eq!(detector.push(&tone(200, 0)), None);
+        assert_eq!(detector.push(&tone(200, 0)), Some(VadEvent::SpeechEnded));
+    }
+}
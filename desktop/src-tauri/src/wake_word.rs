@@ -0,0 +1,146 @@
+//! Wake-word detection
+//!
+//! Listens on the microphone in the background and asks the agent whether
+//! each 500 ms chunk contains the configured keyword. Detection pauses while
+//! the app is already listening so a wake word cannot re-trigger a recording.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::agent::CAPABILITY_WAKE_WORD;
+use crate::devices::{self, DeviceKind};
+use crate::error::SlovoError;
+use crate::recording::{self, CaptureFormat, RecordingState};
+use crate::state::AppState;
+use crate::tray::{TrayManager, TrayState};
+
+/// Length of audio sent to the agent per check
+const CHUNK_DURATION: Duration = Duration::from_millis(500);
+
+/// Payload of `wake-word-detected`
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeWordDetected {
+    pub keyword: String,
+    pub confidence: Option<f32>,
+}
+
+impl WakeWordDetected {
+    pub const EVENT: &'static str = "wake-word-detected";
+}
+
+/// Managed handle to the background detection loop
+#[derive(Default)]
+pub struct WakeWordDetector {
+    active: Mutex<Option<CancellationToken>>,
+}
+
+impl WakeWordDetector {
+    /// Start listening for `keyword`, replacing any detector already running
+    pub async fn start(&self, handle: AppHandle, keyword: String) -> Result<(), SlovoError> {
+        AppState::of(&handle).client.require(CAPABILITY_WAKE_WORD).await?;
+        self.stop();
+
+        let device_id = devices::saved_device(&handle, DeviceKind::Input).await;
+        let capture = CaptureFormat::from_config(&AppState::of(&handle).config.read().unwrap());
+
+        // The cpal stream is not Send, so a dedicated thread keeps it open
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let buffer = samples.clone();
+        std::thread::spawn(move || {
+            let stream = match recording::open_stream(device_id.as_deref(), capture, buffer) {
+                Ok((stream, config)) => {
+                    let _ = ready_tx.send(Ok(config));
+                    stream
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = stop_rx.recv();
+            drop(stream);
+        });
+
+        let config = ready_rx
+            .await
+            .map_err(|_| SlovoError::VoiceError("Wake-word thread exited unexpectedly".to_string()))??;
+
+        let token = CancellationToken::new();
+        *self.active.lock().unwrap() = Some(token.clone());
+        info!("Wake-word detection started for \"{}\"", keyword);
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHUNK_DURATION);
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+
+                let chunk = std::mem::take(&mut *samples.lock().unwrap());
+                if chunk.is_empty() || is_listening(&handle) {
+                    continue;
+                }
+
+                let audio = match recording::encode_wav(&chunk, config.sample_rate.0, config.channels) {
+                    Ok(audio) => audio,
+                    Err(e) => {
+                        warn!("Failed to encode wake-word chunk: {}", e);
+                        continue;
+                    }
+                };
+                let client = &AppState::of(&handle).client;
+                match client.detect_wake_word(audio, &keyword).await {
+                    Ok(result) if result.detected => {
+                        info!("Wake word \"{}\" detected", keyword);
+                        let _ = handle.emit(
+                            WakeWordDetected::EVENT,
+                            WakeWordDetected {
+                                keyword: keyword.clone(),
+                                confidence: result.confidence,
+                            },
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Wake-word check failed: {}", e),
+                }
+            }
+
+            let _ = stop_tx.send(());
+            info!("Wake-word detection stopped");
+        });
+        Ok(())
+    }
+
+    /// Stop the detection loop; returns false if it was not running
+    pub fn stop(&self) -> bool {
+        match self.active.lock().unwrap().take() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the detection loop is running
+    pub fn is_running(&self) -> bool {
+        self.active.lock().unwrap().is_some()
+    }
+}
+
+/// The app is already capturing voice input, so a wake word would double-activate
+fn is_listening(app: &AppHandle) -> bool {
+    app.state::<RecordingState>().is_recording()
+        || app.state::<TrayManager>().state() == TrayState::Listening
+}
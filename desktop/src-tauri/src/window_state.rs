@@ -0,0 +1,264 @@
+//! Main window position and size persistence
+//!
+//! Geometry is saved to `window_state.json` in the app data directory while the
+//! window is moved or resized, and restored on the next launch. Mini mode
+//! shrinks the window to a strip in the corner of the screen and is not saved.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{LogicalSize, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+use tracing::{info, warn};
+
+use crate::error::SlovoError;
+
+/// State file name inside the app data directory
+const STATE_FILE: &str = "window_state.json";
+
+/// Quiet period after the last move or resize before the state is written
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Mini mode window size, in logical pixels
+const MINI_WIDTH: f64 = 400.0;
+const MINI_HEIGHT: f64 = 120.0;
+/// Gap between the mini window and the screen edges, in logical pixels
+const MINI_MARGIN: f64 = 16.0;
+/// Minimum size of the normal window, matching `tauri.conf.json`
+const MIN_WIDTH: f64 = 400.0;
+const MIN_HEIGHT: f64 = 500.0;
+
+/// Most transparent the window may be made, so it never becomes impossible to find
+pub const MIN_OPACITY: f64 = 0.3;
+
+/// Managed record of whether the window is in mini mode
+#[derive(Default)]
+pub struct WindowModeState {
+    /// Geometry to return to, present while in mini mode
+    saved: Mutex<Option<WindowState>>,
+}
+
+impl WindowModeState {
+    pub fn in_mini_mode(&self) -> bool {
+        self.saved.lock().unwrap().is_some()
+    }
+}
+
+/// Saved geometry of the main window, in physical pixels
+///
+/// While maximized, the position and size are those the window returns to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+impl WindowState {
+    /// Read the saved state; `None` if there is none or it is unreadable
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                warn!("Ignoring unreadable window state: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Write the state, creating parent directories
+    pub fn save(&self, path: &Path) -> Result<(), SlovoError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|e| SlovoError::ConfigError(e.to_string()))?;
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Current geometry of the window
+    fn capture(window: &WebviewWindow) -> Option<Self> {
+        let position = window.outer_position().ok()?;
+        let size = window.inner_size().ok()?;
+        Some(Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized: window.is_maximized().unwrap_or(false),
+        })
+    }
+
+    /// Move the window onto the first monitor if its title bar would be on none of them
+    ///
+    /// Happens when the monitor it was last on has been disconnected.
+    fn clamp_to_monitors(mut self, monitors: &[Monitor]) -> Self {
+        let anchor_x = self.x.saturating_add((self.width / 2) as i32);
+        let visible = monitors.iter().any(|monitor| {
+            let origin = monitor.position();
+            let size = monitor.size();
+            anchor_x >= origin.x
+                && anchor_x < origin.x.saturating_add(size.width as i32)
+                && self.y >= origin.y
+                && self.y < origin.y.saturating_add(size.height as i32)
+        });
+        if visible {
+            return self;
+        }
+
+        if let Some(monitor) = monitors.first() {
+            let origin = monitor.position();
+            let size = monitor.size();
+            self.width = self.width.min(size.width);
+            self.height = self.height.min(size.height);
+            self.x = origin.x + ((size.width - self.width) / 2) as i32;
+            self.y = origin.y + ((size.height - self.height) / 2) as i32;
+        }
+        self
+    }
+}
+
+/// Location of the state file for this app
+fn state_path(window: &WebviewWindow) -> Result<PathBuf, SlovoError> {
+    Ok(window.path().app_data_dir()?.join(STATE_FILE))
+}
+
+/// Apply the saved geometry to the window, keeping it on a connected monitor
+pub fn restore(window: &WebviewWindow) {
+    let Ok(path) = state_path(window) else { return };
+    let Some(state) = WindowState::load(&path) else { return };
+
+    apply(window, state);
+    info!("Restored window state {:?}", state);
+}
+
+fn apply(window: &WebviewWindow, state: WindowState) {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let state = state.clamp_to_monitors(&monitors);
+
+    let _ = window.set_size(PhysicalSize::new(state.width, state.height));
+    let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Apply the configured window opacity
+///
+/// Tauri has no per-window alpha on any platform yet, so a translucent setting
+/// is kept in the config but the window itself stays opaque.
+pub fn apply_opacity(opacity: f64) {
+    if opacity < 1.0 {
+        warn!(
+            "Window opacity {:.2} is not supported on this platform; the window stays opaque",
+            opacity
+        );
+    }
+}
+
+/// Shrink the window to a compact strip at the top right of the primary monitor
+///
+/// Returns false if it was already in mini mode.
+pub fn enter_mini_mode(window: &WebviewWindow) -> Result<bool, SlovoError> {
+    let modes = window.state::<WindowModeState>();
+    let mut saved = modes.saved.lock().unwrap();
+    if saved.is_some() {
+        return Ok(false);
+    }
+    let state = WindowState::capture(window)
+        .ok_or_else(|| SlovoError::WindowError("Failed to read window geometry".to_string()))?;
+
+    if state.maximized {
+        window.unmaximize()?;
+    }
+    window.set_min_size(None::<LogicalSize<f64>>)?;
+    window.set_size(LogicalSize::new(MINI_WIDTH, MINI_HEIGHT))?;
+    if let Some(monitor) = window.primary_monitor()? {
+        let scale = monitor.scale_factor();
+        let origin = monitor.position();
+        let right = origin.x + monitor.size().width as i32;
+        let x = right - ((MINI_WIDTH + MINI_MARGIN) * scale) as i32;
+        let y = origin.y + (MINI_MARGIN * scale) as i32;
+        window.set_position(PhysicalPosition::new(x, y))?;
+    }
+
+    *saved = Some(state);
+    Ok(true)
+}
+
+/// Return the window to the geometry it had before entering mini mode
+///
+/// Returns false if it was not in mini mode.
+pub fn exit_mini_mode(window: &WebviewWindow) -> Result<bool, SlovoError> {
+    let modes = window.state::<WindowModeState>();
+    let Some(state) = modes.saved.lock().unwrap().take() else {
+        return Ok(false);
+    };
+
+    window.set_min_size(Some(LogicalSize::new(MIN_WIDTH, MIN_HEIGHT)))?;
+    apply(window, state);
+    Ok(true)
+}
+
+/// Save the window's geometry whenever it is moved or resized, debounced
+pub fn track(window: &WebviewWindow) {
+    let path = match state_path(window) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("Window state will not be saved: {}", e);
+            return;
+        }
+    };
+    let path = Arc::new(path);
+    let last = Arc::new(Mutex::new(WindowState::load(&path)));
+    let generation = Arc::new(AtomicU64::new(0));
+
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if !matches!(event, WindowEvent::Resized(_) | WindowEvent::Moved(_)) {
+            return;
+        }
+
+        let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let (window, path, last, generation) =
+            (handle.clone(), path.clone(), last.clone(), generation.clone());
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DEBOUNCE).await;
+            // A later event restarted the wait
+            if generation.load(Ordering::SeqCst) != current {
+                return;
+            }
+            save_current(&window, &path, &last);
+        });
+    });
+}
+
+/// Capture and persist the window's geometry if it changed
+fn save_current(window: &WebviewWindow, path: &Path, last: &Mutex<Option<WindowState>>) {
+    // Minimized windows report meaningless coordinates, and mini mode is temporary
+    if window.is_minimized().unwrap_or(false) || window.state::<WindowModeState>().in_mini_mode() {
+        return;
+    }
+    let Some(current) = WindowState::capture(window) else { return };
+    let mut last = last.lock().unwrap();
+
+    let state = match *last {
+        // Keep the restored geometry so un-maximizing after a restart still works
+        Some(previous) if current.maximized => WindowState { maximized: true, ..previous },
+        _ => current,
+    };
+    if *last == Some(state) {
+        return;
+    }
+
+    match state.save(path) {
+        Ok(()) => *last = Some(state),
+        Err(e) => warn!("Failed to save window state: {}", e),
+    }
+}
@@ -0,0 +1,456 @@
+//! Agent WebSocket streaming module
+//!
+//! Maintains a persistent WebSocket connection to the agent runtime so chat
+//! responses can be streamed back chunk by chunk, with reasoning and answer
+//! text emitted as separate events. The connection reconnects
+//! with exponential backoff and resumes any response that was cut off mid-stream.
+//! A response that cannot be resumed is saved to history as far as it got.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{info, warn};
+
+use crate::agent::{AgentClient, ChatRequest, ChatResponse};
+use crate::conversation::ConversationManager;
+use crate::error::SlovoError;
+use crate::events::{emit_agent_status, AgentStatus};
+
+/// Stream connection configuration
+pub(crate) const PING_INTERVAL: Duration = Duration::from_secs(20);
+pub(crate) const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+pub(crate) const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Frames sent to the agent
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OutgoingFrame {
+    Chat(ChatRequest),
+    Cancel { conversation_id: String },
+    Resume {
+        conversation_id: String,
+        from_index: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        resume_token: Option<String>,
+    },
+}
+
+/// Frames received from the agent
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum IncomingFrame {
+    Chunk(StreamChunk),
+    Response(ChatResponse),
+    Error(StreamError),
+}
+
+/// Which part of the response a chunk belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkKind {
+    /// Chain-of-thought from a reasoning model
+    Reasoning,
+    #[default]
+    Answer,
+}
+
+/// Incremental piece of a streamed response
+///
+/// Reasoning and answer chunks share one index sequence per conversation,
+/// so the frontend can interleave them in the order the agent produced them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamChunk {
+    pub conversation_id: String,
+    pub index: u64,
+    pub content: String,
+    #[serde(default)]
+    pub kind: ChunkKind,
+    /// Lets the response continue on a new connection, from agents that support it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
+}
+
+/// Emitted as `chat-complete` once a streamed response has finished
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatComplete {
+    pub id: String,
+    pub conversation_id: String,
+    /// Whether any reasoning was streamed or returned with the response
+    pub has_reasoning: bool,
+}
+
+/// Emitted as `chat-interrupted` when the connection drops mid-response
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatInterrupted {
+    pub conversation_id: String,
+    /// Answer text received so far
+    pub partial: String,
+    /// Pass to `resume_stream` to continue the response, when the agent gave one
+    pub resume_token: Option<String>,
+}
+
+/// Progress of a response still being streamed
+#[derive(Debug, Default)]
+struct InFlight {
+    /// Index of the next chunk expected
+    next_index: u64,
+    has_reasoning: bool,
+    /// The user message being answered, when known
+    message: Option<String>,
+    /// Answer text received so far
+    partial: String,
+    resume_token: Option<String>,
+}
+
+/// Error reported by the agent for a streamed request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamError {
+    pub conversation_id: Option<String>,
+    pub message: String,
+}
+
+/// Handle to the background stream connection
+///
+/// Cloning the handle is cheap; the connection shuts down once every handle is dropped.
+#[derive(Clone)]
+pub struct AgentStream {
+    tx: mpsc::UnboundedSender<OutgoingFrame>,
+}
+
+impl AgentStream {
+    /// Spawn the connection task for the given WebSocket URL
+    pub(crate) fn spawn(url: String, auth_token: Option<String>, app: AppHandle) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(run_stream(url, auth_token, app, rx));
+        Self { tx }
+    }
+
+    /// Send a chat message; the response arrives as stream events
+    pub fn send(&self, request: ChatRequest) -> Result<(), SlovoError> {
+        self.push(OutgoingFrame::Chat(request))
+    }
+
+    /// Ask the agent to continue a response that was cut off
+    pub fn resume(
+        &self,
+        conversation_id: &str,
+        resume_token: Option<String>,
+    ) -> Result<(), SlovoError> {
+        // The connection task fills in how far the response got
+        self.push(OutgoingFrame::Resume {
+            conversation_id: conversation_id.to_string(),
+            from_index: 0,
+            resume_token,
+        })
+    }
+
+    /// Ask the agent to stop streaming the response for a conversation
+    pub fn cancel(&self, conversation_id: &str) -> Result<(), SlovoError> {
+        self.push(OutgoingFrame::Cancel {
+            conversation_id: conversation_id.to_string(),
+        })
+    }
+
+    fn push(&self, frame: OutgoingFrame) -> Result<(), SlovoError> {
+        self.tx
+            .send(frame)
+            .map_err(|_| SlovoError::AgentConnection("Agent stream is closed".to_string()))
+    }
+}
+
+/// Managed slot holding the lazily opened agent stream
+#[derive(Default)]
+pub struct AgentStreamState(Mutex<Option<AgentStream>>);
+
+impl AgentStreamState {
+    /// Get the open stream, connecting on first use
+    pub fn get_or_connect(&self, app: &AppHandle, client: &AgentClient) -> AgentStream {
+        let mut slot = self.0.lock().unwrap();
+        slot.get_or_insert_with(|| client.connect_ws(app.clone()))
+            .clone()
+    }
+
+    /// Drop the stream handle, closing the connection
+    pub fn close(&self) -> bool {
+        self.0.lock().unwrap().take().is_some()
+    }
+}
+
+/// Connection loop: connect, pump frames, and reconnect with backoff on drop
+async fn run_stream(
+    url: String,
+    auth_token: Option<String>,
+    app: AppHandle,
+    mut rx: mpsc::UnboundedReceiver<OutgoingFrame>,
+) {
+    // Next expected chunk index for every response still in flight
+    let mut in_flight: HashMap<String, InFlight> = HashMap::new();
+    // Messages that start a new conversation, until their first chunk names it
+    let mut unassigned: VecDeque<String> = VecDeque::new();
+    // Frames submitted while disconnected, sent once the socket is back
+    let mut pending: VecDeque<OutgoingFrame> = VecDeque::new();
+    let mut delay = RECONNECT_BASE_DELAY;
+
+    loop {
+        match connect(&url, auth_token.as_deref()).await {
+            Ok(socket) => {
+                info!("Agent stream connected");
+                delay = RECONNECT_BASE_DELAY;
+                emit_agent_status(&app, AgentStatus::Connected);
+
+                let (mut sink, mut source) = socket.split();
+                let mut ping = tokio::time::interval(PING_INTERVAL);
+
+                // Responses that never got a chunk are answered by the replayed request
+                let resumes = in_flight
+                    .iter()
+                    .filter(|(_, progress)| progress.next_index > 0)
+                    .map(|(id, progress)| OutgoingFrame::Resume {
+                        conversation_id: id.clone(),
+                        from_index: progress.next_index,
+                        resume_token: progress.resume_token.clone(),
+                    });
+                let backlog: Vec<OutgoingFrame> = resumes.chain(pending.drain(..)).collect();
+                let mut healthy = true;
+                for frame in &backlog {
+                    if let Err(e) = send_frame(&mut sink, frame).await {
+                        warn!("Failed to replay frame on agent stream: {}", e);
+                        healthy = false;
+                        break;
+                    }
+                }
+
+                while healthy {
+                    tokio::select! {
+                        frame = rx.recv() => match frame {
+                            Some(mut frame) => {
+                                track_outgoing(&mut frame, &mut in_flight, &mut unassigned);
+                                if let Err(e) = send_frame(&mut sink, &frame).await {
+                                    warn!("Failed to send on agent stream: {}", e);
+                                    pending.push_back(frame);
+                                    healthy = false;
+                                }
+                            }
+                            None => {
+                                let _ = sink.close().await;
+                                info!("Agent stream closed");
+                                preserve_partials(&app, in_flight);
+                                return;
+                            }
+                        },
+                        message = source.next() => match message {
+                            Some(Ok(Message::Text(text))) => {
+                                handle_frame(&app, &text, &mut in_flight, &mut unassigned)
+                            }
+                            Some(Ok(Message::Close(_))) | None => healthy = false,
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                warn!("Agent stream error: {}", e);
+                                healthy = false;
+                            }
+                        },
+                        _ = ping.tick() => {
+                            if sink.send(Message::Ping(Default::default())).await.is_err() {
+                                healthy = false;
+                            }
+                        }
+                    }
+                }
+
+                warn!("Agent stream disconnected, reconnecting in {:?}", delay);
+                emit_agent_status(&app, AgentStatus::Disconnected);
+                for (id, progress) in in_flight.iter().filter(|(_, p)| p.next_index > 0) {
+                    let interrupted = ChatInterrupted {
+                        conversation_id: id.clone(),
+                        partial: progress.partial.clone(),
+                        resume_token: progress.resume_token.clone(),
+                    };
+                    let _ = app.emit("chat-interrupted", interrupted);
+                }
+            }
+            Err(e) => warn!("Agent stream connection failed: {}", e),
+        }
+
+        // Wait out the backoff, buffering anything the frontend sends meanwhile
+        let backoff = tokio::time::sleep(delay);
+        tokio::pin!(backoff);
+        loop {
+            tokio::select! {
+                _ = &mut backoff => break,
+                frame = rx.recv() => match frame {
+                    Some(mut frame) => {
+                        track_outgoing(&mut frame, &mut in_flight, &mut unassigned);
+                        pending.push_back(frame);
+                    }
+                    None => {
+                        preserve_partials(&app, in_flight);
+                        return;
+                    }
+                },
+            }
+        }
+        delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+    }
+}
+
+/// Open the socket, authenticating the handshake when a token is configured
+pub(crate) async fn connect(url: &str, auth_token: Option<&str>) -> Result<WsStream, WsError> {
+    let mut request = url.into_client_request()?;
+    if let Some(token) = auth_token {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|e| WsError::HttpFormat(e.into()))?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+    connect_async(request).await.map(|(socket, _)| socket)
+}
+
+async fn send_frame<S>(sink: &mut S, frame: &OutgoingFrame) -> Result<(), SlovoError>
+where
+    S: SinkExt<Message> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    let payload = serde_json::to_string(frame).map_err(|e| SlovoError::agent(e.to_string()))?;
+    sink.send(Message::text(payload))
+        .await
+        .map_err(|e| SlovoError::AgentConnection(e.to_string()))
+}
+
+/// Update response tracking for a frame about to go to the agent
+fn track_outgoing(
+    frame: &mut OutgoingFrame,
+    in_flight: &mut HashMap<String, InFlight>,
+    unassigned: &mut VecDeque<String>,
+) {
+    match frame {
+        OutgoingFrame::Chat(request) => match &request.conversation_id {
+            Some(id) => {
+                let progress = InFlight {
+                    message: Some(request.message.clone()),
+                    ..InFlight::default()
+                };
+                in_flight.insert(id.clone(), progress);
+            }
+            None => unassigned.push_back(request.message.clone()),
+        },
+        OutgoingFrame::Cancel { conversation_id } => {
+            in_flight.remove(conversation_id);
+        }
+        OutgoingFrame::Resume {
+            conversation_id,
+            from_index,
+            ..
+        } => {
+            if let Some(progress) = in_flight.get(conversation_id) {
+                *from_index = progress.next_index;
+            }
+        }
+    }
+}
+
+/// Save partial replies that can no longer be resumed to their conversations
+fn preserve_partials(app: &AppHandle, in_flight: HashMap<String, InFlight>) {
+    for (id, progress) in in_flight {
+        preserve_partial(app, &id, progress);
+    }
+}
+
+fn preserve_partial(app: &AppHandle, conversation_id: &str, progress: InFlight) {
+    if progress.partial.is_empty() {
+        return;
+    }
+    if let Err(e) = app.state::<ConversationManager>().record_interrupted(
+        conversation_id,
+        progress.message.as_deref(),
+        &progress.partial,
+    ) {
+        warn!("Failed to save interrupted response: {}", e);
+    }
+}
+
+/// Route an incoming frame to the matching frontend event
+fn handle_frame(
+    app: &AppHandle,
+    text: &str,
+    in_flight: &mut HashMap<String, InFlight>,
+    unassigned: &mut VecDeque<String>,
+) {
+    let frame = match serde_json::from_str::<IncomingFrame>(text) {
+        Ok(frame) => frame,
+        Err(e) => {
+            warn!("Ignoring malformed agent stream frame: {}", e);
+            return;
+        }
+    };
+
+    match frame {
+        IncomingFrame::Chunk(chunk) => {
+            let progress = in_flight
+                .entry(chunk.conversation_id.clone())
+                .or_insert_with(|| InFlight {
+                    message: unassigned.pop_front(),
+                    ..InFlight::default()
+                });
+            // A resumed stream may repeat chunks we already forwarded
+            if chunk.index < progress.next_index {
+                return;
+            }
+            progress.next_index = chunk.index + 1;
+            if chunk.resume_token.is_some() {
+                progress.resume_token = chunk.resume_token.clone();
+            }
+            let event = match chunk.kind {
+                ChunkKind::Reasoning => {
+                    progress.has_reasoning = true;
+                    "reasoning-chunk"
+                }
+                ChunkKind::Answer => {
+                    progress.partial.push_str(&chunk.content);
+                    "answer-chunk"
+                }
+            };
+            let _ = app.emit(event, chunk);
+        }
+        IncomingFrame::Response(response) => {
+            let streamed_reasoning = in_flight
+                .remove(&response.conversation_id)
+                .is_some_and(|progress| progress.has_reasoning);
+            // Reasoning that was not streamed still arrives bundled with the response
+            let reasoning = if streamed_reasoning {
+                None
+            } else {
+                response.reasoning_chunk()
+            };
+            if let Some(reasoning) = &reasoning {
+                let _ = app.emit("agent-reasoning-chunk", reasoning);
+            }
+            let complete = ChatComplete {
+                id: response.id.clone(),
+                conversation_id: response.conversation_id.clone(),
+                has_reasoning: streamed_reasoning || reasoning.is_some(),
+            };
+            let _ = app.emit("agent-stream-response", response);
+            let _ = app.emit("chat-complete", complete);
+        }
+        IncomingFrame::Error(error) => {
+            // Includes an agent refusing to resume, so keep what did arrive
+            if let Some(id) = &error.conversation_id {
+                if let Some(progress) = in_flight.remove(id) {
+                    preserve_partial(app, id, progress);
+                }
+            }
+            let _ = app.emit("agent-stream-error", error);
+        }
+    }
+}
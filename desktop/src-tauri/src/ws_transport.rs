@@ -0,0 +1,360 @@
+//! Agent requests over a persistent WebSocket
+//!
+//! Only built with the `ws-transport` feature. Unlike the chat stream in
+//! `ws`, this connection carries request/response pairs matched by `id`, and
+//! lets the agent push frames of its own (for example a finished background
+//! task) without being polled. Pushed frames go to any `subscribe`rs of their
+//! `type` and to the frontend as `agent-push` events.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::agent::{AgentHealth, AgentTransport, ChatOptions, ChatRequest, ChatResponse};
+use crate::config::AgentConfig;
+use crate::error::SlovoError;
+use crate::ws::{self, PING_INTERVAL, RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY};
+
+/// How long a request waits for its response frame
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Responder = oneshot::Sender<Result<Value, SlovoError>>;
+
+/// Request awaiting a response, and frame routing shared with the connection task
+#[derive(Default)]
+struct Routes {
+    pending: Mutex<HashMap<String, Responder>>,
+    subscribers: Mutex<HashMap<String, Vec<mpsc::UnboundedSender<Value>>>>,
+}
+
+impl Routes {
+    fn respond(&self, id: &str, result: Result<Value, SlovoError>) -> bool {
+        match self.pending.lock().unwrap().remove(id) {
+            Some(responder) => {
+                let _ = responder.send(result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fail every waiting request, since its response can no longer arrive
+    fn fail_all(&self, reason: &str) {
+        for (_, responder) in self.pending.lock().unwrap().drain() {
+            let _ = responder.send(Err(SlovoError::AgentConnection(reason.to_string())));
+        }
+    }
+
+    /// Hand a pushed frame to the subscribers of its type, dropping closed ones
+    fn publish(&self, kind: &str, frame: &Value) {
+        if let Some(senders) = self.subscribers.lock().unwrap().get_mut(kind) {
+            senders.retain(|sender| sender.send(frame.clone()).is_ok());
+        }
+    }
+}
+
+/// Agent client that talks over one long-lived WebSocket
+///
+/// Reconnects with exponential backoff; requests made while disconnected fail
+/// immediately with `AgentConnection`.
+pub struct WebSocketAgentClient {
+    tx: mpsc::UnboundedSender<Value>,
+    routes: Arc<Routes>,
+}
+
+impl WebSocketAgentClient {
+    /// Connect in the background to the agent's `/ws` endpoint
+    pub fn connect(app: AppHandle, config: &AgentConfig) -> Self {
+        let url = format!("ws://{}:{}/ws", config.host, config.port);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let routes = Arc::new(Routes::default());
+        tauri::async_runtime::spawn(run_connection(
+            url,
+            config.auth_token.clone(),
+            app,
+            routes.clone(),
+            rx,
+        ));
+        Self { tx, routes }
+    }
+
+    /// Receive every pushed frame whose `type` is `kind`
+    pub fn subscribe(&self, kind: &str) -> mpsc::UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.routes
+            .subscribers
+            .lock()
+            .unwrap()
+            .entry(kind.to_string())
+            .or_default()
+            .push(tx);
+        rx
+    }
+
+    /// Send a frame tagged with a fresh `id` and decode the frame answering it
+    async fn request<T: DeserializeOwned>(&self, mut frame: Value) -> Result<T, SlovoError> {
+        let id = Uuid::new_v4().to_string();
+        frame["id"] = Value::String(id.clone());
+
+        let (responder, response) = oneshot::channel();
+        self.routes.pending.lock().unwrap().insert(id.clone(), responder);
+        if self.tx.send(frame).is_err() {
+            self.routes.pending.lock().unwrap().remove(&id);
+            return Err(SlovoError::AgentConnection("Agent connection is closed".to_string()));
+        }
+
+        let value = match tokio::time::timeout(RESPONSE_TIMEOUT, response).await {
+            Ok(Ok(result)) => result?,
+            Ok(Err(_)) => {
+                return Err(SlovoError::AgentConnection("Agent connection is closed".to_string()));
+            }
+            Err(_) => {
+                self.routes.pending.lock().unwrap().remove(&id);
+                return Err(SlovoError::Timeout("Agent did not respond in time".to_string()));
+            }
+        };
+        serde_json::from_value(value.clone())
+            .map_err(|e| SlovoError::parse(e, value.to_string().as_bytes()))
+    }
+}
+
+#[async_trait]
+impl AgentTransport for WebSocketAgentClient {
+    async fn health_check(&self) -> Result<AgentHealth, SlovoError> {
+        self.request(json!({ "type": "health_check" })).await
+    }
+
+    async fn send_message(
+        &self,
+        message: &str,
+        conversation_id: Option<&str>,
+        options: &ChatOptions,
+    ) -> Result<ChatResponse, SlovoError> {
+        let request = ChatRequest::new(message, conversation_id.map(|s| s.to_string()), options);
+        let mut frame =
+            serde_json::to_value(&request).map_err(|e| SlovoError::agent(e.to_string()))?;
+        frame["type"] = Value::String("chat".to_string());
+        self.request(frame).await
+    }
+}
+
+/// Connection loop: connect, exchange frames, and reconnect with backoff on drop
+async fn run_connection(
+    url: String,
+    auth_token: Option<String>,
+    app: AppHandle,
+    routes: Arc<Routes>,
+    mut rx: mpsc::UnboundedReceiver<Value>,
+) {
+    let mut delay = RECONNECT_BASE_DELAY;
+    loop {
+        match ws::connect(&url, auth_token.as_deref()).await {
+            Ok(socket) => {
+                info!("Agent WebSocket transport connected");
+                delay = RECONNECT_BASE_DELAY;
+                let (mut sink, mut source) = socket.split();
+                let mut ping = tokio::time::interval(PING_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        frame = rx.recv() => {
+                            let Some(frame) = frame else {
+                                let _ = sink.close().await;
+                                routes.fail_all("Agent connection is closed");
+                                return;
+                            };
+                            if let Err(e) = sink.send(Message::text(frame.to_string())).await {
+                                warn!("Failed to send on agent transport: {}", e);
+                                break;
+                            }
+                        }
+                        message = source.next() => match message {
+                            Some(Ok(Message::Text(text))) => {
+                                if let Some(frame) = route_frame(&routes, &text) {
+                                    if let Err(e) = app.emit("agent-push", &frame) {
+                                        warn!("Failed to forward agent push: {}", e);
+                                    }
+                                }
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                warn!("Agent transport error: {}", e);
+                                break;
+                            }
+                        },
+                        _ = ping.tick() => {
+                            if sink.send(Message::Ping(Default::default())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                routes.fail_all("Agent connection was lost");
+                warn!("Agent WebSocket transport disconnected, reconnecting in {:?}", delay);
+            }
+            Err(e) => warn!("Agent WebSocket transport connection failed: {}", e),
+        }
+
+        // Nothing can be answered until reconnected, so requests fail fast meanwhile
+        let backoff = tokio::time::sleep(delay);
+        tokio::pin!(backoff);
+        loop {
+            tokio::select! {
+                _ = &mut backoff => break,
+                frame = rx.recv() => match frame {
+                    Some(frame) => {
+                        if let Some(id) = frame["id"].as_str() {
+                            let error = "Agent is not connected".to_string();
+                            routes.respond(id, Err(SlovoError::AgentConnection(error)));
+                        }
+                    }
+                    None => return,
+                },
+            }
+        }
+        delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+    }
+}
+
+/// Resolve a response frame, or publish a frame the agent pushed unprompted
+///
+/// Returns the pushed frame, for forwarding to the frontend.
+fn route_frame(routes: &Routes, text: &str) -> Option<Value> {
+    let frame: Value = match serde_json::from_str(text) {
+        Ok(frame) => frame,
+        Err(e) => {
+            warn!("Ignoring unreadable agent frame: {}", e);
+            return None;
+        }
+    };
+    let kind = frame["type"].as_str().unwrap_or_default().to_string();
+
+    if let Some(id) = frame["id"].as_str() {
+        let result = if kind == "error" {
+            let message = frame["message"].as_str().unwrap_or("agent error").to_string();
+            let code = frame["code"].as_str().map(|code| code.to_string());
+            Err(SlovoError::AgentError { code, message })
+        } else {
+            Ok(frame.clone())
+        };
+        if routes.respond(id, result) {
+            return None;
+        }
+    }
+
+    routes.publish(&kind, &frame);
+    Some(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn await_response(routes: &Routes, id: &str) -> oneshot::Receiver<Result<Value, SlovoError>> {
+        let (responder, response) = oneshot::channel();
+        routes.pending.lock().unwrap().insert(id.to_string(), responder);
+        response
+    }
+
+    fn subscribe(routes: &Routes, kind: &str) -> mpsc::UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        routes.subscribers.lock().unwrap().entry(kind.to_string()).or_default().push(tx);
+        rx
+    }
+
+    #[test]
+    fn respond_resolves_only_the_matching_request() {
+        let routes = Routes::default();
+        let mut response = await_response(&routes, "req-1");
+
+        assert!(!routes.respond("req-2", Ok(json!({}))));
+        assert!(routes.respond("req-1", Ok(json!({ "status": "healthy" }))));
+        // Each request is answered once
+        assert!(!routes.respond("req-1", Ok(json!({}))));
+
+        assert_eq!(response.try_recv().unwrap().unwrap()["status"], "healthy");
+    }
+
+    #[test]
+    fn fail_all_fails_every_waiting_request() {
+        let routes = Routes::default();
+        let mut first = await_response(&routes, "req-1");
+        let mut second = await_response(&routes, "req-2");
+
+        routes.fail_all("Agent connection was lost");
+
+        for response in [&mut first, &mut second] {
+            let error = response.try_recv().unwrap().unwrap_err();
+            assert!(matches!(error, SlovoError::AgentConnection(_)), "{:?}", error);
+        }
+        assert!(routes.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn publish_reaches_subscribers_of_the_type_and_drops_closed_ones() {
+        let routes = Routes::default();
+        let mut tasks = subscribe(&routes, "task_done");
+        let mut other = subscribe(&routes, "other");
+        drop(subscribe(&routes, "task_done"));
+
+        routes.publish("task_done", &json!({ "type": "task_done" }));
+
+        assert_eq!(tasks.try_recv().unwrap()["type"], "task_done");
+        assert!(other.try_recv().is_err());
+        assert_eq!(routes.subscribers.lock().unwrap()["task_done"].len(), 1);
+    }
+
+    #[test]
+    fn route_frame_answers_the_request_with_its_id() {
+        let routes = Routes::default();
+        let mut response = await_response(&routes, "req-1");
+
+        let pushed = route_frame(&routes, r#"{"id":"req-1","type":"chat","response":"hi"}"#);
+
+        assert!(pushed.is_none());
+        assert_eq!(response.try_recv().unwrap().unwrap()["response"], "hi");
+    }
+
+    #[test]
+    fn route_frame_turns_error_frames_into_agent_errors() {
+        let routes = Routes::default();
+        let mut response = await_response(&routes, "req-1");
+
+        let frame = r#"{"id":"req-1","type":"error","code":"busy","message":"try later"}"#;
+        route_frame(&routes, frame);
+
+        match response.try_recv().unwrap() {
+            Err(SlovoError::AgentError { code, message }) => {
+                assert_eq!(code.as_deref(), Some("busy"));
+                assert_eq!(message, "try later");
+            }
+            other => panic!("expected AgentError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn route_frame_publishes_pushes_and_unmatched_frames() {
+        let routes = Routes::default();
+        let mut tasks = subscribe(&routes, "task_done");
+
+        let pushed = route_frame(&routes, r#"{"type":"task_done","task":"index"}"#).unwrap();
+        let unmatched = route_frame(&routes, r#"{"id":"gone","type":"task_done"}"#).unwrap();
+
+        assert_eq!(pushed["task"], "index");
+        assert_eq!(unmatched["id"], "gone");
+        assert_eq!(tasks.try_recv().unwrap(), pushed);
+        assert_eq!(tasks.try_recv().unwrap(), unmatched);
+        assert!(route_frame(&routes, "not json").is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! `AgentClient` against a mock agent over real HTTP
+
+mod mock_agent;
+
+use std::time::Duration;
+
+use mock_agent::{refused_config, Behavior, MockAgentServer};
+use slovo_lib::agent::{AgentClientBuilder, ChatOptions, ChatRequest};
+use slovo_lib::SlovoError;
+
+#[tokio::test]
+async fn health_check_reports_healthy_agent() {
+    let server = MockAgentServer::spawn().await;
+
+    let health = server.client().health_check().await.unwrap();
+
+    assert_eq!(health.status, "healthy");
+    assert_eq!(health.version, "0.2.0");
+    assert!(health.supports("chat"));
+}
+
+#[tokio::test]
+async fn health_check_parses_degraded_status() {
+    let server = MockAgentServer::spawn_with(Behavior::Degraded).await;
+
+    let health = server.client().health_check().await.unwrap();
+
+    assert_eq!(health.status, "degraded");
+}
+
+#[tokio::test]
+async fn chat_returns_agent_reply() {
+    let server = MockAgentServer::spawn().await;
+
+    let response = server
+        .client()
+        .send_message("hello", Some("conv-7"), &ChatOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(response.response, "echo: hello");
+    assert_eq!(response.conversation_id, "conv-7");
+    assert_eq!(server.chat_requests(), 1);
+}
+
+#[tokio::test]
+async fn ephemeral_chat_is_flagged_and_keeps_the_conversation() {
+    let server = MockAgentServer::spawn().await;
+    let client = server.client();
+
+    client.send_message("hello", Some("conv-7"), &ChatOptions::default()).await.unwrap();
+    let normal = server.last_chat_body().unwrap();
+    let options = ChatOptions {
+        ephemeral: true,
+        ..ChatOptions::default()
+    };
+    client.send_message("title?", Some("conv-7"), &options).await.unwrap();
+    let ephemeral = server.last_chat_body().unwrap();
+
+    assert!(normal.get("ephemeral").is_none());
+    assert_eq!(ephemeral["ephemeral"], true);
+    assert_eq!(ephemeral["conversation_id"], "conv-7");
+}
+
+#[tokio::test]
+async fn chat_maps_429_to_rate_limit_error() {
+    let server = MockAgentServer::spawn_with(Behavior::RateLimited { retry_after: None }).await;
+
+    let error = server
+        .client()
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, SlovoError::RateLimitError { retry_after_ms: None }), "{:?}", error);
+}
+
+#[tokio::test]
+async fn chat_429_carries_retry_after() {
+    let retry_after = Some("2".to_string());
+    let server = MockAgentServer::spawn_with(Behavior::RateLimited { retry_after }).await;
+
+    let error = server
+        .client()
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(error, SlovoError::RateLimitError { retry_after_ms: Some(2_000) }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn chat_maps_500_to_agent_error_with_code() {
+    let server = MockAgentServer::spawn_with(Behavior::ServerError).await;
+
+    let error = server
+        .client()
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    match error {
+        SlovoError::AgentError { code, message } => {
+            assert_eq!(code.as_deref(), Some("internal"));
+            assert_eq!(message, "model crashed");
+        }
+        other => panic!("expected AgentError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn refused_connection_is_a_connection_error() {
+    let client = AgentClientBuilder::from_config(&refused_config().await).build().unwrap();
+
+    let health = client.health_check().await.unwrap_err();
+    let chat = client
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(health, SlovoError::AgentConnection(_)), "{:?}", health);
+    assert!(matches!(chat, SlovoError::AgentConnection(_)), "{:?}", chat);
+}
+
+#[tokio::test]
+async fn disconnect_mid_response_is_a_connection_error() {
+    let server = MockAgentServer::spawn_with(Behavior::DisconnectMidResponse).await;
+
+    let error = server
+        .client()
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, SlovoError::AgentConnection(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn response_one_byte_over_the_cap_is_rejected() {
+    let body = r#"{"id":"resp-1","response":"hi","conversation_id":"conv-1"}"#;
+    let server = MockAgentServer::spawn_with(Behavior::ChatBody(body.to_string())).await;
+    let send = |limit: usize| {
+        let client = server.builder().max_response_bytes(limit).build().unwrap();
+        async move { client.send_message("hello", None, &ChatOptions::default()).await }
+    };
+
+    let at_limit = send(body.len()).await.unwrap();
+    let over_limit = send(body.len() - 1).await.unwrap_err();
+
+    assert_eq!(at_limit.response, "hi");
+    match over_limit {
+        SlovoError::AgentError { message, .. } => assert_eq!(message, "response too large"),
+        other => panic!("expected AgentError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn slow_agent_is_a_timeout_not_a_connection_error() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_secs(2))).await;
+    let client = server.builder().timeout(Duration::from_millis(200)).build().unwrap();
+
+    let error = client
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, SlovoError::Timeout(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn new_request_timeout_applies_to_every_clone() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_secs(3))).await;
+    let client = server.client();
+    let clone = client.clone();
+
+    client.set_request_timeout(1).unwrap();
+    let error = clone
+        .send_message("hello", None, &ChatOptions::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(clone.request_timeout(), Duration::from_secs(1));
+    assert!(matches!(error, SlovoError::Timeout(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn out_of_range_request_timeout_keeps_the_old_one() {
+    let client = MockAgentServer::spawn().await.client();
+    let before = client.request_timeout();
+
+    assert!(matches!(client.set_request_timeout(0), Err(SlovoError::ConfigError(_))));
+    assert!(client.set_request_timeout(601).is_err());
+    assert_eq!(client.request_timeout(), before);
+}
+
+#[tokio::test]
+async fn send_batch_keeps_within_max_concurrent_requests() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_millis(100))).await;
+    let client = server.builder().max_concurrent_requests(2).build().unwrap();
+    let requests = (0..6)
+        .map(|i| ChatRequest::new(format!("message {}", i), None, &ChatOptions::default()))
+        .collect();
+
+    let results = client.send_batch(requests).await;
+
+    assert_eq!(server.chat_requests(), 6);
+    assert_eq!(server.peak_concurrency(), 2);
+    for (i, result) in results.into_iter().enumerate() {
+        assert_eq!(result.unwrap().response, format!("echo: message {}", i));
+    }
+}
@@ -0,0 +1,87 @@
+//! `RequestDeduplicator` in front of real sends to a mock agent
+
+mod mock_agent;
+
+use std::time::Duration;
+
+use mock_agent::{Behavior, MockAgentServer};
+use serde_json::json;
+use slovo_lib::agent::{AgentClient, ChatOptions};
+use slovo_lib::commands::{CommandResponse, RequestDeduplicator, SendMessageResponse};
+
+async fn send(client: &AgentClient, message: &str) -> CommandResponse<SendMessageResponse> {
+    match client.send_message(message, None, &ChatOptions::default()).await {
+        Ok(response) => CommandResponse::ok(SendMessageResponse::Sent(response.into())),
+        Err(e) => CommandResponse::from_error(e),
+    }
+}
+
+#[tokio::test]
+async fn concurrent_duplicates_make_one_request() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_millis(200))).await;
+    let client = server.client();
+    let deduplicator = RequestDeduplicator::new(Duration::from_secs(30));
+
+    let (first, second) = tokio::join!(
+        deduplicator.run("hello", Some("conv-1"), || send(&client, "hello")),
+        deduplicator.run("hello", Some("conv-1"), || send(&client, "hello")),
+    );
+
+    assert!(first.success && second.success);
+    assert_eq!(server.chat_requests(), 1);
+}
+
+#[tokio::test]
+async fn repeat_within_the_window_gets_the_same_reply() {
+    let server = MockAgentServer::spawn().await;
+    let client = server.client();
+    let deduplicator = RequestDeduplicator::new(Duration::from_secs(30));
+
+    let first = deduplicator.run("hello", None, || send(&client, "hello")).await;
+    let repeat = deduplicator.run("hello", None, || send(&client, "hello")).await;
+
+    assert!(first.success);
+    assert_eq!(server.chat_requests(), 1);
+    assert_eq!(json!(repeat), json!(first));
+}
+
+#[tokio::test]
+async fn repeat_after_the_window_is_sent_again() {
+    let server = MockAgentServer::spawn().await;
+    let client = server.client();
+    let deduplicator = RequestDeduplicator::new(Duration::from_millis(100));
+
+    deduplicator.run("hello", None, || send(&client, "hello")).await;
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let repeat = deduplicator.run("hello", None, || send(&client, "hello")).await;
+
+    assert!(repeat.success);
+    assert_eq!(server.chat_requests(), 2);
+}
+
+#[tokio::test]
+async fn failed_send_is_retried_within_the_window() {
+    let server = MockAgentServer::spawn_with(Behavior::ServerError).await;
+    let client = server.client();
+    let deduplicator = RequestDeduplicator::new(Duration::from_secs(30));
+
+    deduplicator.run("hello", None, || send(&client, "hello")).await;
+    let retry = deduplicator.run("hello", None, || send(&client, "hello")).await;
+
+    assert!(!retry.success);
+    assert_eq!(server.chat_requests(), 2);
+}
+
+#[tokio::test]
+async fn different_messages_are_not_collapsed() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_millis(200))).await;
+    let client = server.client();
+    let deduplicator = RequestDeduplicator::new(Duration::from_secs(30));
+
+    tokio::join!(
+        deduplicator.run("hello", None, || send(&client, "hello")),
+        deduplicator.run("goodbye", None, || send(&client, "goodbye")),
+    );
+
+    assert_eq!(server.chat_requests(), 2);
+}
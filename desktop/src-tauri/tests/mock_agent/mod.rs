@@ -0,0 +1,215 @@
+//! In-process stand-in for the agent runtime
+//!
+//! Serves canned `/health` and `/api/v1/chat` responses from a random local
+//! port, so `AgentClient` can be tested without a real agent.
+
+// Each test binary uses a different part of the harness
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use slovo_lib::agent::{AgentClient, AgentClientBuilder};
+use slovo_lib::config::AgentConfig;
+use tokio::net::TcpListener;
+
+/// How the mock agent answers
+#[derive(Debug, Clone)]
+pub enum Behavior {
+    /// Healthy, and echoes chat messages back
+    Healthy,
+    /// Reports `degraded` health, and still answers chat
+    Degraded,
+    /// Answers chat with 429, with this `Retry-After` value if given
+    RateLimited { retry_after: Option<String> },
+    /// Answers chat with 500
+    ServerError,
+    /// Starts a chat response, then drops the connection partway through the body
+    DisconnectMidResponse,
+    /// Echoes chat messages after this delay
+    Slow(Duration),
+    /// Answers chat with this exact body
+    ChatBody(String),
+}
+
+/// Request counts seen by the mock agent
+#[derive(Default)]
+struct Stats {
+    chat_requests: AtomicUsize,
+    in_flight: AtomicUsize,
+    peak_in_flight: AtomicUsize,
+    last_chat_body: Mutex<Option<Value>>,
+}
+
+struct Shared {
+    behavior: Behavior,
+    stats: Stats,
+}
+
+/// A mock agent listening on `url` until the test's runtime shuts down
+pub struct MockAgentServer {
+    pub url: String,
+    pub port: u16,
+    shared: Arc<Shared>,
+}
+
+impl MockAgentServer {
+    /// Start a healthy mock agent
+    pub async fn spawn() -> Self {
+        Self::spawn_with(Behavior::Healthy).await
+    }
+
+    /// Start a mock agent that answers as `behavior` describes
+    pub async fn spawn_with(behavior: Behavior) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let shared = Arc::new(Shared {
+            behavior,
+            stats: Stats::default(),
+        });
+
+        let router = Router::new()
+            .route("/health", get(health))
+            .route("/api/v1/chat", post(chat))
+            .with_state(shared.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self {
+            url: format!("http://127.0.0.1:{}", port),
+            port,
+            shared,
+        }
+    }
+
+    /// Agent config pointing at this server
+    pub fn config(&self) -> AgentConfig {
+        AgentConfig {
+            host: "127.0.0.1".to_string(),
+            port: self.port,
+            ..AgentConfig::default()
+        }
+    }
+
+    /// Client builder pointing at this server, for tests that need extra settings
+    pub fn builder(&self) -> AgentClientBuilder {
+        AgentClientBuilder::from_config(&self.config())
+    }
+
+    /// Client pointing at this server
+    pub fn client(&self) -> AgentClient {
+        self.builder().build().unwrap()
+    }
+
+    /// Chat requests received so far
+    pub fn chat_requests(&self) -> usize {
+        self.shared.stats.chat_requests.load(Ordering::SeqCst)
+    }
+
+    /// JSON body of the latest chat request
+    pub fn last_chat_body(&self) -> Option<Value> {
+        self.shared.stats.last_chat_body.lock().unwrap().clone()
+    }
+
+    /// Most chat requests that were being handled at once
+    pub fn peak_concurrency(&self) -> usize {
+        self.shared.stats.peak_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+/// Agent config for a local port nothing is listening on
+pub async fn refused_config() -> AgentConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    AgentConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        ..AgentConfig::default()
+    }
+}
+
+async fn health(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    let status = match shared.behavior {
+        Behavior::Degraded => "degraded",
+        _ => "healthy",
+    };
+    Json(json!({
+        "status": status,
+        "version": "0.2.0",
+        "uptime": 12.5,
+        "capabilities": ["chat"],
+    }))
+}
+
+async fn chat(State(shared): State<Arc<Shared>>, Json(request): Json<Value>) -> Response {
+    let stats = &shared.stats;
+    stats.chat_requests.fetch_add(1, Ordering::SeqCst);
+    *stats.last_chat_body.lock().unwrap() = Some(request.clone());
+    let in_flight = stats.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+    stats.peak_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+    let response = respond(&shared.behavior, &request).await;
+    stats.in_flight.fetch_sub(1, Ordering::SeqCst);
+    response
+}
+
+async fn respond(behavior: &Behavior, request: &Value) -> Response {
+    match behavior {
+        Behavior::Healthy | Behavior::Degraded => echo(request).into_response(),
+        Behavior::Slow(delay) => {
+            tokio::time::sleep(*delay).await;
+            echo(request).into_response()
+        }
+        Behavior::RateLimited { retry_after } => {
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({ "error": { "code": "rate_limited", "message": "slow down" } })),
+            )
+                .into_response();
+            if let Some(value) = retry_after {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, value.parse().unwrap());
+            }
+            response
+        }
+        Behavior::ServerError => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": { "code": "internal", "message": "model crashed" } })),
+        )
+            .into_response(),
+        Behavior::DisconnectMidResponse => {
+            let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+                Ok(Bytes::from_static(br#"{"id":"resp-1","respo"#)),
+                Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "agent died")),
+            ];
+            Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from_stream(futures_util::stream::iter(chunks)))
+                .unwrap()
+        }
+        Behavior::ChatBody(body) => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.clone()))
+            .unwrap(),
+    }
+}
+
+fn echo(request: &Value) -> Json<Value> {
+    let message = request["message"].as_str().unwrap_or_default();
+    let conversation_id = request["conversation_id"].as_str().unwrap_or("conv-1");
+    Json(json!({
+        "id": "resp-1",
+        "response": format!("echo: {}", message),
+        "conversation_id": conversation_id,
+    }))
+}
@@ -0,0 +1,55 @@
+//! Draining in-flight agent requests before exit
+
+mod mock_agent;
+
+use std::time::Duration;
+
+use mock_agent::{Behavior, MockAgentServer};
+use slovo_lib::agent::ChatOptions;
+use slovo_lib::shutdown::InFlightRequests;
+
+#[tokio::test]
+async fn drain_waits_for_a_slow_request_to_complete() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_millis(300))).await;
+    let client = server.client();
+    let requests = InFlightRequests::default();
+
+    let in_flight = requests.track();
+    let request = tokio::spawn(async move {
+        let _in_flight = in_flight;
+        client.send_message("hello", None, &ChatOptions::default()).await
+    });
+
+    assert!(requests.drain(Duration::from_secs(5)).await);
+    assert_eq!(requests.count(), 0);
+    assert_eq!(request.await.unwrap().unwrap().response, "echo: hello");
+}
+
+#[tokio::test]
+async fn drain_gives_up_after_the_timeout() {
+    let server = MockAgentServer::spawn_with(Behavior::Slow(Duration::from_secs(10))).await;
+    let client = server.client();
+    let requests = InFlightRequests::default();
+
+    let in_flight = requests.track();
+    let request = tokio::spawn(async move {
+        let _in_flight = in_flight;
+        client.send_message("hello", None, &ChatOptions::default()).await
+    });
+
+    assert!(!requests.drain(Duration::from_millis(200)).await);
+    assert_eq!(requests.count(), 1);
+
+    // Exiting drops the request, which releases its place in the count
+    request.abort();
+    assert!(request.await.unwrap_err().is_cancelled());
+    assert_eq!(requests.count(), 0);
+}
+
+#[tokio::test]
+async fn drain_returns_at_once_with_nothing_in_flight() {
+    let requests = InFlightRequests::default();
+    drop(requests.track());
+
+    assert!(requests.drain(Duration::ZERO).await);
+}
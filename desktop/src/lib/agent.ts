@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 const AGENT_BASE_URL = 'http://127.0.0.1:8741';
 
@@ -90,3 +91,44 @@ export async function* streamChatMessage(
 export async function processVoiceInput(audioData: ArrayBuffer): Promise<string> {
   return invoke<string>('process_voice_input', { audioData: Array.from(new Uint8Array(audioData)) });
 }
+
+export interface MonitorHeartbeat {
+  /** Unix time in milliseconds */
+  timestamp: number;
+}
+
+/**
+ * Watch the backend health monitor's heartbeats
+ *
+ * Calls `onChange(false)` once no heartbeat has arrived for `staleAfterMs`
+ * (the agent status shown is then stale) and `onChange(true)` when they resume.
+ * Keep `staleAfterMs` a few times the health check interval.
+ */
+export async function watchMonitorHeartbeat(
+  onChange: (healthy: boolean) => void,
+  staleAfterMs = 30_000,
+): Promise<UnlistenFn> {
+  let healthy = true;
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  const arm = () => {
+    clearTimeout(timer);
+    timer = setTimeout(() => {
+      healthy = false;
+      onChange(false);
+    }, staleAfterMs);
+  };
+
+  const unlisten = await listen<MonitorHeartbeat>('monitor-heartbeat', () => {
+    if (!healthy) {
+      healthy = true;
+      onChange(true);
+    }
+    arm();
+  });
+  arm();
+
+  return () => {
+    clearTimeout(timer);
+    unlisten();
+  };
+}